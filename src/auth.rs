@@ -0,0 +1,288 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{MatchedPath, Request, State},
+    http::{header::AUTHORIZATION, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use utoipa::ToSchema;
+
+use crate::config::Config;
+
+const API_KEY_HEADER: &str = "x-api-key";
+/// Same limit as axum's `Json` extractor, so buffering for the audit log rejects nothing new.
+const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+/// Request fields that hold credentials and are never written to the audit log.
+const SECRET_FIELDS: [&str; 2] = ["mysql_url", "mongodb_uri"];
+
+/// Roles in increasing order of privilege; each one includes the ones before it.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Reader,
+    Writer,
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Reader => "reader",
+            Role::Writer => "writer",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+impl std::str::FromStr for Role {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "reader" => Ok(Role::Reader),
+            "writer" => Ok(Role::Writer),
+            "admin" => Ok(Role::Admin),
+            _ => Err(format!(
+                "unknown role \"{}\", expected reader, writer or admin",
+                value
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKey {
+    /// Who the key belongs to, as written to the audit log.
+    pub name: String,
+    pub key: String,
+    pub role: Role,
+}
+
+/// The authenticated client, available to handlers as an extension.
+#[derive(Debug, Serialize, Clone)]
+pub struct Caller {
+    pub name: String,
+    pub role: Role,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct AuditEntry {
+    pub at: NaiveDateTime,
+    pub caller: String,
+    pub role: Role,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    /// Request body with credentials removed.
+    pub request: Value,
+}
+
+/// Role required for a route, `None` for public ones. Routes not listed need `Writer`, and
+/// `Admin` when the request deletes rows.
+pub fn required_role(method: &Method, route: &str) -> Option<Role> {
+    match route {
+        "/health" | "/openapi.json" | "/docs" => None,
+        "/data" | "/data/geojson" | "/validate" | "/runs" | "/runs/{run_id}" => Some(Role::Reader),
+        "/stream" | "/stream/{id}" if method == Method::GET => Some(Role::Reader),
+        "/clear" | "/runs/{run_id}/rollback" | "/audit" => Some(Role::Admin),
+        route if route.starts_with("/connections") => Some(Role::Admin),
+        _ => Some(Role::Writer),
+    }
+}
+
+/// Whether a request to a `Writer` route asks to delete rows, which needs `Admin` like `/clear`.
+fn deletes_rows(route: &str, body: &Value) -> bool {
+    let field = match route {
+        "/mutate" => "delete_percent",
+        "/stream" => "delete_ratio",
+        _ => return false,
+    };
+    body.get(field)
+        .and_then(Value::as_f64)
+        .is_some_and(|share| share > 0.0)
+}
+
+fn forbidden(required: Role) -> Response {
+    (
+        StatusCode::FORBIDDEN,
+        format!("❌ This endpoint requires the {} role", required.as_str()),
+    )
+        .into_response()
+}
+
+/// Compares in time independent of where the inputs differ.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if SECRET_FIELDS.contains(&key.as_str()) && !field.is_null() {
+                    *field = Value::from("****");
+                } else {
+                    redact(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+#[derive(Clone)]
+pub struct Auth {
+    keys: Arc<Vec<ApiKey>>,
+    audit_log: Arc<Mutex<PathBuf>>,
+}
+
+impl Auth {
+    pub fn new(config: &Config) -> Self {
+        Auth {
+            keys: Arc::new(config.api_keys.clone()),
+            audit_log: Arc::new(Mutex::new(config.audit_log.clone())),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        !self.keys.is_empty()
+    }
+
+    /// Resolves the caller from `Authorization: Bearer <key>` or `X-API-Key: <key>`.
+    /// Without configured keys every request is treated as an anonymous admin.
+    fn caller(&self, request: &Request) -> Option<Caller> {
+        if !self.enabled() {
+            return Some(Caller {
+                name: "anonymous".to_string(),
+                role: Role::Admin,
+            });
+        }
+        let headers = request.headers();
+        let key = headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .or_else(|| {
+                headers
+                    .get(API_KEY_HEADER)
+                    .and_then(|value| value.to_str().ok())
+            })?;
+        self.keys
+            .iter()
+            .find(|api_key| constant_time_eq(api_key.key.as_bytes(), key.trim().as_bytes()))
+            .map(|api_key| Caller {
+                name: api_key.name.clone(),
+                role: api_key.role,
+            })
+    }
+
+    async fn record(&self, entry: &AuditEntry) {
+        let path = self.audit_log.lock().await;
+        let line = match serde_json::to_string(entry) {
+            Ok(line) => line + "\n",
+            Err(e) => {
+                eprintln!("❌ Failed to serialize audit entry: {}", e);
+                return;
+            }
+        };
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&*path)
+            .await;
+        let written = match file {
+            Ok(mut file) => file.write_all(line.as_bytes()).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = written {
+            eprintln!("❌ Failed to write audit log {}: {}", path.display(), e);
+        }
+    }
+
+    pub async fn entries(&self, limit: usize) -> Result<Vec<AuditEntry>, String> {
+        let path = self.audit_log.lock().await;
+        let content = match tokio::fs::read_to_string(&*path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.to_string()),
+        };
+        let lines: Vec<&str> = content.lines().collect();
+        lines[lines.len().saturating_sub(limit)..]
+            .iter()
+            .map(|line| serde_json::from_str(line).map_err(|e| e.to_string()))
+            .collect()
+    }
+}
+
+/// Checks the caller's role against the route and writes state-changing requests
+/// to the audit log. Must be added with `route_layer` so the matched path is known.
+pub async fn authorize(State(auth): State<Auth>, mut request: Request, next: Next) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_default();
+    let method = request.method().clone();
+    let Some(required) = required_role(&method, &route) else {
+        return next.run(request).await;
+    };
+
+    let Some(caller) = auth.caller(&request) else {
+        return (
+            StatusCode::UNAUTHORIZED,
+            "❌ Missing or invalid API key".to_string(),
+        )
+            .into_response();
+    };
+    if caller.role < required {
+        return forbidden(required);
+    }
+    request.extensions_mut().insert(caller.clone());
+
+    if required == Role::Reader || method == Method::GET {
+        return next.run(request).await;
+    }
+
+    let path = request.uri().path().to_string();
+    let (parts, body) = request.into_parts();
+    let bytes = match to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            return (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "❌ Request body is too large".to_string(),
+            )
+                .into_response()
+        }
+    };
+    let mut logged_body = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+    if caller.role < Role::Admin && deletes_rows(&route, &logged_body) {
+        return forbidden(Role::Admin);
+    }
+    redact(&mut logged_body);
+
+    let response = next
+        .run(Request::from_parts(parts, Body::from(bytes)))
+        .await;
+    auth.record(&AuditEntry {
+        at: Utc::now().naive_utc(),
+        caller: caller.name,
+        role: caller.role,
+        method: method.to_string(),
+        path,
+        status: response.status().as_u16(),
+        request: logged_body,
+    })
+    .await;
+    response
+}
@@ -0,0 +1,521 @@
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+use http::header::HeaderValue;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::auth::{ApiKey, Role};
+use crate::db::clear::DEFAULT_CHUNK_SIZE;
+use crate::db::schema::{table_schema, TableName};
+use crate::generator::documents::DocumentShape;
+use crate::generator::pattern::check_column;
+
+const DEFAULT_CONFIG_FILE: &str = "datagen.toml";
+const ENV_PREFIX: &str = "DATAGEN_";
+pub const DEFAULT_CONNECTION: &str = "default";
+const MIN_API_KEY_LENGTH: usize = 16;
+
+/// Server settings, layered from lowest to highest priority:
+/// built-in defaults, the TOML config file, environment variables and command-line flags.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub bind_address: String,
+    pub cors_origins: Vec<String>,
+    pub mysql_url: Option<String>,
+    pub mongodb_uri: Option<String>,
+    pub mongodb_database: String,
+    pub mysql_pool_min: usize,
+    pub mysql_pool_max: usize,
+    pub mongodb_max_pool_size: u32,
+    /// Directory with word lists (`names.txt`, `last_names.txt`, ...) replacing the built-in ones.
+    pub data_pack_dir: Option<PathBuf>,
+    /// Row count used by `/generate` when the request does not specify one.
+    pub default_count: usize,
+    /// Largest row count per table a single `/generate` request may ask for.
+    pub max_count: usize,
+    /// Lower limits for individual tables, e.g. `task = 10000`.
+    pub table_limits: BTreeMap<TableName, usize>,
+    /// Regular expressions generating text columns by default, keyed by `table.column`,
+    /// e.g. `"address.postal_code" = "[0-9]{2}-[0-9]{3}"`. Request overrides take precedence.
+    pub column_patterns: BTreeMap<String, String>,
+    /// Named MongoDB document shapes `/generate/documents` requests can refer to.
+    pub document_shapes: BTreeMap<String, DocumentShape>,
+    /// Rows removed per statement by the chunked clear strategy.
+    pub clear_chunk_size: usize,
+    /// Databases requests may target on the default connection besides its own.
+    pub allowed_databases: Vec<String>,
+    /// Additional named connections requests can target.
+    pub connections: BTreeMap<String, ConnectionConfig>,
+    /// File connections registered through the API are saved to.
+    pub connections_file: PathBuf,
+    /// Keys accepted by the API; authentication is disabled when empty.
+    pub api_keys: Vec<ApiKey>,
+    /// JSON-lines file recording who changed or deleted data.
+    pub audit_log: PathBuf,
+}
+
+/// URLs and pool settings of one named connection.
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConnectionConfig {
+    pub mysql_url: Option<String>,
+    pub mongodb_uri: Option<String>,
+    pub mongodb_database: String,
+    pub mysql_pool_min: usize,
+    pub mysql_pool_max: usize,
+    pub mongodb_max_pool_size: u32,
+    /// Databases requests may target besides the connection's own.
+    pub allowed_databases: Vec<String>,
+}
+
+impl Default for ConnectionConfig {
+    fn default() -> Self {
+        let defaults = Config::default();
+        ConnectionConfig {
+            mysql_url: None,
+            mongodb_uri: None,
+            mongodb_database: defaults.mongodb_database,
+            mysql_pool_min: defaults.mysql_pool_min,
+            mysql_pool_max: defaults.mysql_pool_max,
+            mongodb_max_pool_size: defaults.mongodb_max_pool_size,
+            allowed_databases: Vec::new(),
+        }
+    }
+}
+
+/// MySQL schema and Mongo database names are restricted to letters, digits, `_` and `-`.
+pub fn is_valid_database_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+impl ConnectionConfig {
+    pub fn validate(&self, name: &str) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        let is_set = |value: &Option<String>| value.as_deref().is_some_and(|v| !v.is_empty());
+        if !is_set(&self.mysql_url) && !is_set(&self.mongodb_uri) {
+            errors.push(format!(
+                "connection \"{}\": no backend configured, set mysql_url and/or mongodb_uri",
+                name
+            ));
+        }
+        if !is_valid_database_name(&self.mongodb_database) {
+            errors.push(format!(
+                "connection \"{}\": mongodb_database \"{}\" is not a valid database name",
+                name, self.mongodb_database
+            ));
+        }
+        for database in &self.allowed_databases {
+            if !is_valid_database_name(database) {
+                errors.push(format!(
+                    "connection \"{}\": allowed database \"{}\" is not a valid database name",
+                    name, database
+                ));
+            }
+        }
+        if self.mysql_pool_max == 0 || self.mysql_pool_min > self.mysql_pool_max {
+            errors.push(format!(
+                "connection \"{}\": mysql_pool_min ({}) must not exceed mysql_pool_max ({}), which must be positive",
+                name, self.mysql_pool_min, self.mysql_pool_max
+            ));
+        }
+        if self.mongodb_max_pool_size == 0 {
+            errors.push(format!(
+                "connection \"{}\": mongodb_max_pool_size must be positive",
+                name
+            ));
+        }
+
+        errors
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            bind_address: "0.0.0.0:3000".to_string(),
+            cors_origins: vec!["http://localhost:5173".to_string()],
+            mysql_url: None,
+            mongodb_uri: None,
+            mongodb_database: "soft".to_string(),
+            mysql_pool_min: 10,
+            mysql_pool_max: 100,
+            mongodb_max_pool_size: 10,
+            data_pack_dir: None,
+            default_count: 100,
+            max_count: 100_000,
+            table_limits: BTreeMap::new(),
+            column_patterns: BTreeMap::new(),
+            document_shapes: BTreeMap::new(),
+            clear_chunk_size: DEFAULT_CHUNK_SIZE,
+            allowed_databases: Vec::new(),
+            connections: BTreeMap::new(),
+            connections_file: PathBuf::from("connections.json"),
+            api_keys: Vec::new(),
+            audit_log: PathBuf::from("audit.log"),
+        }
+    }
+}
+
+impl Config {
+    /// Builds the configuration from all layers and validates it.
+    /// Every problem found is reported, not only the first one.
+    pub fn load() -> Result<Config, Vec<String>> {
+        if let Err(e) = dotenvy::dotenv() {
+            if !e.not_found() {
+                return Err(vec![format!("Failed to load .env file: {}", e)]);
+            }
+        }
+
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let flags = parse_flags(&args).map_err(|e| vec![e])?;
+
+        let config_path = flag_value(&flags, "config").map(PathBuf::from).or_else(|| {
+            dotenvy::var(format!("{}CONFIG", ENV_PREFIX))
+                .ok()
+                .map(PathBuf::from)
+        });
+        let mut config = match config_path {
+            Some(path) => Config::from_file(&path).map_err(|e| vec![e])?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Config::from_file(Path::new(DEFAULT_CONFIG_FILE)).map_err(|e| vec![e])?
+            }
+            None => Config::default(),
+        };
+
+        let mut errors = Vec::new();
+        config.apply_env(&mut errors);
+        config.apply_flags(&flags, &mut errors);
+        errors.extend(config.validate());
+
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn from_file(path: &Path) -> Result<Config, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config file {}: {}", path.display(), e))?;
+        toml::from_str(&content)
+            .map_err(|e| format!("Invalid config file {}: {}", path.display(), e))
+    }
+
+    fn apply_env(&mut self, errors: &mut Vec<String>) {
+        let var = |name: &str| dotenvy::var(name).ok();
+        let prefixed = |name: &str| dotenvy::var(format!("{}{}", ENV_PREFIX, name)).ok();
+
+        if let Some(value) = var("MYSQL_URL") {
+            self.mysql_url = Some(value);
+        }
+        if let Some(value) = var("MONGODB_URI") {
+            self.mongodb_uri = Some(value);
+        }
+        if let Some(value) = prefixed("BIND_ADDRESS") {
+            self.bind_address = value;
+        }
+        if let Some(value) = prefixed("CORS_ORIGINS") {
+            self.cors_origins = split_list(&value);
+        }
+        if let Some(value) = prefixed("MONGODB_DATABASE") {
+            self.mongodb_database = value;
+        }
+        if let Some(value) = prefixed("ALLOWED_DATABASES") {
+            self.allowed_databases = split_list(&value);
+        }
+        if let Some(value) = prefixed("CONNECTIONS_FILE") {
+            self.connections_file = PathBuf::from(value);
+        }
+        if let Some(value) = prefixed("API_KEYS") {
+            match parse_api_keys(&value) {
+                Ok(keys) => self.api_keys = keys,
+                Err(e) => errors.push(format!("DATAGEN_API_KEYS: {}", e)),
+            }
+        }
+        if let Some(value) = prefixed("AUDIT_LOG") {
+            self.audit_log = PathBuf::from(value);
+        }
+        if let Some(value) = prefixed("DATA_PACK_DIR") {
+            self.data_pack_dir = Some(PathBuf::from(value));
+        }
+        set_number(
+            &mut self.mysql_pool_min,
+            "DATAGEN_MYSQL_POOL_MIN",
+            prefixed("MYSQL_POOL_MIN"),
+            errors,
+        );
+        set_number(
+            &mut self.mysql_pool_max,
+            "DATAGEN_MYSQL_POOL_MAX",
+            prefixed("MYSQL_POOL_MAX"),
+            errors,
+        );
+        set_number(
+            &mut self.mongodb_max_pool_size,
+            "DATAGEN_MONGODB_MAX_POOL_SIZE",
+            prefixed("MONGODB_MAX_POOL_SIZE"),
+            errors,
+        );
+        set_number(
+            &mut self.default_count,
+            "DATAGEN_DEFAULT_COUNT",
+            prefixed("DEFAULT_COUNT"),
+            errors,
+        );
+        set_number(
+            &mut self.max_count,
+            "DATAGEN_MAX_COUNT",
+            prefixed("MAX_COUNT"),
+            errors,
+        );
+        set_number(
+            &mut self.clear_chunk_size,
+            "DATAGEN_CLEAR_CHUNK_SIZE",
+            prefixed("CLEAR_CHUNK_SIZE"),
+            errors,
+        );
+    }
+
+    fn apply_flags(&mut self, flags: &[(String, String)], errors: &mut Vec<String>) {
+        let mut cors_origins = Vec::new();
+        let mut allowed_databases = Vec::new();
+        for (name, value) in flags {
+            match name.as_str() {
+                "config" => {}
+                "bind" => self.bind_address = value.clone(),
+                "cors-origin" => cors_origins.push(value.clone()),
+                "allow-database" => allowed_databases.push(value.clone()),
+                "mysql-url" => self.mysql_url = Some(value.clone()),
+                "mongodb-uri" => self.mongodb_uri = Some(value.clone()),
+                "mongodb-database" => self.mongodb_database = value.clone(),
+                "connections-file" => self.connections_file = PathBuf::from(value),
+                "audit-log" => self.audit_log = PathBuf::from(value),
+                "data-pack-dir" => self.data_pack_dir = Some(PathBuf::from(value)),
+                "mysql-pool-min" => set_number(
+                    &mut self.mysql_pool_min,
+                    "--mysql-pool-min",
+                    Some(value.clone()),
+                    errors,
+                ),
+                "mysql-pool-max" => set_number(
+                    &mut self.mysql_pool_max,
+                    "--mysql-pool-max",
+                    Some(value.clone()),
+                    errors,
+                ),
+                "mongodb-max-pool-size" => set_number(
+                    &mut self.mongodb_max_pool_size,
+                    "--mongodb-max-pool-size",
+                    Some(value.clone()),
+                    errors,
+                ),
+                "default-count" => set_number(
+                    &mut self.default_count,
+                    "--default-count",
+                    Some(value.clone()),
+                    errors,
+                ),
+                "max-count" => set_number(
+                    &mut self.max_count,
+                    "--max-count",
+                    Some(value.clone()),
+                    errors,
+                ),
+                "clear-chunk-size" => set_number(
+                    &mut self.clear_chunk_size,
+                    "--clear-chunk-size",
+                    Some(value.clone()),
+                    errors,
+                ),
+                _ => errors.push(format!("Unknown flag --{}", name)),
+            }
+        }
+        if !cors_origins.is_empty() {
+            self.cors_origins = cors_origins;
+        }
+        if !allowed_databases.is_empty() {
+            self.allowed_databases = allowed_databases;
+        }
+    }
+
+    /// The connection described by the top-level settings.
+    pub fn default_connection(&self) -> ConnectionConfig {
+        ConnectionConfig {
+            mysql_url: self.mysql_url.clone(),
+            mongodb_uri: self.mongodb_uri.clone(),
+            mongodb_database: self.mongodb_database.clone(),
+            mysql_pool_min: self.mysql_pool_min,
+            mysql_pool_max: self.mysql_pool_max,
+            mongodb_max_pool_size: self.mongodb_max_pool_size,
+            allowed_databases: self.allowed_databases.clone(),
+        }
+    }
+
+    /// Largest row count a request may generate for the table, or for any table.
+    pub fn count_limit(&self, table: Option<TableName>) -> usize {
+        table
+            .and_then(|table| self.table_limits.get(&table))
+            .map_or(self.max_count, |limit| (*limit).min(self.max_count))
+    }
+
+    fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if self.bind_address.parse::<SocketAddr>().is_err() {
+            errors.push(format!(
+                "bind_address \"{}\" is not a valid address, expected e.g. 0.0.0.0:3000",
+                self.bind_address
+            ));
+        }
+        for origin in &self.cors_origins {
+            let valid = (origin.starts_with("http://") || origin.starts_with("https://"))
+                && origin.parse::<HeaderValue>().is_ok();
+            if !valid {
+                errors.push(format!(
+                    "cors_origins entry \"{}\" must be an http:// or https:// origin",
+                    origin
+                ));
+            }
+        }
+        errors.extend(self.default_connection().validate(DEFAULT_CONNECTION));
+        for (name, connection) in &self.connections {
+            if name == DEFAULT_CONNECTION {
+                errors.push(format!(
+                    "connection name \"{}\" is reserved for the top-level settings",
+                    DEFAULT_CONNECTION
+                ));
+            }
+            errors.extend(connection.validate(name));
+        }
+        let mut seen_keys = std::collections::HashSet::new();
+        for api_key in &self.api_keys {
+            if api_key.name.is_empty() {
+                errors.push("api_keys: every key needs a name".to_string());
+            }
+            if api_key.key.len() < MIN_API_KEY_LENGTH {
+                errors.push(format!(
+                    "api_keys: key of \"{}\" must be at least {} characters long",
+                    api_key.name, MIN_API_KEY_LENGTH
+                ));
+            }
+            if !seen_keys.insert(&api_key.key) {
+                errors.push(format!(
+                    "api_keys: key of \"{}\" is used more than once",
+                    api_key.name
+                ));
+            }
+        }
+        if self.default_count == 0 {
+            errors.push("default_count must be positive".to_string());
+        }
+        if self.max_count == 0 {
+            errors.push("max_count must be positive".to_string());
+        }
+        if self.default_count > self.count_limit(None) {
+            errors.push(format!(
+                "default_count ({}) must not exceed max_count ({})",
+                self.default_count, self.max_count
+            ));
+        }
+        for (table, limit) in &self.table_limits {
+            if *limit == 0 {
+                errors.push(format!("table_limits.{} must be positive", table.as_str()));
+            }
+        }
+        for (key, pattern) in &self.column_patterns {
+            let column = key
+                .split_once('.')
+                .and_then(|(table, column)| table_schema(table)?.column(column))
+                .ok_or_else(|| "expected table.column of a known table".to_string());
+            if let Err(e) = column.and_then(|column| check_column(pattern, column)) {
+                errors.push(format!("column_patterns.\"{}\": {}", key, e));
+            }
+        }
+        for (name, shape) in &self.document_shapes {
+            errors.extend(shape.validate(&format!("document_shapes.{}", name)));
+        }
+        if self.clear_chunk_size == 0 {
+            errors.push("clear_chunk_size must be positive".to_string());
+        }
+
+        errors
+    }
+}
+
+/// Parses `name:role:key` entries separated by commas.
+fn parse_api_keys(value: &str) -> Result<Vec<ApiKey>, String> {
+    split_list(value)
+        .iter()
+        .map(|entry| {
+            let mut parts = entry.splitn(3, ':');
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some(name), Some(role), Some(key)) => Ok(ApiKey {
+                    name: name.to_string(),
+                    role: role.parse::<Role>()?,
+                    key: key.to_string(),
+                }),
+                _ => Err("expected entries of the form name:role:key".to_string()),
+            }
+        })
+        .collect()
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn set_number<T: std::str::FromStr>(
+    target: &mut T,
+    name: &str,
+    value: Option<String>,
+    errors: &mut Vec<String>,
+) {
+    if let Some(value) = value {
+        match value.trim().parse() {
+            Ok(number) => *target = number,
+            Err(_) => errors.push(format!("{} must be a number, got \"{}\"", name, value)),
+        }
+    }
+}
+
+/// Parses `--name value` and `--name=value` pairs.
+fn parse_flags(args: &[String]) -> Result<Vec<(String, String)>, String> {
+    let mut flags = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let Some(flag) = arg.strip_prefix("--") else {
+            return Err(format!("Unexpected argument \"{}\"", arg));
+        };
+        match flag.split_once('=') {
+            Some((name, value)) => flags.push((name.to_string(), value.to_string())),
+            None => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("Missing value for --{}", flag))?;
+                flags.push((flag.to_string(), value.clone()));
+            }
+        }
+    }
+    Ok(flags)
+}
+
+fn flag_value<'a>(flags: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    flags
+        .iter()
+        .rev()
+        .find(|(flag, _)| flag == name)
+        .map(|(_, value)| value.as_str())
+}
@@ -0,0 +1,685 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use axum::http::StatusCode;
+use mongodb::bson::doc;
+use mysql_async::prelude::*;
+use mysql_async::{Opts, Pool};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use url::Url;
+use utoipa::ToSchema;
+
+use super::mongodb::connect_mongodb;
+use super::mysql::connect_mysql;
+use crate::config::{is_valid_database_name, Config, ConnectionConfig, DEFAULT_CONNECTION};
+
+/// Minimum time between two connection attempts made on behalf of requests.
+const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+/// How often the background task pings connected backends and reconnects lost ones.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+const REDACTED: &str = "****";
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DbType {
+    Mysql,
+    Mongodb,
+}
+
+impl DbType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DbType::Mysql => "mysql",
+            DbType::Mongodb => "mongodb",
+        }
+    }
+}
+
+/// Where a request reads or writes; both parts fall back to the connection's defaults.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, ToSchema)]
+pub struct Target {
+    /// Named connection from the configuration.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connection: Option<String>,
+    /// MySQL schema or Mongo database on that connection; must be allow-listed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub database: Option<String>,
+}
+
+struct ConnectionState<T> {
+    handle: Option<T>,
+    last_error: Option<String>,
+    last_attempt: Option<Instant>,
+}
+
+/// A lazily opened connection that is dropped when it stops answering and reopened on demand.
+struct Connection<T> {
+    name: String,
+    state: Mutex<ConnectionState<T>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BackendHealth {
+    pub configured: bool,
+    pub connected: bool,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ConnectionHealth {
+    pub mysql: BackendHealth,
+    pub mongodb: BackendHealth,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HealthReport {
+    pub status: &'static str,
+    pub connections: BTreeMap<String, ConnectionHealth>,
+}
+
+impl<T: Clone> Connection<T> {
+    fn new(name: String) -> Self {
+        Connection {
+            name,
+            state: Mutex::new(ConnectionState {
+                handle: None,
+                last_error: None,
+                last_attempt: None,
+            }),
+        }
+    }
+
+    /// Returns the open connection, connecting first when there is none.
+    /// A failed attempt is not retried for `RETRY_INTERVAL` unless `force` is set.
+    async fn get<F, Fut>(&self, force: bool, connect: F) -> Result<T, String>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, String>>,
+    {
+        let mut state = self.state.lock().await;
+        if let Some(handle) = &state.handle {
+            return Ok(handle.clone());
+        }
+        let retry_due = state
+            .last_attempt
+            .is_none_or(|attempt| attempt.elapsed() >= RETRY_INTERVAL);
+        if !force && !retry_due {
+            return Err(state.last_error.clone().unwrap_or_default());
+        }
+
+        println!("🔍 Connecting to {}...", self.name);
+        state.last_attempt = Some(Instant::now());
+        match connect().await {
+            Ok(handle) => {
+                state.handle = Some(handle.clone());
+                state.last_error = None;
+                Ok(handle)
+            }
+            Err(e) => {
+                eprintln!("❌ Failed to connect to {}: {}", self.name, e);
+                state.last_error = Some(e.clone());
+                Err(e)
+            }
+        }
+    }
+
+    /// Forgets a connection that stopped answering so the next use reconnects.
+    async fn drop_handle(&self, error: String) {
+        let mut state = self.state.lock().await;
+        eprintln!("❌ Lost connection to {}: {}", self.name, error);
+        state.last_error = Some(error);
+        state.handle = None;
+    }
+
+    async fn handle(&self) -> Option<T> {
+        self.state.lock().await.handle.clone()
+    }
+
+    async fn health(&self) -> BackendHealth {
+        let state = self.state.lock().await;
+        BackendHealth {
+            configured: true,
+            connected: state.handle.is_some(),
+            last_error: state.last_error.clone(),
+        }
+    }
+}
+
+fn not_configured() -> BackendHealth {
+    BackendHealth {
+        configured: false,
+        connected: false,
+        last_error: None,
+    }
+}
+
+/// One pool per schema, as the schema is part of the MySQL connection options.
+struct MysqlBackend {
+    name: String,
+    url: String,
+    default_database: Option<String>,
+    pool_min: usize,
+    pool_max: usize,
+    pools: std::sync::Mutex<HashMap<Option<String>, Arc<Connection<Pool>>>>,
+}
+
+impl MysqlBackend {
+    fn connection(&self, database: Option<&str>) -> Arc<Connection<Pool>> {
+        let database =
+            database.filter(|database| Some(*database) != self.default_database.as_deref());
+        self.pools
+            .lock()
+            .unwrap()
+            .entry(database.map(str::to_string))
+            .or_insert_with(|| {
+                let name = match database {
+                    Some(database) => format!("MySQL ({}/{})", self.name, database),
+                    None => format!("MySQL ({})", self.name),
+                };
+                Arc::new(Connection::new(name))
+            })
+            .clone()
+    }
+
+    async fn pool(&self, database: Option<&str>, force: bool) -> Result<Pool, String> {
+        self.connection(database)
+            .get(force, || async {
+                connect_mysql(&self.url, database, self.pool_min, self.pool_max)
+                    .await
+                    .map_err(|e| e.to_string())
+            })
+            .await
+    }
+
+    fn databases(&self) -> Vec<Option<String>> {
+        self.pools.lock().unwrap().keys().cloned().collect()
+    }
+}
+
+struct MongodbBackend {
+    uri: String,
+    database: String,
+    max_pool_size: u32,
+    connection: Connection<mongodb::Client>,
+}
+
+impl MongodbBackend {
+    async fn client(&self, force: bool) -> Result<mongodb::Client, String> {
+        self.connection
+            .get(force, || async {
+                connect_mongodb(&self.uri, &self.database, self.max_pool_size)
+                    .await
+                    .map_err(|e| e.to_string())
+            })
+            .await
+    }
+}
+
+/// Where a named connection was defined; only runtime ones can be replaced or removed.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionSource {
+    Config,
+    Runtime,
+}
+
+struct NamedConnection {
+    source: ConnectionSource,
+    config: ConnectionConfig,
+    mysql: Option<MysqlBackend>,
+    mongodb: Option<MongodbBackend>,
+}
+
+/// A named connection as returned by the API, with passwords in its URLs redacted.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ConnectionProfile {
+    pub name: String,
+    pub source: ConnectionSource,
+    #[serde(flatten)]
+    pub settings: ConnectionConfig,
+}
+
+impl NamedConnection {
+    fn new(name: &str, config: &ConnectionConfig, source: ConnectionSource) -> Self {
+        NamedConnection {
+            source,
+            config: config.clone(),
+            mysql: config.mysql_url.clone().map(|url| MysqlBackend {
+                name: name.to_string(),
+                default_database: Opts::from_url(&url)
+                    .ok()
+                    .and_then(|opts| opts.db_name().map(str::to_string)),
+                url,
+                pool_min: config.mysql_pool_min,
+                pool_max: config.mysql_pool_max,
+                pools: Default::default(),
+            }),
+            mongodb: config.mongodb_uri.clone().map(|uri| MongodbBackend {
+                uri,
+                database: config.mongodb_database.clone(),
+                max_pool_size: config.mongodb_max_pool_size,
+                connection: Connection::new(format!("MongoDB ({})", name)),
+            }),
+        }
+    }
+
+    /// Rejects databases other than the connection's default that are not allow-listed.
+    fn check_database<'a>(
+        &self,
+        default: Option<&str>,
+        database: Option<&'a str>,
+    ) -> Result<Option<&'a str>, (StatusCode, String)> {
+        let Some(database) = database else {
+            return Ok(None);
+        };
+        if !is_valid_database_name(database) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("❌ Invalid database name: {}", database),
+            ));
+        }
+        let allowed = default == Some(database)
+            || self
+                .config
+                .allowed_databases
+                .iter()
+                .any(|allowed| allowed == database);
+        if !allowed {
+            return Err((
+                StatusCode::FORBIDDEN,
+                format!("❌ Database {} is not in the allow-list", database),
+            ));
+        }
+        Ok(Some(database))
+    }
+
+    fn profile(&self, name: &str) -> ConnectionProfile {
+        let mut settings = self.config.clone();
+        settings.mysql_url = settings.mysql_url.as_deref().map(redact_url);
+        settings.mongodb_uri = settings.mongodb_uri.as_deref().map(redact_url);
+        ConnectionProfile {
+            name: name.to_string(),
+            source: self.source,
+            settings,
+        }
+    }
+
+    async fn health(&self) -> ConnectionHealth {
+        let mysql = match &self.mysql {
+            Some(backend) => backend.connection(None).health().await,
+            None => not_configured(),
+        };
+        let mongodb = match &self.mongodb {
+            Some(backend) => backend.connection.health().await,
+            None => not_configured(),
+        };
+        ConnectionHealth { mysql, mongodb }
+    }
+
+    /// Connects every configured backend right away, ignoring the retry interval.
+    async fn test(&self) -> ConnectionHealth {
+        if let Some(backend) = &self.mysql {
+            let _ = backend.pool(None, true).await;
+        }
+        if let Some(backend) = &self.mongodb {
+            let _ = backend.client(true).await;
+        }
+        self.health().await
+    }
+
+    async fn check(&self) {
+        if let Some(backend) = &self.mysql {
+            let mut databases = backend.databases();
+            if databases.is_empty() {
+                databases.push(None);
+            }
+            for database in databases {
+                let connection = backend.connection(database.as_deref());
+                match connection.handle().await {
+                    Some(pool) => {
+                        let ping = async { pool.get_conn().await?.ping().await };
+                        if let Err(e) = ping.await {
+                            connection.drop_handle(e.to_string()).await;
+                        }
+                    }
+                    None => {
+                        let _ = backend.pool(database.as_deref(), true).await;
+                    }
+                }
+            }
+        }
+        if let Some(backend) = &self.mongodb {
+            match backend.connection.handle().await {
+                Some(client) => {
+                    let ping = client
+                        .database(&backend.database)
+                        .run_command(doc! {"ping": 1}, None)
+                        .await;
+                    if let Err(e) = ping {
+                        backend.connection.drop_handle(e.to_string()).await;
+                    }
+                }
+                None => {
+                    let _ = backend.client(true).await;
+                }
+            }
+        }
+    }
+
+    async fn shutdown(&self) {
+        if let Some(backend) = &self.mysql {
+            for database in backend.databases() {
+                if let Some(pool) = backend.connection(database.as_deref()).handle().await {
+                    let _ = pool.disconnect().await;
+                }
+            }
+        }
+        if let Some(backend) = &self.mongodb {
+            if let Some(client) = backend.connection.handle().await {
+                client.shutdown().await;
+            }
+        }
+    }
+}
+
+/// Replaces the password of a connection URL so it can be shown to clients.
+fn redact_url(raw: &str) -> String {
+    match Url::parse(raw) {
+        Ok(mut url) => {
+            if url.password().is_some() {
+                let _ = url.set_password(Some(REDACTED));
+            }
+            url.to_string()
+        }
+        Err(_) => REDACTED.to_string(),
+    }
+}
+
+fn load_profiles(path: &Path) -> Result<BTreeMap<String, ConnectionConfig>, String> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let json = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read connections file {}: {}", path.display(), e))?;
+    serde_json::from_str(&json)
+        .map_err(|e| format!("Invalid connections file {}: {}", path.display(), e))
+}
+
+/// The configured connections, shared between handlers.
+/// A backend missing from the configuration or currently down answers with 503.
+#[derive(Clone)]
+pub struct Backends {
+    connections: Arc<RwLock<BTreeMap<String, Arc<NamedConnection>>>>,
+    /// File runtime connections are persisted to; held while it is rewritten.
+    profiles_file: Arc<Mutex<PathBuf>>,
+}
+
+fn unavailable(message: String) -> (StatusCode, String) {
+    (StatusCode::SERVICE_UNAVAILABLE, format!("❌ {}", message))
+}
+
+impl Backends {
+    /// Sets up the connections from the configuration and the persisted runtime profiles.
+    pub fn new(config: &Config) -> Result<Self, Vec<String>> {
+        let mut connections = BTreeMap::new();
+        connections.insert(
+            DEFAULT_CONNECTION.to_string(),
+            Arc::new(NamedConnection::new(
+                DEFAULT_CONNECTION,
+                &config.default_connection(),
+                ConnectionSource::Config,
+            )),
+        );
+        for (name, connection) in &config.connections {
+            connections.insert(
+                name.clone(),
+                Arc::new(NamedConnection::new(
+                    name,
+                    connection,
+                    ConnectionSource::Config,
+                )),
+            );
+        }
+
+        let mut errors = Vec::new();
+        let profiles = load_profiles(&config.connections_file).map_err(|e| vec![e])?;
+        for (name, connection) in profiles {
+            if connections.contains_key(&name) {
+                errors.push(format!(
+                    "connections file: \"{}\" is already defined in the configuration",
+                    name
+                ));
+                continue;
+            }
+            errors.extend(connection.validate(&name));
+            let named = NamedConnection::new(&name, &connection, ConnectionSource::Runtime);
+            connections.insert(name, Arc::new(named));
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(Backends {
+            connections: Arc::new(RwLock::new(connections)),
+            profiles_file: Arc::new(Mutex::new(config.connections_file.clone())),
+        })
+    }
+
+    fn named(&self, target: &Target) -> Result<Arc<NamedConnection>, (StatusCode, String)> {
+        let name = target.connection.as_deref().unwrap_or(DEFAULT_CONNECTION);
+        self.connections.read().unwrap().get(name).cloned().ok_or((
+            StatusCode::BAD_REQUEST,
+            format!("❌ Unknown connection: {}", name),
+        ))
+    }
+
+    fn snapshot(&self) -> Vec<(String, Arc<NamedConnection>)> {
+        self.connections
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(name, named)| (name.clone(), named.clone()))
+            .collect()
+    }
+
+    pub async fn mysql(&self, target: &Target) -> Result<Pool, (StatusCode, String)> {
+        let named = self.named(target)?;
+        let backend = named
+            .mysql
+            .as_ref()
+            .ok_or_else(|| unavailable("MySQL is unavailable: not configured".to_string()))?;
+        let database = named.check_database(
+            backend.default_database.as_deref(),
+            target.database.as_deref(),
+        )?;
+        backend
+            .pool(database, false)
+            .await
+            .map_err(|e| unavailable(format!("MySQL is unavailable: {}", e)))
+    }
+
+    pub async fn mongodb(
+        &self,
+        target: &Target,
+    ) -> Result<mongodb::Database, (StatusCode, String)> {
+        let named = self.named(target)?;
+        let backend = named
+            .mongodb
+            .as_ref()
+            .ok_or_else(|| unavailable("MongoDB is unavailable: not configured".to_string()))?;
+        let database = named
+            .check_database(Some(&backend.database), target.database.as_deref())?
+            .unwrap_or(&backend.database)
+            .to_string();
+        let client = backend
+            .client(false)
+            .await
+            .map_err(|e| unavailable(format!("MongoDB is unavailable: {}", e)))?;
+        Ok(client.database(&database))
+    }
+
+    pub fn profiles(&self) -> Vec<ConnectionProfile> {
+        self.snapshot()
+            .iter()
+            .map(|(name, named)| named.profile(name))
+            .collect()
+    }
+
+    pub fn profile(&self, name: &str) -> Option<ConnectionProfile> {
+        let connections = self.connections.read().unwrap();
+        connections.get(name).map(|named| named.profile(name))
+    }
+
+    /// Adds or replaces a runtime connection and persists the runtime connections.
+    pub async fn register(
+        &self,
+        name: &str,
+        config: ConnectionConfig,
+    ) -> Result<ConnectionProfile, (StatusCode, String)> {
+        if !is_valid_database_name(name) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("❌ Invalid connection name: {}", name),
+            ));
+        }
+        let errors = config.validate(name);
+        if !errors.is_empty() {
+            return Err((StatusCode::BAD_REQUEST, format!("❌ {}", errors.join("; "))));
+        }
+
+        let profiles_file = self.profiles_file.lock().await;
+        let named = Arc::new(NamedConnection::new(
+            name,
+            &config,
+            ConnectionSource::Runtime,
+        ));
+        let replaced = {
+            let mut connections = self.connections.write().unwrap();
+            if connections
+                .get(name)
+                .is_some_and(|existing| existing.source == ConnectionSource::Config)
+            {
+                return Err((
+                    StatusCode::CONFLICT,
+                    format!("❌ Connection {} is defined in the configuration", name),
+                ));
+            }
+            connections.insert(name.to_string(), named.clone())
+        };
+        self.persist(&profiles_file).await?;
+        if let Some(replaced) = replaced {
+            tokio::spawn(async move { replaced.shutdown().await });
+        }
+        Ok(named.profile(name))
+    }
+
+    /// Removes a runtime connection, closing its pools, and persists the runtime connections.
+    pub async fn remove(&self, name: &str) -> Result<ConnectionProfile, (StatusCode, String)> {
+        let profiles_file = self.profiles_file.lock().await;
+        let removed = {
+            let mut connections = self.connections.write().unwrap();
+            match connections.get(name) {
+                None => {
+                    return Err((
+                        StatusCode::NOT_FOUND,
+                        format!("❌ Unknown connection: {}", name),
+                    ))
+                }
+                Some(existing) if existing.source == ConnectionSource::Config => {
+                    return Err((
+                        StatusCode::CONFLICT,
+                        format!("❌ Connection {} is defined in the configuration", name),
+                    ))
+                }
+                Some(_) => connections.remove(name),
+            }
+        };
+        self.persist(&profiles_file).await?;
+        let removed = removed.expect("connection checked above");
+        let profile = removed.profile(name);
+        tokio::spawn(async move { removed.shutdown().await });
+        Ok(profile)
+    }
+
+    async fn persist(&self, path: &Path) -> Result<(), (StatusCode, String)> {
+        let runtime: BTreeMap<String, ConnectionConfig> = self
+            .snapshot()
+            .into_iter()
+            .filter(|(_, named)| named.source == ConnectionSource::Runtime)
+            .map(|(name, named)| (name, named.config.clone()))
+            .collect();
+        let json = serde_json::to_string_pretty(&runtime)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        tokio::fs::write(path, json).await.map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("❌ Failed to save connections to {}: {}", path.display(), e),
+            )
+        })
+    }
+
+    /// Connects a registered connection right away and reports the outcome.
+    pub async fn test(&self, name: &str) -> Result<ConnectionHealth, (StatusCode, String)> {
+        let named = self.connections.read().unwrap().get(name).cloned().ok_or((
+            StatusCode::NOT_FOUND,
+            format!("❌ Unknown connection: {}", name),
+        ))?;
+        Ok(named.test().await)
+    }
+
+    /// Tries the settings of a connection that is not registered.
+    pub async fn test_settings(
+        config: &ConnectionConfig,
+    ) -> Result<ConnectionHealth, (StatusCode, String)> {
+        let errors = config.validate("test");
+        if !errors.is_empty() {
+            return Err((StatusCode::BAD_REQUEST, format!("❌ {}", errors.join("; "))));
+        }
+        let named = NamedConnection::new("test", config, ConnectionSource::Runtime);
+        let health = named.test().await;
+        named.shutdown().await;
+        Ok(health)
+    }
+
+    pub async fn health(&self) -> HealthReport {
+        let mut connections = BTreeMap::new();
+        for (name, named) in self.snapshot() {
+            connections.insert(name, named.health().await);
+        }
+        let all_up = connections.values().all(|connection| {
+            [&connection.mysql, &connection.mongodb]
+                .iter()
+                .all(|backend| !backend.configured || backend.connected)
+        });
+
+        HealthReport {
+            status: if all_up { "ok" } else { "degraded" },
+            connections,
+        }
+    }
+
+    /// Pings connected backends and reconnects lost ones until the process exits.
+    pub fn spawn_health_checks(&self) {
+        let backends = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(HEALTH_CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+                for (_, named) in backends.snapshot() {
+                    named.check().await;
+                }
+            }
+        });
+    }
+
+    pub async fn shutdown(&self) {
+        for (_, named) in self.snapshot() {
+            named.shutdown().await;
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Tables in the order they can be cleared without leaving rows that refer to removed ones:
+/// links and events first, then rows referencing other tables before the rows they reference,
+/// and the log of generation runs last.
+pub const CLEAR_ORDER: &[&str] = &[
+    "employee_project",
+    "project_technology",
+    "employee_technology",
+    "payment_event",
+    "task_status_event",
+    "task",
+    "payment",
+    "contract",
+    "project",
+    "employee",
+    "client",
+    "address",
+    "technology",
+    "generation_runs",
+];
+
+/// Tables of `CLEAR_ORDER` without rows of their own runs, which are only cleared as a whole.
+pub const WHOLE_TABLES: &[&str] = &["payment_event", "task_status_event", "generation_runs"];
+
+pub const DEFAULT_CHUNK_SIZE: usize = 1000;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ClearStrategy {
+    /// A single `DELETE` (MySQL) or `delete_many` (MongoDB).
+    #[default]
+    Delete,
+    /// Deletes in chunks of `chunk_size` rows to keep transactions small.
+    Chunked,
+    /// `TRUNCATE TABLE`, which also resets AUTO_INCREMENT. Removes all documents in MongoDB.
+    Truncate,
+    /// Drops the table or collection and creates it again empty.
+    Recreate,
+}
+
+impl ClearStrategy {
+    pub fn supports_filter(&self) -> bool {
+        matches!(self, ClearStrategy::Delete | ClearStrategy::Chunked)
+    }
+}
@@ -1,269 +1,2143 @@
-use super::table_type_mysql::TableType;
-use axum::{extract::Extension, http::StatusCode, Json};
+use super::schema::{table_schema, ColumnKind, ColumnMetadata, TableName, RUN_ID_COLUMN};
+use super::table_type_mysql::{Row, TableType};
+use axum::{
+    extract::{
+        rejection::{JsonRejection, QueryRejection},
+        Extension, Path, Query,
+    },
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
-use super::mongodb::{clear_mongodb, fetch_all_data_mongodb, insert_batch_mongodb};
-use super::mysql::{clear_mysql, fetch_all_data_mysql, insert_batch, parallel_insert_batch};
-
-use mysql_async::Pool;
-
-use crate::models::{
-    address::Address, client::Client, contract::Contract, employee::Employee, payment::Payment,
-    project::Project, task::Task, technology::Technology,
+use super::backends::{
+    Backends, ConnectionHealth, ConnectionProfile, DbType, HealthReport, Target,
+};
+use super::clear::{ClearStrategy, CLEAR_ORDER, DEFAULT_CHUNK_SIZE, WHOLE_TABLES};
+use super::mongodb::{
+    clear_mongodb, clear_run_documents_mongodb, count_rows_mongodb, delete_rows_mongodb,
+    event_to_document, fetch_all_data_mongodb, fetch_ids_mongodb, fetch_keyed_rows_mongodb,
+    fetch_rows_mongodb, fetch_run_mongodb, fetch_runs_mongodb, insert_batch_mongodb,
+    insert_documents_mongodb, insert_employee_contracts_mongodb, insert_events_mongodb,
+    insert_org_chart_mongodb, insert_org_tree_mongodb, insert_run_mongodb, replace_links_mongodb,
+    update_rows_mongodb, update_run_mongodb, ORG_CHART_COLLECTION,
 };
+use super::mysql::{
+    clear_mysql, count_rows_mysql, delete_rows_mysql, fetch_all_data_mysql, fetch_keyed_rows_mysql,
+    fetch_keys_mysql, fetch_rows_mysql, fetch_run_mysql, fetch_runs_mysql, insert_batch,
+    insert_employee_contracts_mysql, insert_org_chart_mysql, insert_payment_events,
+    insert_run_mysql, insert_task_status_events, parallel_insert_batch, replace_links_mysql,
+    update_rows_mysql, update_run_mysql,
+};
+
+use crate::auth::{AuditEntry, Auth};
+use crate::config::{is_valid_database_name, Config, ConnectionConfig};
+use crate::generator::coherence;
+use crate::generator::documents::DocumentShape;
+use crate::generator::geo::{self, Cluster};
+use crate::generator::invariants;
+use crate::generator::mutation::pick_mutations;
+use crate::generator::org::{self, OrgLayout, OrgShape};
+use crate::generator::overrides::{self, Overrides};
+use crate::generator::profile::{profile_rows, TableProfile};
+use crate::generator::relations::{self, Cardinality, Relation};
+use crate::generator::stream::{StreamConfig, StreamJobs, StreamStatus};
+use crate::generator::timeseries::{payment_events, task_status_events, Seasonality};
+use crate::models::generation_run::GenerationRun;
+use chrono::{NaiveDateTime, Utc};
+use mongodb::bson::Bson;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Arc;
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
 pub struct GenerateRequest {
-    count: usize,
-    db_type: String,
-    table_name: String,
+    /// Rows per table; the configured `default_count` when absent.
+    count: Option<usize>,
+    /// Required unless `insert_into_many` is set, which always writes to MySQL.
+    db_type: Option<DbType>,
+    #[serde(flatten)]
+    target: Target,
+    /// Required unless `insert_into_many` is set, which fills every table.
+    table_name: Option<TableName>,
+    #[serde(default)]
     insert_into_many: bool,
+    profile: Option<String>,
+    /// Seed for the random generator; the same seed and parameters produce the same rows.
+    seed: Option<i64>,
+    /// Replaces generated values of individual columns, applied after `profile`.
+    #[serde(default, skip_serializing_if = "Overrides::is_empty")]
+    overrides: Overrides,
+    /// Extra conditions rows of each table must satisfy, e.g. `"salary >= 5000"`; rows
+    /// breaking one are regenerated.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    invariants: BTreeMap<TableName, Vec<String>>,
+    /// Whether the built-in invariants, such as `end_date >= start_date`, are enforced too.
+    #[serde(default = "default_true")]
+    builtin_invariants: bool,
+    /// Centres generated addresses gather around instead of spreading over the gazetteer.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    clusters: Vec<Cluster>,
+}
+
+fn default_true() -> bool {
+    true
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
 pub struct ClearRequest {
-    db_type: String,
+    db_type: DbType,
+    #[serde(flatten)]
+    target: Target,
+    #[serde(default)]
     table_name: String,
+    #[serde(default)]
+    strategy: ClearStrategy,
+    chunk_size: Option<usize>,
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    filter: Row,
+    /// Clears every table in `CLEAR_ORDER`, including links, events and the run log.
+    #[serde(default)]
+    all_tables: bool,
+    #[serde(default)]
+    dry_run: bool,
+    /// Restricts the clear to rows inserted by the given generation run, and links to or from
+    /// them.
+    run_id: Option<String>,
+    /// Must repeat the table name, or `"all"` with `all_tables`, unless `dry_run` is set.
+    confirm: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct RunsQuery {
+    db_type: DbType,
+    #[serde(flatten)]
+    target: Target,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct RollbackRequest {
+    db_type: DbType,
+    #[serde(flatten)]
+    target: Target,
+    #[serde(default)]
+    dry_run: bool,
+    /// Must repeat the run id unless `dry_run` is set.
+    confirm: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ClearedTable {
+    table_name: String,
+    rows: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ClearReport {
+    db_type: String,
+    strategy: ClearStrategy,
+    dry_run: bool,
+    /// Rows removed per table, or rows that would be removed in a dry run.
+    tables: Vec<ClearedTable>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
 pub struct GetRequest {
+    db_type: DbType,
+    #[serde(flatten)]
+    target: Target,
+    table_name: String,
+    /// Response format; 1 (the default) wraps rows in their type, 2 returns plain rows.
+    #[serde(default = "default_data_version")]
+    version: u32,
+}
+
+fn default_data_version() -> u32 {
+    1
+}
+
+/// Describes the rows of a version 2 `/data` response.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DataHeader {
+    table_name: String,
     db_type: String,
+    primary_key: String,
+    row_count: usize,
+    /// Every column of a row, primary key first and `run_id` last.
+    columns: Vec<ColumnMetadata>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DataPage {
+    version: u32,
+    header: DataHeader,
+    #[schema(value_type = Vec<Object>)]
+    rows: Vec<Row>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum DataResponse {
+    /// Version 1: rows wrapped in their type, e.g. `{"Employee": {...}}`, without ids.
+    V1(Vec<TableType>),
+    V2(DataPage),
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct TimeSeriesRequest {
+    db_type: DbType,
+    #[serde(flatten)]
+    target: Target,
+    series: String,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    count: usize,
+    #[serde(default)]
+    shape: Seasonality,
+    #[serde(default)]
+    time_series_collection: bool,
+}
+
+impl TimeSeriesRequest {
+    /// Every problem with the request, checked before any event is generated.
+    fn validate(&self, config: &Config) -> Result<(), ValidationErrors> {
+        let mut errors = self.shape.validate();
+        let limit = config.count_limit(None);
+        if self.count > limit {
+            errors.push(format!(
+                "count {} exceeds the limit of {}",
+                self.count, limit
+            ));
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationErrors::new(errors))
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct MutateRequest {
+    db_type: DbType,
+    #[serde(flatten)]
+    target: Target,
     table_name: String,
+    #[serde(default)]
+    update_percent: f64,
+    #[serde(default)]
+    delete_percent: f64,
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    filter: Row,
 }
 
-pub async fn generate_data(
-    Extension(pool): Extension<Pool>,
-    Extension(mongodb_client): Extension<mongodb::Client>,
-    Json(payload): Json<GenerateRequest>,
-) -> Result<Json<String>, (StatusCode, String)> {
-    if payload.insert_into_many {
-        let mut all_data = Vec::new();
-        let table_names = [
-            "employee",
-            "client",
-            "address",
-            "contract",
-            "payment",
-            "project",
-            "task",
-            "technology",
-        ];
-
-        for table in table_names.iter() {
-            match *table {
-                "employee" => {
-                    let employees = Employee::generate_batch(payload.count);
-                    all_data.extend(employees.into_iter().map(TableType::Employee));
-                }
-                "client" => {
-                    let clients = Client::generate_batch(payload.count);
-                    all_data.extend(clients.into_iter().map(TableType::Client));
-                }
-                "address" => {
-                    let addresses = Address::generate_batch(payload.count);
-                    all_data.extend(addresses.into_iter().map(TableType::Address));
-                }
-                "contract" => {
-                    let contracts = Contract::generate_batch(payload.count);
-                    all_data.extend(contracts.into_iter().map(TableType::Contract));
-                }
-                "payment" => {
-                    let payments = Payment::generate_batch(payload.count);
-                    all_data.extend(payments.into_iter().map(TableType::Payment));
-                }
-                "project" => {
-                    let projects = Project::generate_batch(payload.count);
-                    all_data.extend(projects.into_iter().map(TableType::Project));
-                }
-                "task" => {
-                    let tasks = Task::generate_batch(payload.count);
-                    all_data.extend(tasks.into_iter().map(TableType::Task));
-                }
-                "technology" => {
-                    let technologies = Technology::generate_batch(payload.count);
-                    all_data.extend(technologies.into_iter().map(TableType::Technology));
-                }
-                _ => {
-                    return Err((
-                        StatusCode::BAD_REQUEST,
-                        "❌ Error while genereting data".to_string(),
-                    ))
-                }
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MutationReport {
+    table_name: String,
+    updated: Vec<Value>,
+    deleted: Vec<Value>,
+    /// Rows short of `update_percent` because too few rows could change, e.g. when most
+    /// tasks are already completed.
+    skipped: usize,
+}
+
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditQuery {
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct ProfileRequest {
+    db_type: DbType,
+    #[serde(flatten)]
+    target: Target,
+    table_name: String,
+    name: Option<String>,
+}
+
+const DEFAULT_AUDIT_LIMIT: usize = 100;
+
+/// Every problem found in a request body, answered with 422.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ValidationErrors {
+    message: String,
+    errors: Vec<String>,
+}
+
+impl ValidationErrors {
+    fn new(errors: Vec<String>) -> Self {
+        ValidationErrors {
+            message: "❌ Invalid request".to_string(),
+            errors,
+        }
+    }
+}
+
+impl IntoResponse for ValidationErrors {
+    fn into_response(self) -> Response {
+        (StatusCode::UNPROCESSABLE_ENTITY, Json(self)).into_response()
+    }
+}
+
+impl GenerateRequest {
+    fn tables(&self) -> Vec<TableName> {
+        match (self.insert_into_many, self.table_name) {
+            (true, _) => TableName::ALL.to_vec(),
+            (false, Some(table)) => vec![table],
+            (false, None) => Vec::new(),
+        }
+    }
+
+    fn backend(&self) -> DbType {
+        if self.insert_into_many {
+            DbType::Mysql
+        } else {
+            self.db_type.unwrap_or(DbType::Mysql)
+        }
+    }
+
+    fn validate(&self, config: &Config) -> Result<(), ValidationErrors> {
+        let mut errors = Vec::new();
+
+        if self.insert_into_many {
+            if self.db_type == Some(DbType::Mongodb) {
+                errors.push(
+                    "insert_into_many always writes to MySQL, db_type must be mysql or omitted"
+                        .to_string(),
+                );
+            }
+            if self.table_name.is_some() {
+                errors.push(
+                    "table_name cannot be combined with insert_into_many, which fills every table"
+                        .to_string(),
+                );
+            }
+        } else {
+            if self.db_type.is_none() {
+                errors.push("db_type is required".to_string());
             }
+            if self.table_name.is_none() {
+                errors.push("table_name is required".to_string());
+            }
+        }
+
+        errors.extend(overrides::validate(&self.overrides, &self.tables()));
+        errors.extend(invariants::validate(&self.invariants, &self.tables()));
+        errors.extend(geo::validate(&self.clusters));
+        if !self.clusters.is_empty() && !self.tables().contains(&TableName::Address) {
+            errors.push("clusters need address rows to be generated".to_string());
+        }
+
+        let count = self.count.unwrap_or(config.default_count);
+        if count == 0 {
+            errors.push("count must be at least 1".to_string());
+        }
+        for table in self.tables() {
+            let limit = config.count_limit(Some(table));
+            if count > limit {
+                errors.push(format!(
+                    "count {} exceeds the limit of {} for table {}",
+                    count,
+                    limit,
+                    table.as_str()
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationErrors::new(errors))
+        }
+    }
+}
+
+fn new_run_id(rng: &mut SmallRng) -> String {
+    format!(
+        "{:x}-{:08x}",
+        Utc::now().timestamp_millis(),
+        rng.gen::<u32>()
+    )
+}
+
+/// Generates and inserts the rows described by `payload`, recording the run and tagging
+/// every inserted row with its id.
+async fn run_generation(
+    backends: &Backends,
+    config: &Config,
+    mut payload: GenerateRequest,
+    replay_of: Option<String>,
+) -> Result<GenerationRun, (StatusCode, String)> {
+    let count = *payload.count.get_or_insert(config.default_count);
+    let tables = payload.tables();
+    overrides::add_patterns(&mut payload.overrides, &config.column_patterns, &tables);
+    let profile = match &payload.profile {
+        Some(name) => Some(TableProfile::load(name).map_err(|e| (StatusCode::BAD_REQUEST, e))?),
+        None => None,
+    };
+    if let (Some(profile), Some(table)) = (&profile, payload.table_name) {
+        if profile.table_name != table.as_str() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("❌ Profile was built for table {}", profile.table_name),
+            ));
         }
-        parallel_insert_batch(&pool, &all_data)
+    }
+
+    let seed = payload
+        .seed
+        .unwrap_or_else(|| SmallRng::from_entropy().gen_range(0..=i64::MAX));
+    payload.seed = Some(seed);
+    let mut rng = SmallRng::seed_from_u64(seed as u64);
+
+    let mut all_data = Vec::new();
+    let mut counts = BTreeMap::new();
+    for table in payload.tables() {
+        let batch = TableType::generate_batch_with_rng(table.as_str(), count, &mut rng)
+            .ok_or((StatusCode::BAD_REQUEST, "❌ Invalid table name".to_string()))?;
+        counts.insert(table.as_str().to_string(), batch.len());
+        all_data.extend(batch);
+    }
+    if let Some(profile) = &profile {
+        all_data = profile
+            .apply(all_data, &mut rng)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    }
+    let invariants =
+        invariants::for_tables(&tables, &payload.invariants, payload.builtin_invariants)
+            .map_err(|e| (StatusCode::BAD_REQUEST, format!("❌ {}", e)))?;
+    // Contracts follow their employee's final values; overridden contract columns stay as
+    // set, and templates are rendered again on the contracts that changed.
+    let kept = overrides::columns(&payload.overrides, &tables, TableName::Contract);
+    let mut rows = overrides::to_rows(all_data);
+    geo::cluster(&mut rows, &payload.clusters, None, &mut rng);
+    overrides::apply(&payload.overrides, &tables, &mut rows, None, &mut rng)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("❌ {}", e)))?;
+    let coherent = coherence::apply(&mut rows, None, &kept, &mut rng);
+    overrides::apply(
+        &payload.overrides,
+        &tables,
+        &mut rows,
+        Some(&coherent),
+        &mut rng,
+    )
+    .map_err(|e| (StatusCode::BAD_REQUEST, format!("❌ {}", e)))?;
+    invariants::enforce(&invariants, &mut rows, &mut rng, |rows, violating, rng| {
+        for index in violating {
+            let table_name = rows[*index].0;
+            let mut fresh = TableType::generate_batch_with_rng(table_name, 1, rng)
+                .ok_or("invalid table name")?;
+            if let Some(profile) = &profile {
+                fresh = profile.apply(fresh, rng)?;
+            }
+            rows[*index].1 = fresh
+                .pop()
+                .ok_or_else(|| format!("no rows generated for {}", table_name))?
+                .to_row();
+        }
+        let mut changed = geo::cluster(rows, &payload.clusters, Some(violating), rng);
+        changed.extend(violating);
+        overrides::apply(&payload.overrides, &tables, rows, Some(&changed), rng)?;
+        let coherent = coherence::apply(rows, Some(&changed), &kept, rng);
+        overrides::apply(&payload.overrides, &tables, rows, Some(&coherent), rng)
+    })
+    .map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, format!("❌ {}", e)))?;
+    let pairs = coherence::pairs(&rows);
+    let all_data =
+        overrides::from_rows(rows).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    let (employee_contracts, all_data) = coherence::take_pairs(all_data, &pairs);
+
+    let mut run = GenerationRun {
+        run_id: new_run_id(&mut rng),
+        backend: payload.backend().as_str().to_string(),
+        params: serde_json::to_value(&payload)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+        seed,
+        counts,
+        started_at: Utc::now().naive_utc(),
+        finished_at: None,
+        rolled_back_at: None,
+        replay_of,
+    };
+
+    if run.backend == "mysql" {
+        insert_run_mysql(&backends.mysql(&payload.target).await?, &run)
             .await
             .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-        return Ok(Json(format!("✅ Generated {}", payload.count)));
-    }
-
-    let result = match payload.db_type.as_str() {
-        "mysql" => {
-            let all_data = match payload.table_name.as_str() {
-                "employee" => Employee::generate_batch(payload.count)
-                    .into_iter()
-                    .map(TableType::Employee)
-                    .collect(),
-                "client" => Client::generate_batch(payload.count)
-                    .into_iter()
-                    .map(TableType::Client)
-                    .collect(),
-                "address" => Address::generate_batch(payload.count)
-                    .into_iter()
-                    .map(TableType::Address)
-                    .collect(),
-                "contract" => Contract::generate_batch(payload.count)
-                    .into_iter()
-                    .map(TableType::Contract)
-                    .collect(),
-                "payment" => Payment::generate_batch(payload.count)
-                    .into_iter()
-                    .map(TableType::Payment)
-                    .collect(),
-                "project" => Project::generate_batch(payload.count)
-                    .into_iter()
-                    .map(TableType::Project)
-                    .collect(),
-                "task" => Task::generate_batch(payload.count)
-                    .into_iter()
-                    .map(TableType::Task)
-                    .collect(),
-                "technology" => Technology::generate_batch(payload.count)
-                    .into_iter()
-                    .map(TableType::Technology)
-                    .collect(),
-                _ => return Err((StatusCode::BAD_REQUEST, "❌ Invalid table name".to_string())),
-            };
-            insert_batch(&pool, &all_data)
+        insert_employee_contracts_mysql(
+            &backends.mysql(&payload.target).await?,
+            &employee_contracts,
+            Some(&run.run_id),
+        )
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        if payload.insert_into_many {
+            parallel_insert_batch(
+                &backends.mysql(&payload.target).await?,
+                &all_data,
+                Some(&run.run_id),
+            )
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        } else {
+            insert_batch(
+                &backends.mysql(&payload.target).await?,
+                &all_data,
+                Some(&run.run_id),
+            )
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        }
+        run.finished_at = Some(Utc::now().naive_utc());
+        update_run_mysql(&backends.mysql(&payload.target).await?, &run)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    } else {
+        insert_run_mongodb(&backends.mongodb(&payload.target).await?, &run)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        insert_employee_contracts_mongodb(
+            &backends.mongodb(&payload.target).await?,
+            &employee_contracts,
+            Some(&run.run_id),
+        )
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        insert_batch_mongodb(
+            &backends.mongodb(&payload.target).await?,
+            &all_data,
+            Some(&run.run_id),
+        )
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        run.finished_at = Some(Utc::now().naive_utc());
+        update_run_mongodb(&backends.mongodb(&payload.target).await?, &run)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+
+    Ok(run)
+}
+
+#[utoipa::path(
+    post,
+    path = "/generate",
+    tag = "generation",
+    request_body = GenerateRequest,
+    responses(
+        (status = 200, description = "Rows generated", body = String, content_type = "application/json"),
+        (status = 422, description = "Invalid request", body = ValidationErrors),
+        (status = 400, description = "Invalid parameters", body = String, content_type = "text/plain"),
+        (status = 500, description = "Database error", body = String, content_type = "text/plain"),
+        (status = 503, description = "Backend unavailable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn generate_data(
+    Extension(backends): Extension<Backends>,
+    Extension(config): Extension<Arc<Config>>,
+    payload: Result<Json<GenerateRequest>, JsonRejection>,
+) -> Result<Json<String>, Response> {
+    let Json(payload) = payload.map_err(|rejection| {
+        let status = rejection.status();
+        let mut response = ValidationErrors::new(vec![rejection.body_text()]).into_response();
+        *response.status_mut() = status;
+        response
+    })?;
+    payload
+        .validate(&config)
+        .map_err(IntoResponse::into_response)?;
+    let run = run_generation(&backends, &config, payload, None)
+        .await
+        .map_err(IntoResponse::into_response)?;
+    let count = run.counts.values().sum::<usize>();
+    Ok(Json(format!("✅ Generated {} (run {})", count, run.run_id)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/generate/timeseries",
+    tag = "generation",
+    request_body = TimeSeriesRequest,
+    responses(
+        (status = 200, description = "Events generated", body = String, content_type = "application/json"),
+        (status = 422, description = "Invalid request", body = ValidationErrors),
+        (status = 400, description = "Invalid parameters", body = String, content_type = "text/plain"),
+        (status = 500, description = "Database error", body = String, content_type = "text/plain"),
+        (status = 503, description = "Backend unavailable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn generate_time_series(
+    Extension(backends): Extension<Backends>,
+    Extension(config): Extension<Arc<Config>>,
+    Json(payload): Json<TimeSeriesRequest>,
+) -> Result<Json<String>, Response> {
+    payload
+        .validate(&config)
+        .map_err(IntoResponse::into_response)?;
+    let generated = insert_time_series(&backends, &payload)
+        .await
+        .map_err(IntoResponse::into_response)?;
+    Ok(Json(format!("✅ Generated {} events", generated)))
+}
+
+/// Generates the requested series and writes it, returning the number of events.
+async fn insert_time_series(
+    backends: &Backends,
+    payload: &TimeSeriesRequest,
+) -> Result<usize, (StatusCode, String)> {
+    let generated = match payload.series.as_str() {
+        "payment" => {
+            let events = payment_events(&payload.shape, payload.start, payload.end, payload.count)
+                .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+            if payload.db_type == DbType::Mysql {
+                insert_payment_events(&backends.mysql(&payload.target).await?, &events)
+                    .await
+                    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            } else {
+                let docs = events
+                    .iter()
+                    .map(|event| event_to_document(event, event.occurred_at))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+                insert_events_mongodb(
+                    &backends.mongodb(&payload.target).await?,
+                    "payment_event",
+                    "method",
+                    docs,
+                    payload.time_series_collection,
+                )
                 .await
                 .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-            Ok(Json(format!("✅ Generated {}", payload.count)))
+            }
+            events.len()
         }
-        "mongodb" => {
-            match payload.table_name.as_str() {
-                "employee" => {
-                    let employees = Employee::generate_batch(payload.count);
-                    let employees: Vec<TableType> =
-                        employees.into_iter().map(TableType::Employee).collect();
-                    insert_batch_mongodb(&mongodb_client, &employees)
-                        .await
-                        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-                }
-                "client" => {
-                    let clients = Client::generate_batch(payload.count);
-                    let clients: Vec<TableType> =
-                        clients.into_iter().map(TableType::Client).collect();
-                    insert_batch_mongodb(&mongodb_client, &clients)
-                        .await
-                        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-                }
-                "address" => {
-                    let addresses = Address::generate_batch(payload.count);
-                    let addresses: Vec<TableType> =
-                        addresses.into_iter().map(TableType::Address).collect();
-                    insert_batch_mongodb(&mongodb_client, &addresses)
-                        .await
-                        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-                }
-                "contract" => {
-                    let contracts = Contract::generate_batch(payload.count);
-                    let contracts: Vec<TableType> =
-                        contracts.into_iter().map(TableType::Contract).collect();
-                    insert_batch_mongodb(&mongodb_client, &contracts)
-                        .await
-                        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-                }
-                "payment" => {
-                    let payments = Payment::generate_batch(payload.count);
-                    let payments: Vec<TableType> =
-                        payments.into_iter().map(TableType::Payment).collect();
-                    insert_batch_mongodb(&mongodb_client, &payments)
-                        .await
-                        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-                }
-                "project" => {
-                    let projects = Project::generate_batch(payload.count);
-                    let projects: Vec<TableType> =
-                        projects.into_iter().map(TableType::Project).collect();
-                    insert_batch_mongodb(&mongodb_client, &projects)
-                        .await
-                        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-                }
-                "task" => {
-                    let tasks = Task::generate_batch(payload.count);
-                    let tasks: Vec<TableType> = tasks.into_iter().map(TableType::Task).collect();
-                    insert_batch_mongodb(&mongodb_client, &tasks)
-                        .await
-                        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-                }
-                "technology" => {
-                    let technologies = Technology::generate_batch(payload.count);
-                    let technologies: Vec<TableType> = technologies
-                        .into_iter()
-                        .map(TableType::Technology)
-                        .collect();
-                    insert_batch_mongodb(&mongodb_client, &technologies)
-                        .await
-                        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-                }
-                _ => return Err((StatusCode::BAD_REQUEST, "❌ Invalid table name".to_string())),
+        "task_status" => {
+            let events =
+                task_status_events(&payload.shape, payload.start, payload.end, payload.count)
+                    .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+            if payload.db_type == DbType::Mysql {
+                insert_task_status_events(&backends.mysql(&payload.target).await?, &events)
+                    .await
+                    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            } else {
+                let docs = events
+                    .iter()
+                    .map(|event| event_to_document(event, event.occurred_at))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+                insert_events_mongodb(
+                    &backends.mongodb(&payload.target).await?,
+                    "task_status_event",
+                    "task_ref",
+                    docs,
+                    payload.time_series_collection,
+                )
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
             }
-            Ok(Json(format!("✅ Generated {}", payload.count)))
+            events.len()
         }
         _ => {
             return Err((
                 StatusCode::BAD_REQUEST,
-                "❌ Invalid database type".to_string(),
+                "❌ Invalid series name".to_string(),
             ))
         }
     };
-    result.map(|_| Json(format!("✅ Generated {}", payload.count)))
+
+    Ok(generated)
+}
+
+/// Key of the request in the params of org chart runs.
+const ORG_CHART_PARAMS: &str = "org_chart";
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct OrgChartRequest {
+    db_type: DbType,
+    #[serde(flatten)]
+    target: Target,
+    #[serde(default)]
+    shape: OrgShape,
+    /// Seed for the random generator; the same seed and shape produce the same chart.
+    seed: Option<i64>,
+    /// Ignored for MySQL, which always references managers through `manager_id`.
+    #[serde(default)]
+    layout: OrgLayout,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OrgChartReport {
+    employees: usize,
+    /// Employees on each level, the chief executive first.
+    levels: Vec<usize>,
+    teams: usize,
+    seed: i64,
+    /// Primary key or `_id` of the chief executive; absent for the nested layout.
+    root: Option<Value>,
+    run_id: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/generate/org-chart",
+    tag = "generation",
+    request_body = OrgChartRequest,
+    responses(
+        (status = 200, description = "Org chart generated", body = OrgChartReport),
+        (status = 422, description = "Invalid shape", body = ValidationErrors),
+        (status = 500, description = "Database error", body = String, content_type = "text/plain"),
+        (status = 503, description = "Backend unavailable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn generate_org_chart(
+    Extension(backends): Extension<Backends>,
+    Extension(config): Extension<Arc<Config>>,
+    Json(payload): Json<OrgChartRequest>,
+) -> Result<Json<OrgChartReport>, Response> {
+    run_org_chart(&backends, &config, payload, None)
+        .await
+        .map(|(_, report)| Json(report))
+}
+
+/// Generates and inserts the org chart described by `payload`, recording the run and tagging
+/// every inserted employee and contract, or the nested document, with its id.
+async fn run_org_chart(
+    backends: &Backends,
+    config: &Config,
+    mut payload: OrgChartRequest,
+    replay_of: Option<String>,
+) -> Result<(GenerationRun, OrgChartReport), Response> {
+    let errors = payload.shape.validate();
+    if !errors.is_empty() {
+        return Err(ValidationErrors::new(errors).into_response());
+    }
+    let seed = *payload
+        .seed
+        .get_or_insert_with(|| SmallRng::from_entropy().gen_range(0..=i64::MAX));
+    let mut rng = SmallRng::seed_from_u64(seed as u64);
+    let limit = config.count_limit(Some(TableName::Employee));
+    let members = org::generate(&payload.shape, limit, &mut rng)
+        .map_err(|e| ValidationErrors::new(vec![e]).into_response())?;
+    let database_error = |e: String| (StatusCode::INTERNAL_SERVER_ERROR, e).into_response();
+
+    let mut params = serde_json::Map::new();
+    params.insert(
+        ORG_CHART_PARAMS.to_string(),
+        serde_json::to_value(&payload).map_err(|e| database_error(e.to_string()))?,
+    );
+    let mut run = GenerationRun {
+        run_id: new_run_id(&mut rng),
+        backend: payload.db_type.as_str().to_string(),
+        params: Value::Object(params),
+        seed,
+        counts: BTreeMap::from([
+            (TableName::Employee.as_str().to_string(), members.len()),
+            (TableName::Contract.as_str().to_string(), members.len()),
+        ]),
+        started_at: Utc::now().naive_utc(),
+        finished_at: None,
+        rolled_back_at: None,
+        replay_of,
+    };
+
+    let root = match payload.db_type {
+        DbType::Mysql => {
+            let pool = backends
+                .mysql(&payload.target)
+                .await
+                .map_err(IntoResponse::into_response)?;
+            insert_run_mysql(&pool, &run)
+                .await
+                .map_err(|e| database_error(e.to_string()))?;
+            let keys = insert_org_chart_mysql(&pool, &members, Some(&run.run_id))
+                .await
+                .map_err(|e| database_error(e.to_string()))?;
+            run.finished_at = Some(Utc::now().naive_utc());
+            update_run_mysql(&pool, &run)
+                .await
+                .map_err(|e| database_error(e.to_string()))?;
+            keys.first().map(|key| Value::from(*key))
+        }
+        DbType::Mongodb => {
+            let database = backends
+                .mongodb(&payload.target)
+                .await
+                .map_err(IntoResponse::into_response)?;
+            insert_run_mongodb(&database, &run)
+                .await
+                .map_err(|e| database_error(e.to_string()))?;
+            let root = match payload.layout {
+                OrgLayout::Referenced => {
+                    insert_org_chart_mongodb(&database, &members, Some(&run.run_id))
+                        .await
+                        .map_err(|e| database_error(e.to_string()))?
+                        .first()
+                        .map(|id| Value::from(id.to_hex()))
+                }
+                OrgLayout::Nested => {
+                    insert_org_tree_mongodb(&database, &members, Some(&run.run_id))
+                        .await
+                        .map_err(|e| database_error(e.to_string()))?;
+                    None
+                }
+            };
+            run.finished_at = Some(Utc::now().naive_utc());
+            update_run_mongodb(&database, &run)
+                .await
+                .map_err(|e| database_error(e.to_string()))?;
+            root
+        }
+    };
+
+    let mut levels = vec![0; payload.shape.depth];
+    for member in &members {
+        levels[member.level] += 1;
+    }
+    let report = OrgChartReport {
+        employees: members.len(),
+        teams: levels[payload.shape.depth - 2],
+        levels,
+        seed,
+        root,
+        run_id: run.run_id.clone(),
+    };
+    Ok((run, report))
+}
+
+/// Key of the settings in the params of stream runs.
+const STREAM_PARAMS: &str = "stream";
+
+/// Key of the request in the params of document runs.
+const DOCUMENTS_PARAMS: &str = "documents";
+
+/// A document shape given inline or by its name in the `document_shapes` setting.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum ShapeRef {
+    Named(String),
+    Inline(DocumentShape),
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct DocumentRequest {
+    #[serde(flatten)]
+    target: Target,
+    shape: ShapeRef,
+    /// Collection the documents go to; `<table>_documents` for the top-level table when
+    /// absent. The collections of generated tables and runs are reserved.
+    collection: Option<String>,
+    /// Top-level documents to generate; the configured `default_count` when absent.
+    count: Option<usize>,
+    /// Seed for the random generator; the same seed and shape produce the same documents.
+    seed: Option<i64>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/generate/documents",
+    tag = "generation",
+    request_body = DocumentRequest,
+    responses(
+        (status = 200, description = "Documents generated", body = String, content_type = "application/json"),
+        (status = 422, description = "Invalid shape", body = ValidationErrors),
+        (status = 500, description = "Database error", body = String, content_type = "text/plain"),
+        (status = 503, description = "Backend unavailable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn generate_documents(
+    Extension(backends): Extension<Backends>,
+    Extension(config): Extension<Arc<Config>>,
+    Json(payload): Json<DocumentRequest>,
+) -> Result<Json<String>, Response> {
+    let run = run_documents(&backends, &config, payload, None).await?;
+    let (collection, count) = run.counts.iter().next().unwrap();
+    Ok(Json(format!(
+        "✅ Generated {} documents into {} (seed {}, run {})",
+        count, collection, run.seed, run.run_id
+    )))
+}
+
+/// Generates documents of a shape and records them as a run, whose params hold the request
+/// with the collection and seed filled in so a replay produces the same documents.
+async fn run_documents(
+    backends: &Backends,
+    config: &Config,
+    mut payload: DocumentRequest,
+    replay_of: Option<String>,
+) -> Result<GenerationRun, Response> {
+    let shape = match &payload.shape {
+        ShapeRef::Inline(shape) => shape.clone(),
+        ShapeRef::Named(name) => config.document_shapes.get(name).cloned().ok_or_else(|| {
+            ValidationErrors::new(vec![format!("shape: no document shape named {}", name)])
+                .into_response()
+        })?,
+    };
+    let count = *payload.count.get_or_insert(config.default_count);
+    let collection = payload
+        .collection
+        .get_or_insert_with(|| format!("{}_documents", shape.table.as_str()))
+        .clone();
+
+    let mut errors = shape.validate("shape");
+    if count == 0 {
+        errors.push("count must be at least 1".to_string());
+    }
+    let rows = count.saturating_mul(shape.max_rows());
+    if rows > config.max_count {
+        errors.push(format!(
+            "count {} of documents holding up to {} rows each exceeds the limit of {} rows",
+            count,
+            shape.max_rows(),
+            config.max_count
+        ));
+    }
+    if !is_valid_database_name(&collection) {
+        errors.push(format!(
+            "collection \"{}\" is not a valid collection name",
+            collection
+        ));
+    } else if CLEAR_ORDER.contains(&collection.as_str()) || collection == ORG_CHART_COLLECTION {
+        errors.push(format!(
+            "collection \"{}\" is reserved for generated tables and runs",
+            collection
+        ));
+    }
+    if !errors.is_empty() {
+        return Err(ValidationErrors::new(errors).into_response());
+    }
+
+    let seed = *payload
+        .seed
+        .get_or_insert_with(|| SmallRng::from_entropy().gen_range(0..=i64::MAX));
+    let mut rng = SmallRng::seed_from_u64(seed as u64);
+    let documents = (0..count)
+        .map(|_| shape.generate(&mut rng))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e).into_response())?;
+    let database_error = |e: String| (StatusCode::INTERNAL_SERVER_ERROR, e).into_response();
+
+    let mut params = serde_json::Map::new();
+    params.insert(
+        DOCUMENTS_PARAMS.to_string(),
+        serde_json::to_value(&payload).map_err(|e| database_error(e.to_string()))?,
+    );
+    let mut run = GenerationRun {
+        run_id: new_run_id(&mut rng),
+        backend: DbType::Mongodb.as_str().to_string(),
+        params: Value::Object(params),
+        seed,
+        counts: BTreeMap::from([(collection.clone(), documents.len())]),
+        started_at: Utc::now().naive_utc(),
+        finished_at: None,
+        rolled_back_at: None,
+        replay_of,
+    };
+
+    let database = backends
+        .mongodb(&payload.target)
+        .await
+        .map_err(IntoResponse::into_response)?;
+    insert_run_mongodb(&database, &run)
+        .await
+        .map_err(|e| database_error(e.to_string()))?;
+    insert_documents_mongodb(&database, &collection, &documents, Some(&run.run_id))
+        .await
+        .map_err(|e| database_error(e.to_string()))?;
+    run.finished_at = Some(Utc::now().naive_utc());
+    update_run_mongodb(&database, &run)
+        .await
+        .map_err(|e| database_error(e.to_string()))?;
+    Ok(run)
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RelationRequest {
+    db_type: DbType,
+    #[serde(flatten)]
+    target: Target,
+    /// Relations to generate between the rows already in the database; each replaces the
+    /// links generated for it before.
+    relations: BTreeMap<Relation, Cardinality>,
+    /// Seed for the random generator; the same seed and rows produce the same links.
+    seed: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RelationCount {
+    relation: Relation,
+    /// Rows of the owning table that were linked.
+    rows: usize,
+    links: usize,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RelationReport {
+    relations: Vec<RelationCount>,
+    seed: i64,
 }
 
+/// Checks the number of links a relation may create against the configured limit.
+fn check_link_count(
+    relation: Relation,
+    cardinality: &Cardinality,
+    owners: usize,
+    limit: usize,
+) -> Result<(), ValidationErrors> {
+    let most = owners.saturating_mul(cardinality.per_row.max);
+    if most > limit {
+        return Err(ValidationErrors::new(vec![format!(
+            "relations.{}: up to {} links exceed the limit of {}",
+            relation.as_str(),
+            most,
+            limit
+        )]));
+    }
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/generate/relations",
+    tag = "generation",
+    request_body = RelationRequest,
+    responses(
+        (status = 200, description = "Links generated per relation", body = RelationReport),
+        (status = 422, description = "Invalid cardinality", body = ValidationErrors),
+        (status = 500, description = "Database error", body = String, content_type = "text/plain"),
+        (status = 503, description = "Backend unavailable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn generate_relations(
+    Extension(backends): Extension<Backends>,
+    Extension(config): Extension<Arc<Config>>,
+    Json(payload): Json<RelationRequest>,
+) -> Result<Json<RelationReport>, Response> {
+    let mut errors = Vec::new();
+    if payload.relations.is_empty() {
+        errors.push("relations must name at least one relation".to_string());
+    }
+    for (relation, cardinality) in &payload.relations {
+        errors.extend(cardinality.validate(&format!("relations.{}", relation.as_str())));
+    }
+    if !errors.is_empty() {
+        return Err(ValidationErrors::new(errors).into_response());
+    }
+
+    let seed = payload
+        .seed
+        .unwrap_or_else(|| SmallRng::from_entropy().gen_range(0..=i64::MAX));
+    let mut rng = SmallRng::seed_from_u64(seed as u64);
+    let database_error = |e: String| (StatusCode::INTERNAL_SERVER_ERROR, e).into_response();
+
+    let mut relations = Vec::new();
+    for (relation, cardinality) in &payload.relations {
+        let (owner_table, linked_table) = relation.tables();
+        let (rows, links) = match payload.db_type {
+            DbType::Mysql => {
+                let pool = backends
+                    .mysql(&payload.target)
+                    .await
+                    .map_err(IntoResponse::into_response)?;
+                let owners = fetch_keys_mysql(&pool, owner_table.as_str())
+                    .await
+                    .map_err(|e| database_error(e.to_string()))?;
+                let linked = fetch_keys_mysql(&pool, linked_table.as_str())
+                    .await
+                    .map_err(|e| database_error(e.to_string()))?;
+                check_link_count(*relation, cardinality, owners.len(), config.max_count)
+                    .map_err(IntoResponse::into_response)?;
+                let links = relations::link(*relation, cardinality, &owners, &linked, &mut rng);
+                replace_links_mysql(&pool, *relation, &links)
+                    .await
+                    .map_err(|e| database_error(e.to_string()))?;
+                (owners.len(), links.len())
+            }
+            DbType::Mongodb => {
+                let database = backends
+                    .mongodb(&payload.target)
+                    .await
+                    .map_err(IntoResponse::into_response)?;
+                let owners = fetch_ids_mongodb(&database, owner_table.as_str())
+                    .await
+                    .map_err(|e| database_error(e.to_string()))?;
+                let linked = fetch_ids_mongodb(&database, linked_table.as_str())
+                    .await
+                    .map_err(|e| database_error(e.to_string()))?;
+                check_link_count(*relation, cardinality, owners.len(), config.max_count)
+                    .map_err(IntoResponse::into_response)?;
+                let links = relations::link(*relation, cardinality, &owners, &linked, &mut rng);
+                replace_links_mongodb(&database, *relation, &owners, &links)
+                    .await
+                    .map_err(|e| database_error(e.to_string()))?;
+                (owners.len(), links.len())
+            }
+        };
+        relations.push(RelationCount {
+            relation: *relation,
+            rows,
+            links,
+        });
+    }
+
+    Ok(Json(RelationReport { relations, seed }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/clear",
+    tag = "data",
+    request_body = ClearRequest,
+    responses(
+        (status = 200, description = "Rows removed per table", body = ClearReport),
+        (status = 400, description = "Invalid parameters", body = String, content_type = "text/plain"),
+        (status = 500, description = "Database error", body = String, content_type = "text/plain"),
+        (status = 503, description = "Backend unavailable", body = String, content_type = "text/plain"),
+    )
+)]
 pub async fn clear_staff(
-    Extension(pool): Extension<Pool>,
-    Extension(mongodb_client): Extension<mongodb::Client>,
+    Extension(backends): Extension<Backends>,
+    Extension(config): Extension<Arc<Config>>,
     Json(payload): Json<ClearRequest>,
-) -> Result<Json<String>, (StatusCode, String)> {
-    if payload.table_name.is_empty() {
+) -> Result<Json<ClearReport>, (StatusCode, String)> {
+    let tables: Vec<&str> = if payload.all_tables {
+        clear_order(payload.db_type, payload.run_id.is_some())
+    } else if payload.table_name.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
             "❌ Table name is required".to_string(),
         ));
+    } else if table_schema(&payload.table_name).is_none() {
+        return Err((StatusCode::BAD_REQUEST, "❌ Invalid table name".to_string()));
+    } else {
+        vec![payload.table_name.as_str()]
+    };
+    if !payload.filter.is_empty() && (payload.all_tables || !payload.strategy.supports_filter()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "❌ Filters are only supported by the delete and chunked strategies on a single table"
+                .to_string(),
+        ));
+    }
+
+    let mut filter = payload.filter.clone();
+    if let Some(run_id) = &payload.run_id {
+        if !payload.strategy.supports_filter() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "❌ Clearing a single run is only supported by the delete and chunked strategies"
+                    .to_string(),
+            ));
+        }
+        filter.insert(RUN_ID_COLUMN.to_string(), Value::from(run_id.clone()));
+    }
+
+    let expected_confirmation = if payload.all_tables {
+        "all"
+    } else {
+        payload.table_name.as_str()
+    };
+    if !payload.dry_run && payload.confirm.as_deref() != Some(expected_confirmation) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "❌ Confirmation required: set confirm to \"{}\"",
+                expected_confirmation
+            ),
+        ));
     }
-    match payload.db_type.as_str() {
-        "mysql" => clear_mysql(&pool, payload.table_name)
+
+    let chunk_size = payload.chunk_size.unwrap_or(config.clear_chunk_size);
+    let mut cleared = Vec::new();
+    for table in tables {
+        let rows = match (payload.db_type, payload.dry_run) {
+            (DbType::Mysql, true) => {
+                count_rows_mysql(&backends.mysql(&payload.target).await?, table, &filter)
+                    .await
+                    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            }
+            (DbType::Mysql, false) => clear_mysql(
+                &backends.mysql(&payload.target).await?,
+                table,
+                payload.strategy,
+                &filter,
+                chunk_size,
+            )
             .await
             .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
-        "mongodb" => clear_mongodb(&mongodb_client, &payload.table_name)
+            (DbType::Mongodb, true) => {
+                count_rows_mongodb(&backends.mongodb(&payload.target).await?, table, &filter)
+                    .await
+                    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            }
+            (DbType::Mongodb, false) => clear_mongodb(
+                &backends.mongodb(&payload.target).await?,
+                table,
+                payload.strategy,
+                &filter,
+                chunk_size,
+            )
             .await
             .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
-        _ => {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                "❌ Invalid database type".to_string(),
-            ))
-        }
+        };
+        cleared.push(ClearedTable {
+            table_name: table.to_string(),
+            rows,
+        });
     }
-    Ok(Json(format!("✅ Cleared {}", payload.db_type)))
+
+    Ok(Json(ClearReport {
+        db_type: payload.db_type.as_str().to_string(),
+        strategy: payload.strategy,
+        dry_run: payload.dry_run,
+        tables: cleared,
+    }))
+}
+
+/// Tables of `CLEAR_ORDER` the backend has, leaving out those only cleared as a whole when
+/// just the rows of one run are removed.
+fn clear_order(db_type: DbType, by_run: bool) -> Vec<&'static str> {
+    CLEAR_ORDER
+        .iter()
+        .copied()
+        .filter(|table| db_type == DbType::Mysql || Relation::from_table(table).is_none())
+        .filter(|table| !by_run || !WHOLE_TABLES.contains(table))
+        .collect()
 }
 
+#[utoipa::path(
+    post,
+    path = "/data",
+    tag = "data",
+    request_body = GetRequest,
+    responses(
+        (status = 200, description = "All rows of the table, in the requested version", body = DataResponse),
+        (status = 400, description = "Invalid parameters", body = String, content_type = "text/plain"),
+        (status = 500, description = "Database error", body = String, content_type = "text/plain"),
+        (status = 503, description = "Backend unavailable", body = String, content_type = "text/plain"),
+    )
+)]
 pub async fn get_data(
-    Extension(pool): Extension<Pool>,
-    Extension(mongodb_client): Extension<mongodb::Client>,
+    Extension(backends): Extension<Backends>,
     Json(payload): Json<GetRequest>,
-) -> Result<Json<Vec<TableType>>, (StatusCode, String)> {
-    match payload.db_type.as_str() {
-        "mysql" => fetch_all_data_mysql(&pool, payload.table_name)
+) -> Result<Json<DataResponse>, (StatusCode, String)> {
+    match payload.version {
+        1 => get_data_v1(&backends, payload).await.map(DataResponse::V1),
+        2 => get_data_v2(&backends, payload).await.map(DataResponse::V2),
+        version => Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "❌ Unsupported response version {}, expected 1 or 2",
+                version
+            ),
+        )),
+    }
+    .map(Json)
+}
+
+async fn get_data_v1(
+    backends: &Backends,
+    payload: GetRequest,
+) -> Result<Vec<TableType>, (StatusCode, String)> {
+    let Json(list) = match payload.db_type {
+        DbType::Mysql => {
+            fetch_all_data_mysql(&backends.mysql(&payload.target).await?, payload.table_name)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        }
+        DbType::Mongodb => fetch_all_data_mongodb(
+            &backends.mongodb(&payload.target).await?,
+            &payload.table_name,
+        )
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+    };
+    Ok(list)
+}
+
+async fn get_data_v2(
+    backends: &Backends,
+    payload: GetRequest,
+) -> Result<DataPage, (StatusCode, String)> {
+    let Some(schema) = table_schema(&payload.table_name) else {
+        return Err((StatusCode::BAD_REQUEST, "❌ Invalid table name".to_string()));
+    };
+    let (primary_key, rows) = match payload.db_type {
+        DbType::Mysql => (
+            ColumnMetadata {
+                name: schema.primary_key.to_string(),
+                kind: ColumnKind::Integer,
+                nullable: false,
+            },
+            fetch_rows_mysql(&backends.mysql(&payload.target).await?, schema.name)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+        ),
+        DbType::Mongodb => (
+            ColumnMetadata {
+                name: "_id".to_string(),
+                kind: ColumnKind::Text,
+                nullable: false,
+            },
+            fetch_rows_mongodb(&backends.mongodb(&payload.target).await?, schema.name)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+        ),
+    };
+
+    let mut columns = vec![primary_key.clone()];
+    columns.extend(schema.columns.iter().map(ColumnMetadata::from));
+    columns.push(ColumnMetadata {
+        name: RUN_ID_COLUMN.to_string(),
+        kind: ColumnKind::Text,
+        nullable: true,
+    });
+    Ok(DataPage {
+        version: 2,
+        header: DataHeader {
+            table_name: schema.name.to_string(),
+            db_type: payload.db_type.as_str().to_string(),
+            primary_key: primary_key.name,
+            row_count: rows.len(),
+            columns,
+        },
+        rows,
+    })
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ValidateRequest {
+    db_type: DbType,
+    #[serde(flatten)]
+    target: Target,
+    table_name: TableName,
+    /// Conditions to check besides the built-in ones, e.g. `"salary >= 5000"`.
+    #[serde(default)]
+    invariants: Vec<String>,
+    #[serde(default = "default_true")]
+    builtin_invariants: bool,
+}
+
+/// Rows listed in an invariant report at most; the counts cover every row.
+const MAX_REPORTED_VIOLATIONS: usize = 100;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InvariantCount {
+    rule: String,
+    violations: usize,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Violation {
+    /// Primary key of the row, or its `_id` in MongoDB.
+    id: Value,
+    invariant: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InvariantReport {
+    table_name: TableName,
+    db_type: DbType,
+    checked: usize,
+    invariants: Vec<InvariantCount>,
+    violations: Vec<Violation>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/validate",
+    tag = "data",
+    request_body = ValidateRequest,
+    responses(
+        (status = 200, description = "Rows breaking each invariant", body = InvariantReport),
+        (status = 400, description = "Invalid parameters", body = String, content_type = "text/plain"),
+        (status = 500, description = "Database error", body = String, content_type = "text/plain"),
+        (status = 503, description = "Backend unavailable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn validate_data(
+    Extension(backends): Extension<Backends>,
+    Json(payload): Json<ValidateRequest>,
+) -> Result<Json<InvariantReport>, (StatusCode, String)> {
+    let table = payload.table_name;
+    let mut rules = BTreeMap::new();
+    rules.insert(table, payload.invariants);
+    let invariants = invariants::for_tables(&[table], &rules, payload.builtin_invariants)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("❌ {}", e)))?;
+    let schema = table_schema(table.as_str())
+        .ok_or((StatusCode::BAD_REQUEST, "❌ Invalid table name".to_string()))?;
+
+    let (id_column, rows) = match payload.db_type {
+        DbType::Mysql => (
+            schema.primary_key,
+            fetch_rows_mysql(&backends.mysql(&payload.target).await?, schema.name)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+        ),
+        DbType::Mongodb => (
+            "_id",
+            fetch_rows_mongodb(&backends.mongodb(&payload.target).await?, schema.name)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+        ),
+    };
+
+    let mut counts: Vec<InvariantCount> = invariants
+        .iter()
+        .map(|invariant| InvariantCount {
+            rule: invariant.rule.clone(),
+            violations: 0,
+        })
+        .collect();
+    let mut violations = Vec::new();
+    for row in &rows {
+        for (invariant, count) in invariants.iter().zip(counts.iter_mut()) {
+            let broken = match invariant.holds(row) {
+                Ok(holds) => (!holds).then(|| invariant.rule.clone()),
+                Err(e) => Some(e),
+            };
+            if let Some(broken) = broken {
+                count.violations += 1;
+                if violations.len() < MAX_REPORTED_VIOLATIONS {
+                    violations.push(Violation {
+                        id: row.get(id_column).cloned().unwrap_or(Value::Null),
+                        invariant: broken,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(Json(InvariantReport {
+        table_name: table,
+        db_type: payload.db_type,
+        checked: rows.len(),
+        invariants: counts,
+        violations,
+    }))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct GeoJsonRequest {
+    db_type: DbType,
+    #[serde(flatten)]
+    target: Target,
+}
+
+#[utoipa::path(
+    post,
+    path = "/data/geojson",
+    tag = "data",
+    request_body = GeoJsonRequest,
+    responses(
+        (status = 200, description = "Addresses as a GeoJSON FeatureCollection of points", body = Object, content_type = "application/geo+json"),
+        (status = 500, description = "Database error", body = String, content_type = "text/plain"),
+        (status = 503, description = "Backend unavailable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn export_geojson(
+    Extension(backends): Extension<Backends>,
+    Json(payload): Json<GeoJsonRequest>,
+) -> Result<Response, (StatusCode, String)> {
+    let rows = match payload.db_type {
+        DbType::Mysql => fetch_rows_mysql(&backends.mysql(&payload.target).await?, "address")
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+        DbType::Mongodb => fetch_rows_mongodb(&backends.mongodb(&payload.target).await?, "address")
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+    };
+    Ok((
+        [(header::CONTENT_TYPE, "application/geo+json")],
+        Json(geo::feature_collection(rows)),
+    )
+        .into_response())
+}
+
+fn mongodb_key(id: &Bson) -> Value {
+    match id.as_object_id() {
+        Some(id) => Value::String(id.to_hex()),
+        None => Value::String(id.to_string()),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/mutate",
+    tag = "data",
+    request_body = MutateRequest,
+    responses(
+        (status = 200, description = "Updated and deleted rows", body = MutationReport),
+        (status = 400, description = "Invalid parameters", body = String, content_type = "text/plain"),
+        (status = 500, description = "Database error", body = String, content_type = "text/plain"),
+        (status = 503, description = "Backend unavailable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn mutate_data(
+    Extension(backends): Extension<Backends>,
+    Json(payload): Json<MutateRequest>,
+) -> Result<Json<MutationReport>, (StatusCode, String)> {
+    let Some(schema) = table_schema(&payload.table_name) else {
+        return Err((StatusCode::BAD_REQUEST, "❌ Invalid table name".to_string()));
+    };
+    if let Some(column) = payload.filter.keys().find(|c| !schema.accepts_filter(c)) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("❌ Invalid column name: {}", column),
+        ));
+    }
+    let percents = [payload.update_percent, payload.delete_percent];
+    if percents.iter().any(|p| !(0.0..=100.0).contains(p))
+        || payload.update_percent + payload.delete_percent > 100.0
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "❌ Update and delete percentages must add up to at most 100".to_string(),
+        ));
+    }
+
+    let mut rng = SmallRng::from_entropy();
+    let report = match payload.db_type {
+        DbType::Mysql => {
+            let rows = fetch_keyed_rows_mysql(
+                &backends.mysql(&payload.target).await?,
+                &payload.table_name,
+                &payload.filter,
+            )
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            let (to_update, to_delete, skipped) = pick_mutations(
+                rows,
+                payload.update_percent,
+                payload.delete_percent,
+                &mut rng,
+            );
+            let deleted: Vec<u64> = to_delete.into_iter().map(|(key, _)| key).collect();
+
+            update_rows_mysql(&backends.mysql(&payload.target).await?, &to_update)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            delete_rows_mysql(
+                &backends.mysql(&payload.target).await?,
+                &payload.table_name,
+                &deleted,
+            )
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+            MutationReport {
+                table_name: payload.table_name,
+                updated: to_update.into_iter().map(|(key, _)| key.into()).collect(),
+                deleted: deleted.into_iter().map(Value::from).collect(),
+                skipped,
+            }
+        }
+        DbType::Mongodb => {
+            let rows = fetch_keyed_rows_mongodb(
+                &backends.mongodb(&payload.target).await?,
+                &payload.table_name,
+                &payload.filter,
+            )
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            let (to_update, to_delete, skipped) = pick_mutations(
+                rows,
+                payload.update_percent,
+                payload.delete_percent,
+                &mut rng,
+            );
+            let deleted: Vec<Bson> = to_delete.into_iter().map(|(id, _)| id).collect();
+
+            update_rows_mongodb(
+                &backends.mongodb(&payload.target).await?,
+                &payload.table_name,
+                &to_update,
+            )
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            delete_rows_mongodb(
+                &backends.mongodb(&payload.target).await?,
+                &payload.table_name,
+                &deleted,
+            )
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+            MutationReport {
+                table_name: payload.table_name,
+                updated: to_update.iter().map(|(id, _)| mongodb_key(id)).collect(),
+                deleted: deleted.iter().map(mongodb_key).collect(),
+                skipped,
+            }
+        }
+    };
+
+    Ok(Json(report))
+}
+
+#[utoipa::path(
+    post,
+    path = "/profile",
+    tag = "generation",
+    request_body = ProfileRequest,
+    responses(
+        (status = 200, description = "Saved profile", body = TableProfile),
+        (status = 400, description = "Invalid parameters", body = String, content_type = "text/plain"),
+        (status = 500, description = "Database error", body = String, content_type = "text/plain"),
+        (status = 503, description = "Backend unavailable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn profile_table(
+    Extension(backends): Extension<Backends>,
+    Json(payload): Json<ProfileRequest>,
+) -> Result<Json<TableProfile>, (StatusCode, String)> {
+    if table_schema(&payload.table_name).is_none() {
+        return Err((StatusCode::BAD_REQUEST, "❌ Invalid table name".to_string()));
+    }
+    let rows = match payload.db_type {
+        DbType::Mysql => fetch_all_data_mysql(
+            &backends.mysql(&payload.target).await?,
+            payload.table_name.clone(),
+        )
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+        DbType::Mongodb => fetch_all_data_mongodb(
+            &backends.mongodb(&payload.target).await?,
+            &payload.table_name,
+        )
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+    };
+
+    let profile =
+        profile_rows(&payload.table_name, &rows).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let name = payload
+        .name
+        .unwrap_or_else(|| format!("{}_{}", payload.db_type.as_str(), payload.table_name));
+    profile
+        .save(&name)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    Ok(Json(profile))
+}
+
+#[utoipa::path(
+    post,
+    path = "/stream",
+    tag = "streams",
+    request_body = StreamConfig,
+    responses(
+        (status = 200, description = "Started stream", body = StreamStatus),
+        (status = 400, description = "Invalid parameters", body = String, content_type = "text/plain"),
+        (status = 500, description = "Database error", body = String, content_type = "text/plain"),
+        (status = 503, description = "Backend unavailable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn start_stream(
+    Extension(backends): Extension<Backends>,
+    Extension(config): Extension<Arc<Config>>,
+    Extension(jobs): Extension<StreamJobs>,
+    Json(payload): Json<StreamConfig>,
+) -> Result<Json<StreamStatus>, (StatusCode, String)> {
+    payload
+        .validate(config.max_count as f64)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("❌ {}", e)))?;
+    let mut rng = SmallRng::from_entropy();
+    let mut params = serde_json::Map::new();
+    params.insert(
+        STREAM_PARAMS.to_string(),
+        serde_json::to_value(&payload)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+    );
+    let run = GenerationRun {
+        run_id: new_run_id(&mut rng),
+        backend: payload.db_type.as_str().to_string(),
+        params: Value::Object(params),
+        seed: rng.gen_range(0..=i64::MAX),
+        counts: BTreeMap::from([(payload.table_name.clone(), 0)]),
+        started_at: Utc::now().naive_utc(),
+        finished_at: None,
+        rolled_back_at: None,
+        replay_of: None,
+    };
+    // Also surfaces unknown connections and databases outside the allow-list before starting.
+    match payload.db_type {
+        DbType::Mysql => insert_run_mysql(&backends.mysql(&payload.target).await?, &run)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+        DbType::Mongodb => insert_run_mongodb(&backends.mongodb(&payload.target).await?, &run)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+    }
+    let id = jobs.start(payload, run, backends);
+    jobs.status(id).map(Json).ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "❌ Stream not started".to_string(),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/stream",
+    tag = "streams",
+    responses(
+        (status = 200, description = "Running and finished streams", body = Vec<StreamStatus>),
+    )
+)]
+pub async fn list_streams(Extension(jobs): Extension<StreamJobs>) -> Json<Vec<StreamStatus>> {
+    Json(jobs.list())
+}
+
+#[utoipa::path(
+    get,
+    path = "/stream/{id}",
+    tag = "streams",
+    params(("id" = u64, Path, description = "Stream id")),
+    responses(
+        (status = 200, description = "Stream status", body = StreamStatus),
+        (status = 404, description = "Stream not found", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn get_stream(
+    Extension(jobs): Extension<StreamJobs>,
+    Path(id): Path<u64>,
+) -> Result<Json<StreamStatus>, (StatusCode, String)> {
+    jobs.status(id)
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, "❌ Stream not found".to_string()))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/stream/{id}",
+    tag = "streams",
+    params(("id" = u64, Path, description = "Stream id")),
+    responses(
+        (status = 200, description = "Final stream status", body = StreamStatus),
+        (status = 404, description = "Stream not found", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn cancel_stream(
+    Extension(backends): Extension<Backends>,
+    Extension(jobs): Extension<StreamJobs>,
+    Path(id): Path<u64>,
+) -> Result<Json<StreamStatus>, (StatusCode, String)> {
+    jobs.cancel(id, &backends)
+        .await
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, "❌ Stream not found".to_string()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/runs",
+    tag = "runs",
+    params(
+        ("db_type" = DbType, Query, description = "mysql or mongodb"),
+        ("connection" = Option<String>, Query, description = "Named connection"),
+        ("database" = Option<String>, Query, description = "Allow-listed database on that connection"),
+    ),
+    responses(
+        (status = 200, description = "Generation runs", body = Vec<GenerationRun>),
+        (status = 400, description = "Invalid parameters", body = String, content_type = "text/plain"),
+        (status = 500, description = "Database error", body = String, content_type = "text/plain"),
+        (status = 503, description = "Backend unavailable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn list_runs(
+    Extension(backends): Extension<Backends>,
+    query: Result<Query<RunsQuery>, QueryRejection>,
+) -> Result<Json<Vec<GenerationRun>>, (StatusCode, String)> {
+    let Query(query) = query.map_err(query_rejection)?;
+    match query.db_type {
+        DbType::Mysql => fetch_runs_mysql(&backends.mysql(&query.target).await?)
             .await
+            .map(Json)
             .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
-        "mongodb" => fetch_all_data_mongodb(&mongodb_client, &payload.table_name)
+        DbType::Mongodb => fetch_runs_mongodb(&backends.mongodb(&query.target).await?)
             .await
+            .map(Json)
             .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
-        _ => Err((
+    }
+}
+
+/// Answers malformed query parameters with 422, like malformed request bodies.
+fn query_rejection(rejection: QueryRejection) -> (StatusCode, String) {
+    (
+        StatusCode::UNPROCESSABLE_ENTITY,
+        format!("❌ {}", rejection.body_text()),
+    )
+}
+
+async fn find_run(
+    backends: &Backends,
+    target: &Target,
+    db_type: DbType,
+    run_id: &str,
+) -> Result<GenerationRun, (StatusCode, String)> {
+    let run = match db_type {
+        DbType::Mysql => fetch_run_mysql(&backends.mysql(target).await?, run_id)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+        DbType::Mongodb => fetch_run_mongodb(&backends.mongodb(target).await?, run_id)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+    };
+    run.ok_or((StatusCode::NOT_FOUND, "❌ Run not found".to_string()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/runs/{run_id}",
+    tag = "runs",
+    params(
+        ("run_id" = String, Path, description = "Run id"),
+        ("db_type" = DbType, Query, description = "mysql or mongodb"),
+        ("connection" = Option<String>, Query, description = "Named connection"),
+        ("database" = Option<String>, Query, description = "Allow-listed database on that connection"),
+    ),
+    responses(
+        (status = 200, description = "Generation run", body = GenerationRun),
+        (status = 404, description = "Run not found", body = String, content_type = "text/plain"),
+        (status = 400, description = "Invalid parameters", body = String, content_type = "text/plain"),
+        (status = 500, description = "Database error", body = String, content_type = "text/plain"),
+        (status = 503, description = "Backend unavailable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn get_run(
+    Extension(backends): Extension<Backends>,
+    Path(run_id): Path<String>,
+    query: Result<Query<RunsQuery>, QueryRejection>,
+) -> Result<Json<GenerationRun>, (StatusCode, String)> {
+    let Query(query) = query.map_err(query_rejection)?;
+    find_run(&backends, &query.target, query.db_type, &run_id)
+        .await
+        .map(Json)
+}
+
+/// Generates the rows of an earlier run again, with the same parameters and seed, as a new run.
+#[utoipa::path(
+    post,
+    path = "/runs/{run_id}/replay",
+    tag = "runs",
+    params(("run_id" = String, Path, description = "Run id")),
+    request_body = RunsQuery,
+    responses(
+        (status = 200, description = "New run with the same rows", body = GenerationRun),
+        (status = 404, description = "Run not found", body = String, content_type = "text/plain"),
+        (status = 422, description = "Stored parameters are no longer valid", body = ValidationErrors),
+        (status = 400, description = "Invalid parameters", body = String, content_type = "text/plain"),
+        (status = 500, description = "Database error", body = String, content_type = "text/plain"),
+        (status = 503, description = "Backend unavailable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn replay_run(
+    Extension(backends): Extension<Backends>,
+    Extension(config): Extension<Arc<Config>>,
+    Path(run_id): Path<String>,
+    Json(query): Json<RunsQuery>,
+) -> Result<Json<GenerationRun>, Response> {
+    let run = find_run(&backends, &query.target, query.db_type, &run_id)
+        .await
+        .map_err(IntoResponse::into_response)?;
+    if let Some(params) = run.params.get(ORG_CHART_PARAMS) {
+        let payload: OrgChartRequest = serde_json::from_value(params.clone())
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response())?;
+        return run_org_chart(&backends, &config, payload, Some(run.run_id))
+            .await
+            .map(|(run, _)| Json(run));
+    }
+    if run.params.get(STREAM_PARAMS).is_some() {
+        return Err((
             StatusCode::BAD_REQUEST,
-            "❌ Invalid database type".to_string(),
-        )),
+            "❌ Stream runs cannot be replayed; start a new stream instead".to_string(),
+        )
+            .into_response());
+    }
+    if let Some(params) = run.params.get(DOCUMENTS_PARAMS) {
+        let payload: DocumentRequest = serde_json::from_value(params.clone())
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response())?;
+        return run_documents(&backends, &config, payload, Some(run.run_id))
+            .await
+            .map(Json);
+    }
+    let payload: GenerateRequest = serde_json::from_value(run.params)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response())?;
+    payload
+        .validate(&config)
+        .map_err(IntoResponse::into_response)?;
+    run_generation(&backends, &config, payload, Some(run.run_id))
+        .await
+        .map(Json)
+        .map_err(IntoResponse::into_response)
+}
+
+/// Collection outside the generated tables holding a MongoDB run's documents, if any.
+fn run_collection(run: &GenerationRun) -> Option<String> {
+    if run.backend != DbType::Mongodb.as_str() {
+        return None;
+    }
+    if run.params.get(ORG_CHART_PARAMS).is_some() {
+        return Some(ORG_CHART_COLLECTION.to_string());
+    }
+    run.params
+        .get(DOCUMENTS_PARAMS)
+        .and_then(|params| params.get("collection"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+/// Deletes every row tagged with the run id, leaving rows of other runs untouched.
+#[utoipa::path(
+    post,
+    path = "/runs/{run_id}/rollback",
+    tag = "runs",
+    params(("run_id" = String, Path, description = "Run id")),
+    request_body = RollbackRequest,
+    responses(
+        (status = 200, description = "Rows removed per table", body = ClearReport),
+        (status = 404, description = "Run not found", body = String, content_type = "text/plain"),
+        (status = 400, description = "Invalid parameters", body = String, content_type = "text/plain"),
+        (status = 500, description = "Database error", body = String, content_type = "text/plain"),
+        (status = 503, description = "Backend unavailable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn rollback_run(
+    Extension(backends): Extension<Backends>,
+    Path(run_id): Path<String>,
+    Json(payload): Json<RollbackRequest>,
+) -> Result<Json<ClearReport>, (StatusCode, String)> {
+    let mut run = find_run(&backends, &payload.target, payload.db_type, &run_id).await?;
+    if !payload.dry_run && payload.confirm.as_deref() != Some(run.run_id.as_str()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "❌ Confirmation required: set confirm to \"{}\"",
+                run.run_id
+            ),
+        ));
+    }
+
+    let mut filter = Row::new();
+    filter.insert(RUN_ID_COLUMN.to_string(), Value::from(run.run_id.clone()));
+
+    let db_type = if run.backend == DbType::Mysql.as_str() {
+        DbType::Mysql
+    } else {
+        DbType::Mongodb
+    };
+    let mut cleared = Vec::new();
+    for table in clear_order(db_type, true) {
+        let rows = match (run.backend.as_str(), payload.dry_run) {
+            ("mysql", true) => {
+                count_rows_mysql(&backends.mysql(&payload.target).await?, table, &filter)
+                    .await
+                    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            }
+            ("mysql", false) => clear_mysql(
+                &backends.mysql(&payload.target).await?,
+                table,
+                ClearStrategy::Delete,
+                &filter,
+                DEFAULT_CHUNK_SIZE,
+            )
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+            (_, true) => {
+                count_rows_mongodb(&backends.mongodb(&payload.target).await?, table, &filter)
+                    .await
+                    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            }
+            (_, false) => clear_mongodb(
+                &backends.mongodb(&payload.target).await?,
+                table,
+                ClearStrategy::Delete,
+                &filter,
+                DEFAULT_CHUNK_SIZE,
+            )
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+        };
+        cleared.push(ClearedTable {
+            table_name: table.to_string(),
+            rows,
+        });
+    }
+    if let Some(collection) = run_collection(&run) {
+        let rows = clear_run_documents_mongodb(
+            &backends.mongodb(&payload.target).await?,
+            &collection,
+            &run.run_id,
+            payload.dry_run,
+        )
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        cleared.push(ClearedTable {
+            table_name: collection,
+            rows,
+        });
     }
+
+    if !payload.dry_run {
+        run.rolled_back_at = Some(Utc::now().naive_utc());
+        match run.backend.as_str() {
+            "mysql" => update_run_mysql(&backends.mysql(&payload.target).await?, &run)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+            _ => update_run_mongodb(&backends.mongodb(&payload.target).await?, &run)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+        }
+    }
+
+    Ok(Json(ClearReport {
+        db_type: run.backend,
+        strategy: ClearStrategy::Delete,
+        dry_run: payload.dry_run,
+        tables: cleared,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "connections",
+    responses(
+        (status = 200, description = "Status of every connection", body = HealthReport),
+    )
+)]
+pub async fn health(Extension(backends): Extension<Backends>) -> Json<HealthReport> {
+    Json(backends.health().await)
+}
+
+#[utoipa::path(
+    get,
+    path = "/connections",
+    tag = "connections",
+    responses(
+        (status = 200, description = "Named connections", body = Vec<ConnectionProfile>),
+    )
+)]
+pub async fn list_connections(
+    Extension(backends): Extension<Backends>,
+) -> Json<Vec<ConnectionProfile>> {
+    Json(backends.profiles())
+}
+
+#[utoipa::path(
+    get,
+    path = "/connections/{name}",
+    tag = "connections",
+    params(("name" = String, Path, description = "Connection name")),
+    responses(
+        (status = 200, description = "Named connection", body = ConnectionProfile),
+        (status = 404, description = "Connection not found", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn get_connection(
+    Extension(backends): Extension<Backends>,
+    Path(name): Path<String>,
+) -> Result<Json<ConnectionProfile>, (StatusCode, String)> {
+    backends.profile(&name).map(Json).ok_or((
+        StatusCode::NOT_FOUND,
+        format!("❌ Unknown connection: {}", name),
+    ))
+}
+
+/// Adds or replaces a named connection; it is saved and survives restarts.
+#[utoipa::path(
+    put,
+    path = "/connections/{name}",
+    tag = "connections",
+    params(("name" = String, Path, description = "Connection name")),
+    request_body = ConnectionConfig,
+    responses(
+        (status = 200, description = "Registered connection", body = ConnectionProfile),
+        (status = 400, description = "Invalid parameters", body = String, content_type = "text/plain"),
+        (status = 409, description = "Connection is defined in the configuration", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn register_connection(
+    Extension(backends): Extension<Backends>,
+    Path(name): Path<String>,
+    Json(payload): Json<ConnectionConfig>,
+) -> Result<Json<ConnectionProfile>, (StatusCode, String)> {
+    backends.register(&name, payload).await.map(Json)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/connections/{name}",
+    tag = "connections",
+    params(("name" = String, Path, description = "Connection name")),
+    responses(
+        (status = 200, description = "Removed connection", body = ConnectionProfile),
+        (status = 404, description = "Connection not found", body = String, content_type = "text/plain"),
+        (status = 409, description = "Connection is defined in the configuration", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn remove_connection(
+    Extension(backends): Extension<Backends>,
+    Path(name): Path<String>,
+) -> Result<Json<ConnectionProfile>, (StatusCode, String)> {
+    backends.remove(&name).await.map(Json)
+}
+
+#[utoipa::path(
+    post,
+    path = "/connections/{name}/test",
+    tag = "connections",
+    params(("name" = String, Path, description = "Connection name")),
+    responses(
+        (status = 200, description = "Result of a fresh connection attempt", body = ConnectionHealth),
+        (status = 404, description = "Connection not found", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn test_connection(
+    Extension(backends): Extension<Backends>,
+    Path(name): Path<String>,
+) -> Result<Json<ConnectionHealth>, (StatusCode, String)> {
+    backends.test(&name).await.map(Json)
+}
+
+/// Tries connection settings without registering them.
+#[utoipa::path(
+    post,
+    path = "/connections/test",
+    tag = "connections",
+    request_body = ConnectionConfig,
+    responses(
+        (status = 200, description = "Result of a connection attempt", body = ConnectionHealth),
+        (status = 400, description = "Invalid parameters", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn test_connection_settings(
+    Json(payload): Json<ConnectionConfig>,
+) -> Result<Json<ConnectionHealth>, (StatusCode, String)> {
+    Backends::test_settings(&payload).await.map(Json)
+}
+
+/// Returns the most recent audit log entries, oldest first.
+#[utoipa::path(
+    get,
+    path = "/audit",
+    tag = "admin",
+    params(AuditQuery),
+    responses(
+        (status = 200, description = "Audit log entries", body = Vec<AuditEntry>),
+        (status = 500, description = "Database error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn list_audit(
+    Extension(auth): Extension<Auth>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<Vec<AuditEntry>>, (StatusCode, String)> {
+    auth.entries(query.limit.unwrap_or(DEFAULT_AUDIT_LIMIT))
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("❌ {}", e)))
 }
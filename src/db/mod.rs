@@ -1,4 +1,7 @@
+pub mod backends;
+pub mod clear;
 pub mod database_handler;
 pub mod mongodb;
 pub mod mysql;
+pub mod schema;
 pub mod table_type_mysql;
@@ -1,24 +1,47 @@
-use crate::db::table_type_mysql::TableType;
+use crate::db::clear::{ClearStrategy, WHOLE_TABLES};
+use crate::db::schema::RUN_ID_COLUMN;
+use crate::db::table_type_mysql::{Row, TableType};
+use crate::generator::org::OrgMember;
+use crate::generator::relations::{Link, Relation};
 use crate::models;
 use crate::models::{
-    address::Address, contract::Contract, employee::Employee, payment::Payment, project::Project,
-    task::Task, technology::Technology,
+    address::Address, contract::Contract, employee::Employee, generation_run::GenerationRun,
+    payment::Payment, project::Project, task::Task, technology::Technology,
 };
 use axum::Json;
-use chrono::NaiveDate;
+use chrono::{NaiveDate, NaiveDateTime};
 use futures::stream::StreamExt;
 use mongodb::{
-    bson::{doc, to_bson, Bson, Document},
+    bson::{doc, from_document, oid::ObjectId, to_bson, Bson, DateTime, Document},
     error::Result,
-    options::ClientOptions,
-    Client, Collection,
+    options::{ClientOptions, CreateCollectionOptions, FindOptions, TimeseriesOptions},
+    Client, Collection, Database, IndexModel,
 };
+use serde_json::Value;
+use std::time::Duration;
 
-pub async fn connect_mongodb(uri: &str) -> Result<Client> {
-    let client_options = ClientOptions::parse(uri).await?;
+/// Keeps requests against an unreachable server from hanging for the driver's default 30s.
+const SERVER_SELECTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Top-level field holding the `2dsphere`-indexed point of an address document.
+const LOCATION_FIELD: &str = "location";
+
+/// Top-level field of an employee document holding their manager's `_id`.
+const MANAGER_ID_FIELD: &str = "manager_id";
+
+/// Field of contract documents holding the `_id` of their employee.
+const EMPLOYEE_ID_FIELD: &str = "employee_id";
+
+/// Collection of org charts stored as nested documents.
+pub const ORG_CHART_COLLECTION: &str = "org_chart";
+
+pub async fn connect_mongodb(uri: &str, database: &str, max_pool_size: u32) -> Result<Client> {
+    let mut client_options = ClientOptions::parse(uri).await?;
+    client_options.max_pool_size = Some(max_pool_size);
+    client_options.server_selection_timeout = Some(SERVER_SELECTION_TIMEOUT);
     let client = Client::with_options(client_options)?;
     client
-        .database("soft")
+        .database(database)
         .run_command(doc! {"ping": 1}, None)
         .await?;
     println!("✅ Successfully connected to MongoDB!");
@@ -26,10 +49,9 @@ pub async fn connect_mongodb(uri: &str) -> Result<Client> {
 }
 
 pub async fn fetch_all_data_mongodb(
-    client: &Client,
+    database: &Database,
     collection_name: &str,
 ) -> Result<Json<Vec<TableType>>> {
-    let database = client.database("soft");
     let collection: Collection<Document> = database.collection(collection_name);
     let mut cursor = collection.find(None, None).await?;
     let mut results = Vec::new();
@@ -60,16 +82,27 @@ fn mongodb_doc_to_table_type(collection_name: &str, doc: Document) -> Result<Tab
                     .get_str("street_number")
                     .unwrap_or_default()
                     .to_string();
-                let postal_code = address_doc
-                    .get_str("postal_code")
+                let postal_code = address_doc.get_str("postal_code").ok().map(str::to_string);
+                let voivodeship = address_doc
+                    .get_str("voivodeship")
                     .unwrap_or_default()
                     .to_string();
+                let country = address_doc
+                    .get_str("country")
+                    .unwrap_or_default()
+                    .to_string();
+                let latitude = address_doc.get_f64("latitude").unwrap_or_default();
+                let longitude = address_doc.get_f64("longitude").unwrap_or_default();
 
                 Ok(TableType::Address(Address {
                     city,
                     street,
                     street_number,
                     postal_code,
+                    voivodeship,
+                    country,
+                    latitude,
+                    longitude,
                 }))
             } else {
                 Err(mongodb::error::Error::from(std::io::Error::new(
@@ -116,14 +149,16 @@ fn mongodb_doc_to_table_type(collection_name: &str, doc: Document) -> Result<Tab
                 let start_date_str = contract_doc.get_str("start_date").unwrap_or_default();
                 let start_date = NaiveDate::parse_from_str(start_date_str, "%Y-%m-%d")
                     .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap());
-                let end_date_str = contract_doc.get_str("end_date").unwrap_or_default();
-                let end_date = NaiveDate::parse_from_str(end_date_str, "%Y-%m-%d")
-                    .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap());
-                let salary: i32 = contract_doc
-                    .get_str("salary")
-                    .unwrap_or("0")
-                    .parse::<i32>()
-                    .unwrap_or(0);
+                let end_date = contract_doc
+                    .get_str("end_date")
+                    .ok()
+                    .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok());
+                let salary: i32 = match contract_doc.get("salary") {
+                    Some(Bson::Int32(salary)) => *salary,
+                    Some(Bson::Int64(salary)) => *salary as i32,
+                    Some(Bson::String(salary)) => salary.parse::<i32>().unwrap_or(0),
+                    _ => 0,
+                };
 
                 Ok(TableType::Contract(Contract {
                     type_of_contract,
@@ -160,9 +195,13 @@ fn mongodb_doc_to_table_type(collection_name: &str, doc: Document) -> Result<Tab
                     .get_str("position")
                     .unwrap_or_default()
                     .to_string();
-                let contract_date_str = employee_doc.get_str("contract_date").unwrap_or_default();
-                let contract_date = NaiveDate::parse_from_str(contract_date_str, "%Y-%m-%d")
-                    .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap());
+                let contract_date = employee_doc
+                    .get_str("contract_date")
+                    .ok()
+                    .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok());
+
+                let department = employee_doc.get_str("department").ok().map(str::to_string);
+                let team = employee_doc.get_str("team").ok().map(str::to_string);
 
                 Ok(TableType::Employee(Employee {
                     first_name,
@@ -171,6 +210,8 @@ fn mongodb_doc_to_table_type(collection_name: &str, doc: Document) -> Result<Tab
                     phone_number,
                     position,
                     contract_date,
+                    department,
+                    team,
                 }))
             } else {
                 Err(mongodb::error::Error::from(std::io::Error::new(
@@ -181,8 +222,11 @@ fn mongodb_doc_to_table_type(collection_name: &str, doc: Document) -> Result<Tab
         }
         "payment" => {
             if let Some(Bson::Document(payment_doc)) = doc.get("Payment") {
-                let amount_str = payment_doc.get_str("amount").unwrap_or("0");
-                let amount = amount_str.parse::<f32>().unwrap_or(0.0);
+                let amount = match payment_doc.get("amount") {
+                    Some(Bson::Double(amount)) => *amount as f32,
+                    Some(Bson::String(amount)) => amount.parse::<f32>().unwrap_or(0.0),
+                    _ => 0.0,
+                };
                 let payment_due_date_str =
                     payment_doc.get_str("payment_due_date").unwrap_or_default();
                 let payment_due_date = NaiveDate::parse_from_str(payment_due_date_str, "%Y-%m-%d")
@@ -214,9 +258,10 @@ fn mongodb_doc_to_table_type(collection_name: &str, doc: Document) -> Result<Tab
                 let start_date_str = project_doc.get_str("start_date").unwrap_or_default();
                 let start_date = NaiveDate::parse_from_str(start_date_str, "%Y-%m-%d")
                     .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap());
-                let end_date_str = project_doc.get_str("end_date").unwrap_or_default();
-                let end_date = NaiveDate::parse_from_str(end_date_str, "%Y-%m-%d")
-                    .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap());
+                let end_date = project_doc
+                    .get_str("end_date")
+                    .ok()
+                    .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok());
                 let status = project_doc
                     .get_str("status")
                     .unwrap_or_default()
@@ -246,9 +291,10 @@ fn mongodb_doc_to_table_type(collection_name: &str, doc: Document) -> Result<Tab
                 let start_date_str = task_doc.get_str("start_date").unwrap_or_default();
                 let start_date = NaiveDate::parse_from_str(start_date_str, "%Y-%m-%d")
                     .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap());
-                let end_date_str = task_doc.get_str("end_date").unwrap_or_default();
-                let end_date = NaiveDate::parse_from_str(end_date_str, "%Y-%m-%d")
-                    .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap());
+                let end_date = task_doc
+                    .get_str("end_date")
+                    .ok()
+                    .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok());
                 let status = task_doc.get_str("status").unwrap_or_default().to_string();
 
                 Ok(TableType::Task(Task {
@@ -291,15 +337,73 @@ fn mongodb_doc_to_table_type(collection_name: &str, doc: Document) -> Result<Tab
     }
 }
 
-pub async fn clear_mongodb(client: &Client, collection_name: &str) -> Result<()> {
-    let database = client.database("soft");
+pub async fn count_rows_mongodb(
+    database: &Database,
+    collection_name: &str,
+    filter: &Row,
+) -> Result<u64> {
+    let query = clear_filter(collection_name, filter)?;
     let collection: Collection<Document> = database.collection(collection_name);
-    collection.delete_many(doc! {}, None).await?;
-    Ok(())
+    collection.count_documents(query, None).await
+}
+
+/// Removes documents using the given strategy and returns how many were removed.
+pub async fn clear_mongodb(
+    database: &Database,
+    collection_name: &str,
+    strategy: ClearStrategy,
+    filter: &Row,
+    chunk_size: usize,
+) -> Result<u64> {
+    let query = clear_filter(collection_name, filter)?;
+    let collection: Collection<Document> = database.collection(collection_name);
+
+    match strategy {
+        ClearStrategy::Delete | ClearStrategy::Truncate => {
+            let result = collection.delete_many(query, None).await?;
+            Ok(result.deleted_count)
+        }
+        ClearStrategy::Chunked => {
+            let options = FindOptions::builder()
+                .projection(doc! {"_id": 1})
+                .limit(chunk_size.max(1) as i64)
+                .build();
+            let mut removed = 0;
+            loop {
+                let mut cursor = collection.find(query.clone(), options.clone()).await?;
+                let mut ids = Vec::new();
+                while let Some(doc) = cursor.next().await {
+                    if let Some(id) = doc?.get("_id") {
+                        ids.push(id.clone());
+                    }
+                }
+                if ids.is_empty() {
+                    break;
+                }
+                let result = collection
+                    .delete_many(doc! {"_id": {"$in": ids}}, None)
+                    .await?;
+                removed += result.deleted_count;
+            }
+            Ok(removed)
+        }
+        ClearStrategy::Recreate => {
+            let count = collection.count_documents(query, None).await?;
+            collection.drop(None).await?;
+            // Event and run collections are created with their options on the next insert.
+            if wrapper_field(collection_name).is_some() {
+                database.create_collection(collection_name, None).await?;
+            }
+            Ok(count)
+        }
+    }
 }
 
-pub async fn insert_batch_mongodb(client: &Client, list: &Vec<TableType>) -> Result<()> {
-    let database = client.database("soft");
+pub async fn insert_batch_mongodb(
+    database: &Database,
+    list: &Vec<TableType>,
+    run_id: Option<&str>,
+) -> Result<()> {
     let mut collections: Vec<(&str, Vec<Document>)> = Vec::new();
 
     for item in list {
@@ -314,7 +418,13 @@ pub async fn insert_batch_mongodb(client: &Client, list: &Vec<TableType>) -> Res
             TableType::Technology(_) => "technology",
         };
 
-        let doc = to_bson(item)?.as_document().cloned().unwrap();
+        let mut doc = to_bson(item)?.as_document().cloned().unwrap();
+        if let Some(run_id) = run_id {
+            doc.insert(RUN_ID_COLUMN, run_id);
+        }
+        if let Some(location) = location(item) {
+            doc.insert(LOCATION_FIELD, location);
+        }
         if let Some((_, docs)) = collections
             .iter_mut()
             .find(|(name, _)| *name == collection_name)
@@ -327,8 +437,540 @@ pub async fn insert_batch_mongodb(client: &Client, list: &Vec<TableType>) -> Res
 
     for (collection_name, docs) in collections {
         let collection = database.collection::<Document>(collection_name);
+        if collection_name == "address" {
+            let index = IndexModel::builder()
+                .keys(doc! {LOCATION_FIELD: "2dsphere"})
+                .build();
+            collection.create_index(index, None).await?;
+        }
+        collection.insert_many(docs, None).await?;
+    }
+
+    Ok(())
+}
+
+/// GeoJSON point of an address, stored next to the row so `2dsphere` queries can use it.
+fn location(item: &TableType) -> Option<Document> {
+    match item {
+        TableType::Address(address) => Some(doc! {
+            "type": "Point",
+            "coordinates": [address.longitude, address.latitude],
+        }),
+        _ => None,
+    }
+}
+
+/// Inserts employees with their contracts, each contract holding the `_id` of its employee in
+/// a top-level `employee_id`. Returns the `_id`s of the employees in order.
+pub async fn insert_employee_contracts_mongodb(
+    database: &Database,
+    pairs: &[(Employee, Contract)],
+    run_id: Option<&str>,
+) -> Result<Vec<ObjectId>> {
+    let ids: Vec<ObjectId> = pairs.iter().map(|_| ObjectId::new()).collect();
+    if pairs.is_empty() {
+        return Ok(ids);
+    }
+    let mut employees = Vec::with_capacity(pairs.len());
+    for ((employee, _), id) in pairs.iter().zip(&ids) {
+        let mut doc = to_bson(&TableType::Employee(employee.clone()))?
+            .as_document()
+            .cloned()
+            .unwrap();
+        doc.insert("_id", *id);
+        if let Some(run_id) = run_id {
+            doc.insert(RUN_ID_COLUMN, run_id);
+        }
+        employees.push(doc);
+    }
+    database
+        .collection::<Document>("employee")
+        .insert_many(employees, None)
+        .await?;
+    insert_contracts_mongodb(
+        database,
+        pairs.iter().map(|(_, contract)| contract).zip(&ids),
+        run_id,
+    )
+    .await?;
+    Ok(ids)
+}
+
+/// Inserts contracts with the `_id` of the employee each belongs to.
+async fn insert_contracts_mongodb<'a>(
+    database: &Database,
+    contracts: impl Iterator<Item = (&'a Contract, &'a ObjectId)>,
+    run_id: Option<&str>,
+) -> Result<()> {
+    let mut docs = Vec::new();
+    for (contract, employee_id) in contracts {
+        let mut doc = to_bson(&TableType::Contract(contract.clone()))?
+            .as_document()
+            .cloned()
+            .unwrap();
+        doc.insert(EMPLOYEE_ID_FIELD, *employee_id);
+        if let Some(run_id) = run_id {
+            doc.insert(RUN_ID_COLUMN, run_id);
+        }
+        docs.push(doc);
+    }
+    if !docs.is_empty() {
+        let collection = database.collection::<Document>("contract");
+        collection
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! {EMPLOYEE_ID_FIELD: 1})
+                    .build(),
+                None,
+            )
+            .await?;
         collection.insert_many(docs, None).await?;
     }
+    Ok(())
+}
+
+/// Inserts an org chart as employee documents with their manager's `_id` in a top-level
+/// `manager_id`, followed by the contracts with the `_id` of their employee in `employee_id`.
+/// Returns the `_id`s of the employees in chart order.
+pub async fn insert_org_chart_mongodb(
+    database: &Database,
+    members: &[OrgMember],
+    run_id: Option<&str>,
+) -> Result<Vec<ObjectId>> {
+    let ids: Vec<ObjectId> = members.iter().map(|_| ObjectId::new()).collect();
+    let mut employees = Vec::with_capacity(members.len());
+    for (member, id) in members.iter().zip(&ids) {
+        let mut doc = to_bson(&TableType::Employee(member.employee.clone()))?
+            .as_document()
+            .cloned()
+            .unwrap();
+        doc.insert("_id", *id);
+        if let Some(manager) = member.manager {
+            doc.insert(MANAGER_ID_FIELD, ids[manager]);
+        }
+        if let Some(run_id) = run_id {
+            doc.insert(RUN_ID_COLUMN, run_id);
+        }
+        employees.push(doc);
+    }
+    if employees.is_empty() {
+        return Ok(ids);
+    }
+    let collection = database.collection::<Document>("employee");
+    collection
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! {MANAGER_ID_FIELD: 1})
+                .build(),
+            None,
+        )
+        .await?;
+    collection.insert_many(employees, None).await?;
+    insert_contracts_mongodb(
+        database,
+        members.iter().map(|member| &member.contract).zip(&ids),
+        run_id,
+    )
+    .await?;
+    Ok(ids)
+}
+
+/// Stores an org chart as a single `org_chart` document: the chief executive with their
+/// contract and level, and their reports nested the same way under `reports`. The `run_id`
+/// goes on the top-level document.
+pub async fn insert_org_tree_mongodb(
+    database: &Database,
+    members: &[OrgMember],
+    run_id: Option<&str>,
+) -> Result<()> {
+    let mut reports: Vec<Vec<Document>> = vec![Vec::new(); members.len()];
+    let mut root = None;
+    // Reports come after their manager, so walking backwards completes every subtree first.
+    for (index, member) in members.iter().enumerate().rev() {
+        let mut doc = to_bson(&TableType::Employee(member.employee.clone()))?
+            .as_document()
+            .cloned()
+            .unwrap();
+        doc.extend(
+            to_bson(&TableType::Contract(member.contract.clone()))?
+                .as_document()
+                .cloned()
+                .unwrap(),
+        );
+        doc.insert("level", member.level as i64);
+        let mut own_reports = std::mem::take(&mut reports[index]);
+        own_reports.reverse();
+        doc.insert("reports", own_reports);
+        match member.manager {
+            Some(manager) => reports[manager].push(doc),
+            None => root = Some(doc),
+        }
+    }
+    if let Some(mut root) = root {
+        if let Some(run_id) = run_id {
+            root.insert(RUN_ID_COLUMN, run_id);
+        }
+        let collection = database.collection::<Document>(ORG_CHART_COLLECTION);
+        collection.insert_one(root, None).await?;
+    }
+    Ok(())
+}
+
+/// Removes a run's documents from a collection outside the generated tables, such as
+/// `org_chart`, or only counts them when `dry_run` is set.
+pub async fn clear_run_documents_mongodb(
+    database: &Database,
+    collection_name: &str,
+    run_id: &str,
+    dry_run: bool,
+) -> Result<u64> {
+    let collection = database.collection::<Document>(collection_name);
+    let query = doc! {RUN_ID_COLUMN: run_id};
+    if dry_run {
+        collection.count_documents(query, None).await
+    } else {
+        Ok(collection.delete_many(query, None).await?.deleted_count)
+    }
+}
 
+/// Inserts generated documents as they are, nested fields and arrays included.
+pub async fn insert_documents_mongodb(
+    database: &Database,
+    collection_name: &str,
+    documents: &[Row],
+    run_id: Option<&str>,
+) -> Result<()> {
+    let mut docs = Vec::with_capacity(documents.len());
+    for document in documents {
+        let mut doc = to_bson(document)?.as_document().cloned().unwrap();
+        if let Some(run_id) = run_id {
+            doc.insert(RUN_ID_COLUMN, run_id);
+        }
+        docs.push(doc);
+    }
+    if docs.is_empty() {
+        return Ok(());
+    }
+    let collection = database.collection::<Document>(collection_name);
+    collection.insert_many(docs, None).await?;
     Ok(())
 }
+
+/// `_id`s of every document of a collection, in order.
+pub async fn fetch_ids_mongodb(database: &Database, collection_name: &str) -> Result<Vec<Bson>> {
+    let collection: Collection<Document> = database.collection(collection_name);
+    let options = FindOptions::builder()
+        .projection(doc! {"_id": 1})
+        .sort(doc! {"_id": 1})
+        .build();
+    let mut cursor = collection.find(None, options).await?;
+    let mut ids = Vec::new();
+    while let Some(doc) = cursor.next().await {
+        if let Some(id) = doc?.get("_id") {
+            ids.push(id.clone());
+        }
+    }
+    Ok(ids)
+}
+
+/// Sets the relation's array of references on every owning document to its links, replacing
+/// earlier ones; owners without links get an empty array. Links with a skill level are stored
+/// as `{technology_id, skill_level}` documents.
+pub async fn replace_links_mongodb(
+    database: &Database,
+    relation: Relation,
+    owners: &[Bson],
+    links: &[Link<Bson>],
+) -> Result<()> {
+    let (owner_table, linked_table) = relation.tables();
+    let linked_key = format!("{}_id", linked_table.as_str());
+    let mut references: Vec<(Bson, Vec<Bson>)> = owners
+        .iter()
+        .map(|owner| (owner.clone(), Vec::new()))
+        .collect();
+    let mut position = 0;
+    for link in links {
+        // Links come grouped by owner, in the order of `owners`.
+        let Some(offset) = references[position..]
+            .iter()
+            .position(|(owner, _)| *owner == link.owner)
+        else {
+            continue;
+        };
+        position += offset;
+        let reference = match link.skill_level {
+            Some(skill_level) => Bson::Document(doc! {
+                &linked_key: link.linked.clone(),
+                "skill_level": skill_level as i32,
+            }),
+            None => link.linked.clone(),
+        };
+        references[position].1.push(reference);
+    }
+
+    let collection: Collection<Document> = database.collection(owner_table.as_str());
+    for (owner, references) in references {
+        collection
+            .update_one(
+                doc! {"_id": owner},
+                doc! {"$set": {relation.field(): references}},
+                None,
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+/// Converts an event into a flat document with `occurred_at` stored as a BSON date,
+/// as required by time-series collections.
+pub fn event_to_document<T: serde::Serialize>(
+    event: &T,
+    occurred_at: NaiveDateTime,
+) -> Result<Document> {
+    let mut doc = to_bson(event)?.as_document().cloned().unwrap();
+    doc.insert(
+        "occurred_at",
+        DateTime::from_millis(occurred_at.and_utc().timestamp_millis()),
+    );
+    Ok(doc)
+}
+
+pub async fn insert_events_mongodb(
+    database: &Database,
+    collection_name: &str,
+    meta_field: &str,
+    docs: Vec<Document>,
+    time_series: bool,
+) -> Result<()> {
+    if time_series {
+        let existing = database
+            .list_collection_names(doc! {"name": collection_name})
+            .await?;
+        if existing.is_empty() {
+            let timeseries = TimeseriesOptions::builder()
+                .time_field("occurred_at".to_string())
+                .meta_field(Some(meta_field.to_string()))
+                .build();
+            let options = CreateCollectionOptions::builder()
+                .timeseries(timeseries)
+                .build();
+            database.create_collection(collection_name, options).await?;
+        }
+    }
+
+    if docs.is_empty() {
+        return Ok(());
+    }
+    let collection = database.collection::<Document>(collection_name);
+    collection.insert_many(docs, None).await?;
+    Ok(())
+}
+
+async fn random_ids_mongodb(collection: &Collection<Document>, count: usize) -> Result<Vec<Bson>> {
+    let pipeline = [
+        doc! {"$sample": {"size": count as i64}},
+        doc! {"$project": {"_id": 1}},
+    ];
+    let mut cursor = collection.aggregate(pipeline, None).await?;
+    let mut ids = Vec::new();
+    while let Some(doc) = cursor.next().await {
+        if let Some(id) = doc?.get("_id") {
+            ids.push(id.clone());
+        }
+    }
+    Ok(ids)
+}
+
+/// Replaces up to `count` random documents with freshly generated ones, keeping their `_id`.
+pub async fn update_random_mongodb(
+    database: &Database,
+    collection_name: &str,
+    count: usize,
+) -> Result<Vec<Bson>> {
+    let collection: Collection<Document> = database.collection(collection_name);
+    let ids = random_ids_mongodb(&collection, count).await?;
+    let rows = TableType::generate_batch(collection_name, ids.len()).unwrap_or_default();
+
+    let keyed: Vec<(Bson, TableType)> = ids.iter().cloned().zip(rows).collect();
+    update_rows_mongodb(database, collection_name, &keyed).await?;
+    Ok(ids)
+}
+
+/// Deletes up to `count` random documents and returns their `_id`s.
+pub async fn delete_random_mongodb(
+    database: &Database,
+    collection_name: &str,
+    count: usize,
+) -> Result<Vec<Bson>> {
+    let collection: Collection<Document> = database.collection(collection_name);
+    let ids = random_ids_mongodb(&collection, count).await?;
+    delete_rows_mongodb(database, collection_name, &ids).await?;
+    Ok(ids)
+}
+
+/// Name of the field wrapping the row in documents written by `insert_batch_mongodb`.
+fn wrapper_field(collection_name: &str) -> Option<&'static str> {
+    match collection_name {
+        "address" => Some("Address"),
+        "client" => Some("Client"),
+        "contract" => Some("Contract"),
+        "employee" => Some("Employee"),
+        "payment" => Some("Payment"),
+        "project" => Some("Project"),
+        "task" => Some("Task"),
+        "technology" => Some("Technology"),
+        _ => None,
+    }
+}
+
+/// Query for a collection in `CLEAR_ORDER`: events and the run log are only cleared whole.
+fn clear_filter(collection_name: &str, filter: &Row) -> Result<Document> {
+    if !WHOLE_TABLES.contains(&collection_name) {
+        return row_filter(collection_name, filter);
+    }
+    if !filter.is_empty() {
+        return Err(mongodb::error::Error::from(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("{} can only be cleared as a whole", collection_name),
+        )));
+    }
+    Ok(Document::new())
+}
+
+/// Builds a query matching documents whose fields equal the values in `filter`.
+fn row_filter(collection_name: &str, filter: &Row) -> Result<Document> {
+    let wrapper = wrapper_field(collection_name).ok_or_else(|| {
+        mongodb::error::Error::from(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Unknown collection",
+        ))
+    })?;
+    let mut query = Document::new();
+    for (field, value) in filter {
+        if field == RUN_ID_COLUMN {
+            query.insert(RUN_ID_COLUMN, to_bson(value)?);
+        } else {
+            query.insert(format!("{}.{}", wrapper, field), to_bson(value)?);
+        }
+    }
+    Ok(query)
+}
+
+/// Documents as plain rows: `_id` as a hex string, the row's columns and `run_id`.
+pub async fn fetch_rows_mongodb(database: &Database, collection_name: &str) -> Result<Vec<Row>> {
+    let collection: Collection<Document> = database.collection(collection_name);
+    let options = FindOptions::builder().sort(doc! {"_id": 1}).build();
+    let mut cursor = collection.find(None, options).await?;
+    let mut results = Vec::new();
+    while let Some(doc) = cursor.next().await {
+        let doc = doc?;
+        let id = match doc.get("_id") {
+            Some(Bson::ObjectId(id)) => Value::from(id.to_hex()),
+            Some(id) => id.clone().into_relaxed_extjson(),
+            None => continue,
+        };
+        let run_id = doc
+            .get_str(RUN_ID_COLUMN)
+            .map(Value::from)
+            .unwrap_or(Value::Null);
+        match mongodb_doc_to_table_type(collection_name, doc) {
+            Ok(entry) => {
+                let mut row = Row::new();
+                row.insert("_id".to_string(), id);
+                row.extend(entry.to_row());
+                row.insert(RUN_ID_COLUMN.to_string(), run_id);
+                results.push(row);
+            }
+            Err(e) => println!("❌ Nie udało się sparsować {}: {}", id, e),
+        }
+    }
+    Ok(results)
+}
+
+/// Reads the documents of a collection together with their `_id`s.
+/// `filter` restricts the result to documents whose fields equal the given values.
+pub async fn fetch_keyed_rows_mongodb(
+    database: &Database,
+    collection_name: &str,
+    filter: &Row,
+) -> Result<Vec<(Bson, TableType)>> {
+    let query = row_filter(collection_name, filter)?;
+    let collection: Collection<Document> = database.collection(collection_name);
+    let mut cursor = collection.find(query, None).await?;
+    let mut results = Vec::new();
+    while let Some(doc) = cursor.next().await {
+        let doc = doc?;
+        let Some(id) = doc.get("_id").cloned() else {
+            continue;
+        };
+        match mongodb_doc_to_table_type(collection_name, doc) {
+            Ok(entry) => results.push((id, entry)),
+            Err(e) => println!("❌ Nie udało się sparsować {}: {}", id, e),
+        }
+    }
+    Ok(results)
+}
+
+/// Overwrites the row stored in each document, keeping top-level fields such as `run_id`.
+pub async fn update_rows_mongodb(
+    database: &Database,
+    collection_name: &str,
+    list: &[(Bson, TableType)],
+) -> Result<()> {
+    let collection: Collection<Document> = database.collection(collection_name);
+    for (id, row) in list {
+        let mut fields = to_bson(row)?.as_document().cloned().unwrap();
+        if let Some(location) = location(row) {
+            fields.insert(LOCATION_FIELD, location);
+        }
+        collection
+            .update_one(doc! {"_id": id.clone()}, doc! {"$set": fields}, None)
+            .await?;
+    }
+    Ok(())
+}
+
+pub async fn delete_rows_mongodb(
+    database: &Database,
+    collection_name: &str,
+    ids: &[Bson],
+) -> Result<()> {
+    let collection: Collection<Document> = database.collection(collection_name);
+    collection
+        .delete_many(doc! {"_id": {"$in": ids.to_vec()}}, None)
+        .await?;
+    Ok(())
+}
+
+pub async fn insert_run_mongodb(database: &Database, run: &GenerationRun) -> Result<()> {
+    let collection: Collection<GenerationRun> = database.collection("generation_runs");
+    collection.insert_one(run, None).await?;
+    Ok(())
+}
+
+pub async fn update_run_mongodb(database: &Database, run: &GenerationRun) -> Result<()> {
+    let collection: Collection<GenerationRun> = database.collection("generation_runs");
+    collection
+        .replace_one(doc! {"run_id": &run.run_id}, run, None)
+        .await?;
+    Ok(())
+}
+
+pub async fn fetch_runs_mongodb(database: &Database) -> Result<Vec<GenerationRun>> {
+    let collection: Collection<Document> = database.collection("generation_runs");
+    let options = FindOptions::builder().sort(doc! {"started_at": -1}).build();
+    let mut cursor = collection.find(None, options).await?;
+    let mut runs = Vec::new();
+    while let Some(doc) = cursor.next().await {
+        match from_document::<GenerationRun>(doc?) {
+            Ok(run) => runs.push(run),
+            Err(e) => println!("❌ Nie udało się sparsować: {}", e),
+        }
+    }
+    Ok(runs)
+}
+
+pub async fn fetch_run_mongodb(database: &Database, run_id: &str) -> Result<Option<GenerationRun>> {
+    let collection: Collection<GenerationRun> = database.collection("generation_runs");
+    collection.find_one(doc! {"run_id": run_id}, None).await
+}
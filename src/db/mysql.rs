@@ -1,17 +1,34 @@
-use crate::db::table_type_mysql::{GetParams, TableType};
+use crate::db::clear::{ClearStrategy, WHOLE_TABLES};
+use crate::db::schema::{table_schema, ColumnKind, TableName, TableSchema, RUN_ID_COLUMN};
+use crate::db::table_type_mysql::{GetParams, Row, TableType};
+use crate::generator::org::OrgMember;
+use crate::generator::relations::{Link, Relation};
 use axum::Json;
-use chrono::NaiveDate;
+use chrono::{NaiveDate, NaiveDateTime};
 use futures::future::join_all;
 use mysql_async::prelude::*;
-use mysql_async::{Conn, Error, Opts, Pool};
+use mysql_async::{Conn, Error, Opts, OptsBuilder, Params, Pool, PoolConstraints, PoolOpts};
+use rand::Rng;
 
 use crate::models::{
-    address::Address, client::Client, contract::Contract, employee::Employee, payment::Payment,
-    project::Project, task::Task, technology::Technology,
+    address::Address, client::Client, contract::Contract, employee::Employee,
+    generation_run::GenerationRun, payment::Payment, payment_event::PaymentEvent, project::Project,
+    task::Task, task_status_event::TaskStatusEvent, technology::Technology,
 };
 
-pub async fn connect_mysql(database_url: &str) -> Result<Pool, Error> {
-    let opts = Opts::from_url(database_url)?;
+pub async fn connect_mysql(
+    database_url: &str,
+    database: Option<&str>,
+    pool_min: usize,
+    pool_max: usize,
+) -> Result<Pool, Error> {
+    let constraints = PoolConstraints::new(pool_min, pool_max)
+        .ok_or_else(|| Error::Other("Invalid MySQL pool size".into()))?;
+    let mut opts = OptsBuilder::from_opts(Opts::from_url(database_url)?)
+        .pool_opts(PoolOpts::new().with_constraints(constraints));
+    if let Some(database) = database {
+        opts = opts.db_name(Some(database));
+    }
     let pool = Pool::new(opts);
 
     let mut conn: Conn = pool.get_conn().await?;
@@ -21,6 +38,17 @@ pub async fn connect_mysql(database_url: &str) -> Result<Pool, Error> {
     Ok(pool)
 }
 
+type EmployeeRow = (
+    String,
+    String,
+    String,
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+);
+
 pub async fn fetch_all_data_mysql(
     pool: &Pool,
     table_name: String,
@@ -30,18 +58,26 @@ pub async fn fetch_all_data_mysql(
         "address" => {
             let address_list: Vec<TableType> = conn
                 .query_map(
-                    "SELECT city, street, street_number, postal_code FROM address",
-                    |(city, street, street_number, postal_code): (
+                    "SELECT city, street, street_number, postal_code, voivodeship, country, latitude, longitude FROM address",
+                    |(city, street, street_number, postal_code, voivodeship, country, latitude, longitude): (
                         String,
                         String,
                         String,
+                        Option<String>,
                         String,
+                        String,
+                        f64,
+                        f64,
                     )| {
                         TableType::Address(Address {
                             city,
                             street,
                             street_number,
                             postal_code,
+                            voivodeship,
+                            country,
+                            latitude,
+                            longitude,
                         })
                     },
                 )
@@ -76,13 +112,14 @@ pub async fn fetch_all_data_mysql(
                     |(type_of_contract, start_date, end_date, salary): (
                         String,
                         String,
-                        String,
+                        Option<String>,
                         i32,
                     )| {
                         TableType::Contract(Contract {
                             type_of_contract,
                             start_date: NaiveDate::parse_from_str(&start_date, "%Y-%m-%d").unwrap(),
-                            end_date: NaiveDate::parse_from_str(&end_date, "%Y-%m-%d").unwrap(),
+                            end_date: end_date
+                                .and_then(|date| NaiveDate::parse_from_str(&date, "%Y-%m-%d").ok()),
                             salary,
                         })
                     },
@@ -93,22 +130,17 @@ pub async fn fetch_all_data_mysql(
         "employee" => {
             let employee_list: Vec<TableType> = conn
                 .query_map(
-                    "SELECT first_name, last_name, phone_number, email, position, contract_date FROM employee",
-                |(first_name, last_name, phone_number, email, position, contract_date): (
-                String,
-                String,
-                String,
-                String,
-                String,
-                String,
-            )| {
+                    "SELECT first_name, last_name, phone_number, email, position, contract_date, department, team FROM employee",
+                |(first_name, last_name, phone_number, email, position, contract_date, department, team): EmployeeRow| {
                 TableType::Employee(Employee {
                     first_name,
                     last_name,
                     email,
                     phone_number,
                     position,
-                    contract_date: NaiveDate::parse_from_str(&contract_date, "%Y-%m-%d").unwrap(),
+                    contract_date: contract_date.and_then(|date| NaiveDate::parse_from_str(&date, "%Y-%m-%d").ok()),
+                    department,
+                    team,
                 })
             },
         )
@@ -142,14 +174,15 @@ pub async fn fetch_all_data_mysql(
                         String,
                         String,
                         String,
-                        String,
+                        Option<String>,
                         String,
                     )| {
                         TableType::Project(Project {
                             name,
                             description,
                             start_date: NaiveDate::parse_from_str(&start_date, "%Y-%m-%d").unwrap(),
-                            end_date: NaiveDate::parse_from_str(&end_date, "%Y-%m-%d").unwrap(),
+                            end_date: end_date
+                                .and_then(|date| NaiveDate::parse_from_str(&date, "%Y-%m-%d").ok()),
                             status,
                         })
                     },
@@ -165,14 +198,15 @@ pub async fn fetch_all_data_mysql(
                         String,
                         String,
                         String,
-                        String,
+                        Option<String>,
                         String,
                     )| {
                         TableType::Task(Task {
                             name,
                             description,
                             start_date: NaiveDate::parse_from_str(&start_date, "%Y-%m-%d").unwrap(),
-                            end_date: NaiveDate::parse_from_str(&end_date, "%Y-%m-%d").unwrap(),
+                            end_date: end_date
+                                .and_then(|date| NaiveDate::parse_from_str(&date, "%Y-%m-%d").ok()),
                             status,
                         })
                     },
@@ -191,104 +225,141 @@ pub async fn fetch_all_data_mysql(
                 .await?;
             Ok(Json(technology_list))
         }
-        _ => Err(Error::from(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "Invalid table name",
-        ))),
+        _ => Err(Error::from(std::io::Error::other("Invalid table name"))),
     }
 }
 
+pub async fn count_rows_mysql(pool: &Pool, table_name: &str, filter: &Row) -> Result<u64, Error> {
+    let (name, where_clause, params) = clear_target(table_name, filter)?;
+
+    let mut conn = pool.get_conn().await?;
+    let count: Option<u64> = conn
+        .exec_first(
+            format!("SELECT COUNT(*) FROM {}{}", name, where_clause),
+            params,
+        )
+        .await?;
+    Ok(count.unwrap_or(0))
+}
+
+/// Removes rows using the given strategy and returns how many were removed.
 pub async fn clear_mysql(
     pool: &Pool,
-    table_name: String,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let mut conn = pool.get_conn().await.map_err(|e| e.to_string())?;
-    match table_name.as_str() {
-        "address" => {
-            conn.query_drop("DELETE FROM address").await?;
-        }
-        "client" => {
-            conn.query_drop("DELETE FROM client").await?;
-        }
-        "contract" => {
-            conn.query_drop("DELETE FROM contract").await?;
-        }
-        "employee" => {
-            conn.query_drop("DELETE FROM employee").await?;
-        }
-        "payment" => {
-            conn.query_drop("DELETE FROM payment").await?;
+    table_name: &str,
+    strategy: ClearStrategy,
+    filter: &Row,
+    chunk_size: usize,
+) -> Result<u64, Error> {
+    let (name, where_clause, params) = clear_target(table_name, filter)?;
+    let mut conn = pool.get_conn().await?;
+
+    match strategy {
+        ClearStrategy::Delete => {
+            conn.exec_drop(format!("DELETE FROM {}{}", name, where_clause), params)
+                .await?;
+            Ok(conn.affected_rows())
         }
-        "project" => {
-            conn.query_drop("DELETE FROM project").await?;
+        ClearStrategy::Chunked => {
+            let query = format!(
+                "DELETE FROM {}{} LIMIT {}",
+                name,
+                where_clause,
+                chunk_size.max(1)
+            );
+            let mut removed = 0;
+            loop {
+                conn.exec_drop(&query, params.clone()).await?;
+                let affected = conn.affected_rows();
+                if affected == 0 {
+                    break;
+                }
+                removed += affected;
+            }
+            Ok(removed)
         }
-        "task" => {
-            conn.query_drop("DELETE FROM task").await?;
+        ClearStrategy::Truncate => {
+            let count = count_rows_mysql(pool, table_name, filter).await?;
+            conn.query_drop(format!("TRUNCATE TABLE {}", name)).await?;
+            Ok(count)
         }
-        "technology" => {
-            conn.query_drop("DELETE FROM technology").await?;
+        ClearStrategy::Recreate => {
+            let count = count_rows_mysql(pool, table_name, filter).await?;
+            let create: Option<(String, String)> = conn
+                .query_first(format!("SHOW CREATE TABLE {}", name))
+                .await?;
+            let Some((_, create_statement)) = create else {
+                return Err(Error::from(std::io::Error::other(
+                    "Table definition not found",
+                )));
+            };
+            conn.query_drop(format!("DROP TABLE {}", name)).await?;
+            conn.query_drop(create_statement).await?;
+            Ok(count)
         }
-        _ => return Err("Invalid table name".into()),
     }
-    Ok(())
 }
 
-pub async fn parallel_insert_batch(pool: &Pool, list: &Vec<TableType>) -> Result<(), Error> {
+pub async fn parallel_insert_batch(
+    pool: &Pool,
+    list: &Vec<TableType>,
+    run_id: Option<&str>,
+) -> Result<(), Error> {
     let mut tasks = Vec::new();
 
     for item in list {
         let pool = pool.clone();
         let item = item.clone();
+        let run_id = run_id.map(str::to_string);
 
         let task = tokio::spawn(async move {
             let mut conn = pool.get_conn().await?;
             match item {
                 TableType::Technology(_) => {
                     conn.exec_drop(
-                        "INSERT IGNORE INTO technology (name, description) VALUES (?, ?)",
-                        item.get_params()
+                        "INSERT IGNORE INTO technology (name, description, run_id) VALUES (?, ?, ?)",
+                        with_run_id(item.get_params(), run_id.as_deref())
                     ).await
                 },
                 TableType::Task(_) => {
                     conn.exec_drop(
-                        "INSERT IGNORE INTO task (name, description, start_date, end_date, status) VALUES (?, ?, ?, ?, ?)",
-                        item.get_params()
+                        "INSERT IGNORE INTO task (name, description, start_date, end_date, status, run_id) VALUES (?, ?, ?, ?, ?, ?)",
+                        with_run_id(item.get_params(), run_id.as_deref())
                     ).await
                 },
                 TableType::Project(_) => {
                     conn.exec_drop(
-                        "INSERT IGNORE INTO project (name, description, start_date, end_date, status) VALUES (?, ?, ?, ?, ?)",
-                        item.get_params()
+                        "INSERT IGNORE INTO project (name, description, start_date, end_date, status, run_id) VALUES (?, ?, ?, ?, ?, ?)",
+                        with_run_id(item.get_params(), run_id.as_deref())
                     ).await
                 },
                 TableType::Payment(_) => {
                     conn.exec_drop(
-                        "INSERT IGNORE INTO payment (amount, payment_due_date, method) VALUES (?, ?, ?)",
-                        item.get_params()
+                        "INSERT IGNORE INTO payment (amount, payment_due_date, method, run_id) VALUES (?, ?, ?, ?)",
+                        with_run_id(item.get_params(), run_id.as_deref())
                     ).await
                 },
                 TableType::Employee(_) => {
                     conn.exec_drop(
-                        "INSERT IGNORE INTO employee (first_name, last_name, email, phone_number, position, contract_date) VALUES (?, ?, ?, ?, ?, ?)",
-                        item.get_params()
+                        "INSERT IGNORE INTO employee (first_name, last_name, email, phone_number, position, contract_date, department, team, run_id) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                        with_run_id(item.get_params(), run_id.as_deref())
                     ).await
                 },
                 TableType::Contract(_) => {
                     conn.exec_drop(
-                        "INSERT IGNORE INTO contract (type_of_contract, start_date, end_date, salary) VALUES (?, ?, ?, ?)",
-                        item.get_params()
+                        "INSERT IGNORE INTO contract (type_of_contract, start_date, end_date, salary, run_id) VALUES (?, ?, ?, ?, ?)",
+                        with_run_id(item.get_params(), run_id.as_deref())
                     ).await
                 },
                 TableType::Client(_) => {
                     conn.exec_drop(
-                        "INSERT IGNORE INTO client (first_name, last_name, email, phone_number) VALUES (?, ?, ?, ?)",
-                        item.get_params()
+                        "INSERT IGNORE INTO client (first_name, last_name, email, phone_number, run_id) VALUES (?, ?, ?, ?, ?)",
+                        with_run_id(item.get_params(), run_id.as_deref())
                     ).await
                 },
                 TableType::Address(_) => {
                     conn.exec_drop(
-                        "INSERT IGNORE INTO address (city, street, street_number, postal_code) VALUES (?, ?, ?, ?)",
-                        item.get_params()
+                        "INSERT IGNORE INTO address (city, street, street_number, postal_code, voivodeship, country, latitude, longitude, run_id) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                        with_run_id(item.get_params(), run_id.as_deref())
                     ).await
                 },
             }
@@ -309,7 +380,11 @@ pub async fn parallel_insert_batch(pool: &Pool, list: &Vec<TableType>) -> Result
     Ok(())
 }
 
-pub async fn insert_batch(pool: &Pool, list: &Vec<TableType>) -> Result<(), Error> {
+pub async fn insert_batch(
+    pool: &Pool,
+    list: &Vec<TableType>,
+    run_id: Option<&str>,
+) -> Result<(), Error> {
     let mut conn = pool.get_conn().await?;
     let mut tx = conn
         .start_transaction(mysql_async::TxOpts::default())
@@ -320,51 +395,51 @@ pub async fn insert_batch(pool: &Pool, list: &Vec<TableType>) -> Result<(), Erro
     for item in list {
         match item {
             TableType::Technology(_) => queries.push((
-                "INSERT IGNORE INTO technology (name, description) VALUES (?, ?)",
+                "INSERT IGNORE INTO technology (name, description, run_id) VALUES (?, ?, ?)",
                 vec![
-                    item.get_params()
+                    with_run_id(item.get_params(), run_id)
                 ]
             )),
             TableType::Task(_) => queries.push((
-                "INSERT IGNORE INTO task (name, description, start_date, end_date, status) VALUES (?, ?, ?, ?, ?)",
+                "INSERT IGNORE INTO task (name, description, start_date, end_date, status, run_id) VALUES (?, ?, ?, ?, ?, ?)",
                 vec![
-                    item.get_params()
+                    with_run_id(item.get_params(), run_id)
                 ]
             )),
             TableType::Project(_) => queries.push((
-                "INSERT IGNORE INTO project (name, description, start_date, end_date, status) VALUES (?, ?, ?, ?, ?)",
+                "INSERT IGNORE INTO project (name, description, start_date, end_date, status, run_id) VALUES (?, ?, ?, ?, ?, ?)",
                 vec![
-                    item.get_params()
+                    with_run_id(item.get_params(), run_id)
                 ]
             )),
             TableType::Payment(_) => queries.push((
-                "INSERT IGNORE INTO payment (amount, payment_due_date, method) VALUES (?, ?, ?)",
+                "INSERT IGNORE INTO payment (amount, payment_due_date, method, run_id) VALUES (?, ?, ?, ?)",
                 vec![
-                    item.get_params()
+                    with_run_id(item.get_params(), run_id)
                 ]
             )),
             TableType::Employee(_) => queries.push((
-                "INSERT IGNORE INTO employee (first_name, last_name, email, phone_number, position, contract_date) VALUES (?, ?, ?, ?, ?, ?)",
+                "INSERT IGNORE INTO employee (first_name, last_name, email, phone_number, position, contract_date, department, team, run_id) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
                 vec![
-                    item.get_params()
+                    with_run_id(item.get_params(), run_id)
                 ]
             )),
             TableType::Contract(_) => queries.push((
-                "INSERT IGNORE INTO contract (type_of_contract, start_date, end_date, salary) VALUES (?, ?, ?, ?)",
+                "INSERT IGNORE INTO contract (type_of_contract, start_date, end_date, salary, run_id) VALUES (?, ?, ?, ?, ?)",
                 vec![
-                    item.get_params()
+                    with_run_id(item.get_params(), run_id)
                 ]
             )),
             TableType::Client(_) => queries.push((
-                "INSERT IGNORE INTO client (first_name, last_name, email, phone_number) VALUES (?, ?, ?, ?)",
+                "INSERT IGNORE INTO client (first_name, last_name, email, phone_number, run_id) VALUES (?, ?, ?, ?, ?)",
                 vec![
-                    item.get_params()
+                    with_run_id(item.get_params(), run_id)
                 ]
             )),
             TableType::Address(_) => queries.push((
-                "INSERT IGNORE INTO address (city, street, street_number, postal_code) VALUES (?, ?, ?, ?)",
+                "INSERT IGNORE INTO address (city, street, street_number, postal_code, voivodeship, country, latitude, longitude, run_id) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
                 vec![
-                    item.get_params()
+                    with_run_id(item.get_params(), run_id)
                 ]
             )),
         }
@@ -377,3 +452,656 @@ pub async fn insert_batch(pool: &Pool, list: &Vec<TableType>) -> Result<(), Erro
     tx.commit().await?;
     Ok(())
 }
+
+/// Inserts employees one at a time, each followed by their contract referencing them through
+/// `employee_id`. Returns the primary keys of the employees in order.
+pub async fn insert_employee_contracts_mysql(
+    pool: &Pool,
+    pairs: &[(Employee, Contract)],
+    run_id: Option<&str>,
+) -> Result<Vec<u64>, Error> {
+    let mut conn = pool.get_conn().await?;
+    let mut tx = conn
+        .start_transaction(mysql_async::TxOpts::default())
+        .await?;
+
+    let mut keys: Vec<u64> = Vec::with_capacity(pairs.len());
+    for (employee, _) in pairs {
+        tx.exec_drop(
+            "INSERT INTO employee (first_name, last_name, email, phone_number, position, contract_date, department, team, run_id) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            with_run_id(TableType::Employee(employee.clone()).get_params(), run_id),
+        )
+        .await?;
+        keys.push(
+            tx.last_insert_id()
+                .ok_or_else(|| Error::from(std::io::Error::other("Missing employee id")))?,
+        );
+    }
+    insert_contracts(
+        &mut tx,
+        pairs.iter().map(|(_, contract)| contract).zip(&keys),
+        run_id,
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(keys)
+}
+
+/// Inserts contracts with the primary key of the employee each belongs to.
+async fn insert_contracts<'a>(
+    tx: &mut mysql_async::Transaction<'_>,
+    contracts: impl Iterator<Item = (&'a Contract, &'a u64)> + Send,
+    run_id: Option<&str>,
+) -> Result<(), Error> {
+    tx.exec_batch(
+        "INSERT INTO contract (type_of_contract, start_date, end_date, salary, employee_id, run_id) VALUES (?, ?, ?, ?, ?, ?)",
+        contracts.map(|(contract, employee_id)| {
+            with_run_id(
+                with_key(
+                    TableType::Contract(contract.clone()).get_params(),
+                    *employee_id,
+                ),
+                run_id,
+            )
+        }),
+    )
+    .await
+}
+
+/// Inserts an org chart, managers first so every `manager_id` refers to an inserted employee,
+/// followed by the contracts referencing their employee through `employee_id`. Returns the
+/// primary keys of the employees in chart order.
+pub async fn insert_org_chart_mysql(
+    pool: &Pool,
+    members: &[OrgMember],
+    run_id: Option<&str>,
+) -> Result<Vec<u64>, Error> {
+    let mut conn = pool.get_conn().await?;
+    let mut tx = conn
+        .start_transaction(mysql_async::TxOpts::default())
+        .await?;
+
+    let mut keys: Vec<u64> = Vec::with_capacity(members.len());
+    for member in members {
+        let manager_id = member.manager.map(|manager| keys[manager]);
+        let params = match TableType::Employee(member.employee.clone()).get_params() {
+            Params::Positional(mut values) => {
+                values.push(manager_id.into());
+                Params::Positional(values)
+            }
+            other => other,
+        };
+        tx.exec_drop(
+            "INSERT INTO employee (first_name, last_name, email, phone_number, position, contract_date, department, team, manager_id, run_id) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            with_run_id(params, run_id),
+        )
+        .await?;
+        keys.push(
+            tx.last_insert_id()
+                .ok_or_else(|| Error::from(std::io::Error::other("Missing employee id")))?,
+        );
+    }
+
+    insert_contracts(
+        &mut tx,
+        members.iter().map(|member| &member.contract).zip(&keys),
+        run_id,
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(keys)
+}
+
+/// Primary keys of every row of a table, in order.
+pub async fn fetch_keys_mysql(pool: &Pool, table_name: &str) -> Result<Vec<u64>, Error> {
+    let schema = table_schema(table_name)
+        .ok_or_else(|| Error::from(std::io::Error::other("Invalid table name")))?;
+    let mut conn = pool.get_conn().await?;
+    conn.query(format!(
+        "SELECT {key} FROM {table} ORDER BY {key}",
+        key = schema.primary_key,
+        table = schema.name
+    ))
+    .await
+}
+
+/// Replaces the rows of a relation's join table with the given links.
+pub async fn replace_links_mysql(
+    pool: &Pool,
+    relation: Relation,
+    links: &[Link<u64>],
+) -> Result<(), Error> {
+    let (owner, linked) = relation.tables();
+    let key = |table: TableName| {
+        table_schema(table.as_str())
+            .map(|schema| schema.primary_key)
+            .ok_or_else(|| Error::from(std::io::Error::other("Invalid table name")))
+    };
+    let mut columns = vec![key(owner)?, key(linked)?];
+    if relation.has_skill_level() {
+        columns.push("skill_level");
+    }
+
+    let mut conn = pool.get_conn().await?;
+    let mut tx = conn
+        .start_transaction(mysql_async::TxOpts::default())
+        .await?;
+    tx.query_drop(format!("DELETE FROM {}", relation.as_str()))
+        .await?;
+    tx.exec_batch(
+        format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            relation.as_str(),
+            columns.join(", "),
+            vec!["?"; columns.len()].join(", ")
+        ),
+        links.iter().map(|link| {
+            let mut values: Vec<mysql_async::Value> = vec![link.owner.into(), link.linked.into()];
+            if let Some(skill_level) = link.skill_level {
+                values.push(skill_level.into());
+            }
+            Params::Positional(values)
+        }),
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+pub async fn insert_payment_events(pool: &Pool, list: &[PaymentEvent]) -> Result<(), Error> {
+    let mut conn = pool.get_conn().await?;
+    let mut tx = conn
+        .start_transaction(mysql_async::TxOpts::default())
+        .await?;
+
+    tx.exec_batch(
+        "INSERT INTO payment_event (amount, method, occurred_at) VALUES (?, ?, ?)",
+        list.iter().map(|event| {
+            (
+                event.amount.to_string(),
+                event.method.clone(),
+                event.occurred_at.format(DATETIME_FORMAT).to_string(),
+            )
+        }),
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn insert_task_status_events(pool: &Pool, list: &[TaskStatusEvent]) -> Result<(), Error> {
+    let mut conn = pool.get_conn().await?;
+    let mut tx = conn
+        .start_transaction(mysql_async::TxOpts::default())
+        .await?;
+
+    tx.exec_batch(
+        "INSERT INTO task_status_event (task_ref, task_name, status, occurred_at) VALUES (?, ?, ?, ?)",
+        list.iter().map(|event| {
+            (
+                event.task_ref.clone(),
+                event.task_name.clone(),
+                event.status.clone(),
+                event.occurred_at.format(DATETIME_FORMAT).to_string(),
+            )
+        }),
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Picks up to `count` existing primary keys, starting from a random point in the key range.
+async fn random_keys_mysql(
+    conn: &mut Conn,
+    schema: &TableSchema,
+    count: usize,
+) -> Result<Vec<u64>, Error> {
+    let (table, key) = (schema.name, schema.primary_key);
+    let bounds: Option<(Option<u64>, Option<u64>)> = conn
+        .query_first(format!("SELECT MIN({key}), MAX({key}) FROM {table}"))
+        .await?;
+    let Some((Some(min), Some(max))) = bounds else {
+        return Ok(Vec::new());
+    };
+    let start = rand::thread_rng().gen_range(min..=max);
+
+    let mut keys: Vec<u64> = conn
+        .exec(
+            format!("SELECT {key} FROM {table} WHERE {key} >= ? ORDER BY {key} LIMIT ?"),
+            (start, count as u64),
+        )
+        .await?;
+    if keys.len() < count {
+        let wrapped: Vec<u64> = conn
+            .exec(
+                format!("SELECT {key} FROM {table} WHERE {key} < ? ORDER BY {key} LIMIT ?"),
+                (start, (count - keys.len()) as u64),
+            )
+            .await?;
+        keys.extend(wrapped);
+    }
+    Ok(keys)
+}
+
+fn update_query(schema: &TableSchema) -> String {
+    let assignments: Vec<String> = schema
+        .columns
+        .iter()
+        .map(|column| format!("{} = ?", column.name))
+        .collect();
+    format!(
+        "UPDATE {} SET {} WHERE {} = ?",
+        schema.name,
+        assignments.join(", "),
+        schema.primary_key
+    )
+}
+
+fn with_run_id(params: Params, run_id: Option<&str>) -> Params {
+    match params {
+        Params::Positional(mut values) => {
+            values.push(run_id.map(str::to_string).into());
+            Params::Positional(values)
+        }
+        other => other,
+    }
+}
+
+fn with_key(params: Params, key: u64) -> Params {
+    match params {
+        Params::Positional(mut values) => {
+            values.push(key.into());
+            Params::Positional(values)
+        }
+        other => other,
+    }
+}
+
+/// Overwrites up to `count` existing rows with freshly generated values.
+/// Returns the primary keys of the updated rows.
+pub async fn update_random_mysql(
+    pool: &Pool,
+    table_name: &str,
+    count: usize,
+) -> Result<Vec<u64>, Error> {
+    let schema = table_schema(table_name)
+        .ok_or_else(|| Error::from(std::io::Error::other("Invalid table name")))?;
+    let mut conn = pool.get_conn().await?;
+    let keys = random_keys_mysql(&mut conn, schema, count).await?;
+    let rows = TableType::generate_batch(table_name, keys.len()).unwrap_or_default();
+
+    let keyed: Vec<(u64, TableType)> = keys.iter().cloned().zip(rows).collect();
+    update_rows_mysql(pool, &keyed).await?;
+    Ok(keys)
+}
+
+/// Deletes up to `count` existing rows and returns their primary keys.
+pub async fn delete_random_mysql(
+    pool: &Pool,
+    table_name: &str,
+    count: usize,
+) -> Result<Vec<u64>, Error> {
+    let schema = table_schema(table_name)
+        .ok_or_else(|| Error::from(std::io::Error::other("Invalid table name")))?;
+    let mut conn = pool.get_conn().await?;
+    let keys = random_keys_mysql(&mut conn, schema, count).await?;
+
+    delete_rows_mysql(pool, table_name, &keys).await?;
+    Ok(keys)
+}
+
+fn mysql_value_to_json(value: mysql_async::Value, kind: ColumnKind) -> serde_json::Value {
+    match value {
+        mysql_async::Value::NULL => serde_json::Value::Null,
+        mysql_async::Value::Int(value) => value.into(),
+        mysql_async::Value::UInt(value) => value.into(),
+        mysql_async::Value::Float(value) => (value as f64).into(),
+        mysql_async::Value::Double(value) => value.into(),
+        mysql_async::Value::Date(year, month, day, ..) => {
+            format!("{:04}-{:02}-{:02}", year, month, day).into()
+        }
+        mysql_async::Value::Bytes(bytes) => {
+            let text = String::from_utf8_lossy(&bytes).to_string();
+            match kind {
+                ColumnKind::Integer => text.parse::<i64>().map(Into::into).unwrap_or(text.into()),
+                ColumnKind::Decimal => text.parse::<f64>().map(Into::into).unwrap_or(text.into()),
+                ColumnKind::Date => text.chars().take(10).collect::<String>().into(),
+                ColumnKind::Text => text.into(),
+            }
+        }
+        mysql_async::Value::Time(..) => serde_json::Value::Null,
+    }
+}
+
+fn json_to_mysql_value(value: &serde_json::Value) -> mysql_async::Value {
+    match value {
+        serde_json::Value::Null => mysql_async::Value::NULL,
+        serde_json::Value::Bool(value) => (*value as i64).into(),
+        serde_json::Value::Number(number) => match number.as_i64() {
+            Some(value) => value.into(),
+            None => number.as_f64().unwrap_or_default().into(),
+        },
+        serde_json::Value::String(value) => value.clone().into(),
+        other => other.to_string().into(),
+    }
+}
+
+/// Name and `WHERE` clause of a table in `CLEAR_ORDER`. Join tables only take a `run_id`, which
+/// matches links to or from rows of that run; events and the run log are only cleared whole.
+fn clear_target(table_name: &str, filter: &Row) -> Result<(&'static str, String, Params), Error> {
+    if let Some(schema) = table_schema(table_name) {
+        let (where_clause, params) = where_clause(schema, filter)?;
+        return Ok((schema.name, where_clause, params));
+    }
+    if let Some(relation) = Relation::from_table(table_name) {
+        if filter.is_empty() {
+            return Ok((relation.as_str(), String::new(), Params::Empty));
+        }
+        let run_id = match filter.get(RUN_ID_COLUMN) {
+            Some(run_id) if filter.len() == 1 => json_to_mysql_value(run_id),
+            _ => {
+                return Err(Error::from(std::io::Error::other(format!(
+                    "{} can only be filtered by {}",
+                    table_name, RUN_ID_COLUMN
+                ))))
+            }
+        };
+        let mut conditions = Vec::new();
+        let (owner, linked) = relation.tables();
+        for table in [owner, linked] {
+            let key = table_schema(table.as_str())
+                .map(|schema| schema.primary_key)
+                .ok_or_else(|| Error::from(std::io::Error::other("Invalid table name")))?;
+            conditions.push(format!(
+                "{} IN (SELECT {} FROM {} WHERE {} = ?)",
+                key,
+                key,
+                table.as_str(),
+                RUN_ID_COLUMN
+            ));
+        }
+        return Ok((
+            relation.as_str(),
+            format!(" WHERE {}", conditions.join(" OR ")),
+            Params::Positional(vec![run_id.clone(), run_id]),
+        ));
+    }
+    match WHOLE_TABLES.iter().find(|table| **table == table_name) {
+        Some(table) if filter.is_empty() => Ok((table, String::new(), Params::Empty)),
+        Some(table) => Err(Error::from(std::io::Error::other(format!(
+            "{} can only be cleared as a whole",
+            table
+        )))),
+        None => Err(Error::from(std::io::Error::other("Invalid table name"))),
+    }
+}
+
+/// Builds a `WHERE` clause matching rows whose columns equal the values in `filter`.
+fn where_clause(schema: &TableSchema, filter: &Row) -> Result<(String, Params), Error> {
+    let mut conditions = Vec::new();
+    let mut params = Vec::new();
+    for (column, value) in filter {
+        if !schema.accepts_filter(column) {
+            return Err(Error::from(std::io::Error::other(format!(
+                "Invalid column name: {}",
+                column
+            ))));
+        }
+        conditions.push(format!("{} = ?", column));
+        params.push(json_to_mysql_value(value));
+    }
+    if conditions.is_empty() {
+        return Ok((String::new(), Params::Empty));
+    }
+    Ok((
+        format!(" WHERE {}", conditions.join(" AND ")),
+        Params::Positional(params),
+    ))
+}
+
+/// Rows as plain objects, primary key first and `run_id` last, in primary key order.
+pub async fn fetch_rows_mysql(pool: &Pool, table_name: &str) -> Result<Vec<Row>, Error> {
+    let schema = table_schema(table_name)
+        .ok_or_else(|| Error::from(std::io::Error::other("Invalid table name")))?;
+    let columns: Vec<&str> = schema.columns.iter().map(|column| column.name).collect();
+
+    let mut conn = pool.get_conn().await?;
+    let rows: Vec<mysql_async::Row> = conn
+        .query(format!(
+            "SELECT {}, {}, {} FROM {} ORDER BY {}",
+            schema.primary_key,
+            columns.join(", "),
+            RUN_ID_COLUMN,
+            schema.name,
+            schema.primary_key
+        ))
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let mut values = row.unwrap().into_iter();
+            let mut row = Row::new();
+            if let Some(key) = values.next() {
+                row.insert(
+                    schema.primary_key.to_string(),
+                    mysql_value_to_json(key, ColumnKind::Integer),
+                );
+            }
+            for column in schema.columns {
+                if let Some(value) = values.next() {
+                    row.insert(
+                        column.name.to_string(),
+                        mysql_value_to_json(value, column.kind),
+                    );
+                }
+            }
+            if let Some(run_id) = values.next() {
+                row.insert(
+                    RUN_ID_COLUMN.to_string(),
+                    mysql_value_to_json(run_id, ColumnKind::Text),
+                );
+            }
+            row
+        })
+        .collect())
+}
+
+/// Reads the rows of a table together with their primary keys.
+/// `filter` restricts the result to rows whose columns equal the given values.
+pub async fn fetch_keyed_rows_mysql(
+    pool: &Pool,
+    table_name: &str,
+    filter: &Row,
+) -> Result<Vec<(u64, TableType)>, Error> {
+    let schema = table_schema(table_name)
+        .ok_or_else(|| Error::from(std::io::Error::other("Invalid table name")))?;
+    let columns: Vec<&str> = schema.columns.iter().map(|column| column.name).collect();
+    let (where_clause, params) = where_clause(schema, filter)?;
+
+    let mut conn = pool.get_conn().await?;
+    let rows: Vec<mysql_async::Row> = conn
+        .exec(
+            format!(
+                "SELECT {}, {} FROM {}{}",
+                schema.primary_key,
+                columns.join(", "),
+                schema.name,
+                where_clause
+            ),
+            params,
+        )
+        .await?;
+
+    let mut list = Vec::new();
+    for row in rows {
+        let mut values = row.unwrap().into_iter();
+        let Some(key) = values
+            .next()
+            .and_then(|key| mysql_async::from_value_opt::<u64>(key).ok())
+        else {
+            continue;
+        };
+        let row: Row = schema
+            .columns
+            .iter()
+            .zip(values)
+            .map(|(column, value)| {
+                (
+                    column.name.to_string(),
+                    mysql_value_to_json(value, column.kind),
+                )
+            })
+            .collect();
+        let item = TableType::from_row(table_name, row).map_err(|e| {
+            Error::from(std::io::Error::other(format!(
+                "Invalid {} row {}: {}",
+                table_name, key, e
+            )))
+        })?;
+        list.push((key, item));
+    }
+    Ok(list)
+}
+
+/// Writes every column of the given rows back under their primary keys.
+pub async fn update_rows_mysql(pool: &Pool, list: &[(u64, TableType)]) -> Result<(), Error> {
+    let Some((_, first)) = list.first() else {
+        return Ok(());
+    };
+    let schema = table_schema(first.table_name())
+        .ok_or_else(|| Error::from(std::io::Error::other("Invalid table name")))?;
+
+    let mut conn = pool.get_conn().await?;
+    conn.exec_batch(
+        update_query(schema),
+        list.iter()
+            .map(|(key, item)| with_key(item.get_params(), *key)),
+    )
+    .await?;
+    Ok(())
+}
+
+pub async fn delete_rows_mysql(pool: &Pool, table_name: &str, keys: &[u64]) -> Result<(), Error> {
+    let schema = table_schema(table_name)
+        .ok_or_else(|| Error::from(std::io::Error::other("Invalid table name")))?;
+
+    let mut conn = pool.get_conn().await?;
+    conn.exec_batch(
+        format!(
+            "DELETE FROM {} WHERE {} = ?",
+            schema.name, schema.primary_key
+        ),
+        keys.iter().map(|key| (*key,)),
+    )
+    .await?;
+    Ok(())
+}
+
+const RUN_COLUMNS: &str = "run_id, backend, params, seed, counts, \
+    DATE_FORMAT(started_at, '%Y-%m-%d %H:%i:%s'), \
+    DATE_FORMAT(finished_at, '%Y-%m-%d %H:%i:%s'), \
+    DATE_FORMAT(rolled_back_at, '%Y-%m-%d %H:%i:%s'), \
+    replay_of";
+
+type RunRow = (
+    String,
+    String,
+    String,
+    i64,
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+);
+
+fn parse_datetime(value: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value, DATETIME_FORMAT).ok()
+}
+
+fn run_from_row(row: RunRow) -> Option<GenerationRun> {
+    let (run_id, backend, params, seed, counts, started_at, finished_at, rolled_back_at, replay_of) =
+        row;
+    Some(GenerationRun {
+        run_id,
+        backend,
+        params: serde_json::from_str(&params).ok()?,
+        seed,
+        counts: serde_json::from_str(&counts).ok()?,
+        started_at: parse_datetime(&started_at)?,
+        finished_at: finished_at.as_deref().and_then(parse_datetime),
+        rolled_back_at: rolled_back_at.as_deref().and_then(parse_datetime),
+        replay_of,
+    })
+}
+
+fn format_datetime(value: Option<NaiveDateTime>) -> Option<String> {
+    value.map(|value| value.format(DATETIME_FORMAT).to_string())
+}
+
+pub async fn insert_run_mysql(pool: &Pool, run: &GenerationRun) -> Result<(), Error> {
+    let mut conn = pool.get_conn().await?;
+    conn.exec_drop(
+        "INSERT INTO generation_runs (run_id, backend, params, seed, counts, started_at, finished_at, rolled_back_at, replay_of) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        (
+            run.run_id.clone(),
+            run.backend.clone(),
+            run.params.to_string(),
+            run.seed,
+            serde_json::to_string(&run.counts).unwrap_or_default(),
+            run.started_at.format(DATETIME_FORMAT).to_string(),
+            format_datetime(run.finished_at),
+            format_datetime(run.rolled_back_at),
+            run.replay_of.clone(),
+        ),
+    )
+    .await
+}
+
+/// Stores the counts and the finish and rollback timestamps of a run.
+pub async fn update_run_mysql(pool: &Pool, run: &GenerationRun) -> Result<(), Error> {
+    let mut conn = pool.get_conn().await?;
+    conn.exec_drop(
+        "UPDATE generation_runs SET counts = ?, finished_at = ?, rolled_back_at = ? WHERE run_id = ?",
+        (
+            serde_json::to_string(&run.counts).unwrap_or_default(),
+            format_datetime(run.finished_at),
+            format_datetime(run.rolled_back_at),
+            run.run_id.clone(),
+        ),
+    )
+    .await
+}
+
+pub async fn fetch_runs_mysql(pool: &Pool) -> Result<Vec<GenerationRun>, Error> {
+    let mut conn = pool.get_conn().await?;
+    let rows: Vec<RunRow> = conn
+        .query(format!(
+            "SELECT {} FROM generation_runs ORDER BY started_at DESC",
+            RUN_COLUMNS
+        ))
+        .await?;
+    Ok(rows.into_iter().filter_map(run_from_row).collect())
+}
+
+pub async fn fetch_run_mysql(pool: &Pool, run_id: &str) -> Result<Option<GenerationRun>, Error> {
+    let mut conn = pool.get_conn().await?;
+    let row: Option<RunRow> = conn
+        .exec_first(
+            format!(
+                "SELECT {} FROM generation_runs WHERE run_id = ?",
+                RUN_COLUMNS
+            ),
+            (run_id,),
+        )
+        .await?;
+    Ok(row.and_then(run_from_row))
+}
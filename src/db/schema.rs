@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnKind {
+    Text,
+    Integer,
+    Decimal,
+    Date,
+}
+
+impl ColumnKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ColumnKind::Text => "text",
+            ColumnKind::Integer => "integer",
+            ColumnKind::Decimal => "decimal",
+            ColumnKind::Date => "date",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Copy)]
+pub struct ColumnDef {
+    pub name: &'static str,
+    pub kind: ColumnKind,
+    pub max_len: Option<usize>,
+    pub nullable: bool,
+}
+
+/// Name and type of a column as described to API clients.
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct ColumnMetadata {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: ColumnKind,
+    pub nullable: bool,
+}
+
+impl From<&ColumnDef> for ColumnMetadata {
+    fn from(column: &ColumnDef) -> Self {
+        ColumnMetadata {
+            name: column.name.to_string(),
+            kind: column.kind,
+            nullable: column.nullable,
+        }
+    }
+}
+
+/// Column layout of a table as defined in `src/utils/soft.sql`.
+/// Columns are listed in the same order as the values returned by `GetParams`.
+#[derive(Debug, Clone, Copy)]
+pub struct TableSchema {
+    pub name: &'static str,
+    pub primary_key: &'static str,
+    pub columns: &'static [ColumnDef],
+}
+
+/// Tables rows can be generated for, in the order `insert_into_many` fills them.
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum TableName {
+    Employee,
+    Client,
+    Address,
+    Contract,
+    Payment,
+    Project,
+    Task,
+    Technology,
+}
+
+impl TableName {
+    pub const ALL: [TableName; 8] = [
+        TableName::Employee,
+        TableName::Client,
+        TableName::Address,
+        TableName::Contract,
+        TableName::Payment,
+        TableName::Project,
+        TableName::Task,
+        TableName::Technology,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TableName::Employee => "employee",
+            TableName::Client => "client",
+            TableName::Address => "address",
+            TableName::Contract => "contract",
+            TableName::Payment => "payment",
+            TableName::Project => "project",
+            TableName::Task => "task",
+            TableName::Technology => "technology",
+        }
+    }
+}
+
+/// Column present in every table, holding the id of the generation run that inserted the row.
+pub const RUN_ID_COLUMN: &str = "run_id";
+
+impl TableSchema {
+    pub fn column(&self, name: &str) -> Option<&'static ColumnDef> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+
+    /// Whether rows can be filtered on the given column.
+    pub fn accepts_filter(&self, name: &str) -> bool {
+        name == RUN_ID_COLUMN || self.column(name).is_some()
+    }
+}
+
+const fn text(name: &'static str, max_len: usize, nullable: bool) -> ColumnDef {
+    ColumnDef {
+        name,
+        kind: ColumnKind::Text,
+        max_len: Some(max_len),
+        nullable,
+    }
+}
+
+const fn column(name: &'static str, kind: ColumnKind, nullable: bool) -> ColumnDef {
+    ColumnDef {
+        name,
+        kind,
+        max_len: None,
+        nullable,
+    }
+}
+
+pub const TABLES: &[TableSchema] = &[
+    TableSchema {
+        name: "address",
+        primary_key: "address_id",
+        columns: &[
+            text("city", 40, false),
+            text("street", 40, false),
+            text("street_number", 15, false),
+            text("postal_code", 15, true),
+            text("voivodeship", 40, false),
+            text("country", 56, false),
+            column("latitude", ColumnKind::Decimal, false),
+            column("longitude", ColumnKind::Decimal, false),
+        ],
+    },
+    TableSchema {
+        name: "client",
+        primary_key: "client_id",
+        columns: &[
+            text("first_name", 35, false),
+            text("last_name", 100, false),
+            text("email", 100, false),
+            text("phone_number", 15, false),
+        ],
+    },
+    TableSchema {
+        name: "contract",
+        primary_key: "contract_id",
+        columns: &[
+            text("type_of_contract", 100, false),
+            column("start_date", ColumnKind::Date, false),
+            column("end_date", ColumnKind::Date, true),
+            column("salary", ColumnKind::Integer, false),
+        ],
+    },
+    TableSchema {
+        name: "employee",
+        primary_key: "employee_id",
+        columns: &[
+            text("first_name", 35, false),
+            text("last_name", 100, false),
+            text("email", 100, false),
+            text("phone_number", 15, false),
+            text("position", 35, false),
+            column("contract_date", ColumnKind::Date, true),
+            text("department", 35, true),
+            text("team", 60, true),
+        ],
+    },
+    TableSchema {
+        name: "payment",
+        primary_key: "payment_id",
+        columns: &[
+            column("amount", ColumnKind::Decimal, false),
+            column("payment_due_date", ColumnKind::Date, false),
+            text("method", 30, false),
+        ],
+    },
+    TableSchema {
+        name: "project",
+        primary_key: "project_id",
+        columns: &[
+            text("name", 100, false),
+            text("description", 250, false),
+            column("start_date", ColumnKind::Date, false),
+            column("end_date", ColumnKind::Date, true),
+            text("status", 15, false),
+        ],
+    },
+    TableSchema {
+        name: "task",
+        primary_key: "task_id",
+        columns: &[
+            text("name", 100, false),
+            text("description", 250, false),
+            column("start_date", ColumnKind::Date, false),
+            column("end_date", ColumnKind::Date, true),
+            text("status", 15, false),
+        ],
+    },
+    TableSchema {
+        name: "technology",
+        primary_key: "technology_id",
+        columns: &[text("name", 100, false), text("description", 250, false)],
+    },
+];
+
+pub fn table_schema(table_name: &str) -> Option<&'static TableSchema> {
+    TABLES.iter().find(|table| table.name == table_name)
+}
@@ -1,11 +1,14 @@
+use rand::{rngs::SmallRng, SeedableRng};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use utoipa::ToSchema;
 
 use crate::models::{
     address::Address, client::Client, contract::Contract, employee::Employee, payment::Payment,
     project::Project, task::Task, technology::Technology,
 };
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub enum TableType {
     Technology(Technology),
     Task(Task),
@@ -17,6 +20,106 @@ pub enum TableType {
     Address(Address),
 }
 
+/// A single row as a map of column name to JSON value.
+pub type Row = Map<String, Value>;
+
+impl TableType {
+    pub fn generate_batch(table_name: &str, count: usize) -> Option<Vec<TableType>> {
+        Self::generate_batch_with_rng(table_name, count, &mut SmallRng::from_entropy())
+    }
+
+    pub fn generate_batch_with_rng(
+        table_name: &str,
+        count: usize,
+        rng: &mut SmallRng,
+    ) -> Option<Vec<TableType>> {
+        let list = match table_name {
+            "address" => Address::generate_batch(count, rng)
+                .into_iter()
+                .map(TableType::Address)
+                .collect(),
+            "client" => Client::generate_batch(count, rng)
+                .into_iter()
+                .map(TableType::Client)
+                .collect(),
+            "contract" => Contract::generate_batch(count, rng)
+                .into_iter()
+                .map(TableType::Contract)
+                .collect(),
+            "employee" => Employee::generate_batch(count, rng)
+                .into_iter()
+                .map(TableType::Employee)
+                .collect(),
+            "payment" => Payment::generate_batch(count, rng)
+                .into_iter()
+                .map(TableType::Payment)
+                .collect(),
+            "project" => Project::generate_batch(count, rng)
+                .into_iter()
+                .map(TableType::Project)
+                .collect(),
+            "task" => Task::generate_batch(count, rng)
+                .into_iter()
+                .map(TableType::Task)
+                .collect(),
+            "technology" => Technology::generate_batch(count, rng)
+                .into_iter()
+                .map(TableType::Technology)
+                .collect(),
+            _ => return None,
+        };
+        Some(list)
+    }
+
+    pub fn table_name(&self) -> &'static str {
+        match self {
+            TableType::Technology(_) => "technology",
+            TableType::Task(_) => "task",
+            TableType::Project(_) => "project",
+            TableType::Payment(_) => "payment",
+            TableType::Employee(_) => "employee",
+            TableType::Contract(_) => "contract",
+            TableType::Client(_) => "client",
+            TableType::Address(_) => "address",
+        }
+    }
+
+    pub fn to_row(&self) -> Row {
+        let value = match self {
+            TableType::Technology(tech) => serde_json::to_value(tech),
+            TableType::Task(task) => serde_json::to_value(task),
+            TableType::Project(proj) => serde_json::to_value(proj),
+            TableType::Payment(pay) => serde_json::to_value(pay),
+            TableType::Employee(emp) => serde_json::to_value(emp),
+            TableType::Contract(cont) => serde_json::to_value(cont),
+            TableType::Client(cli) => serde_json::to_value(cli),
+            TableType::Address(addr) => serde_json::to_value(addr),
+        };
+        match value {
+            Ok(Value::Object(row)) => row,
+            _ => Row::new(),
+        }
+    }
+
+    pub fn from_row(table_name: &str, row: Row) -> Result<TableType, serde_json::Error> {
+        let value = Value::Object(row);
+        match table_name {
+            "address" => serde_json::from_value(value).map(TableType::Address),
+            "client" => serde_json::from_value(value).map(TableType::Client),
+            "contract" => serde_json::from_value(value).map(TableType::Contract),
+            "employee" => serde_json::from_value(value).map(TableType::Employee),
+            "payment" => serde_json::from_value(value).map(TableType::Payment),
+            "project" => serde_json::from_value(value).map(TableType::Project),
+            "task" => serde_json::from_value(value).map(TableType::Task),
+            "technology" => serde_json::from_value(value).map(TableType::Technology),
+            _ => Err(serde::de::Error::custom(format!(
+                "Invalid table name: {}",
+                table_name
+            ))),
+        }
+    }
+}
+
 pub trait GetParams {
     fn get_params(&self) -> mysql_async::Params;
 }
@@ -32,14 +135,14 @@ impl GetParams for TableType {
                 task.name.clone().into(),
                 task.description.clone().into(),
                 task.start_date.to_string().into(),
-                task.end_date.to_string().into(),
+                task.end_date.map(|date| date.to_string()).into(),
                 task.status.clone().into(),
             ]),
             TableType::Project(proj) => mysql_async::Params::Positional(vec![
                 proj.name.clone().into(),
                 proj.description.clone().into(),
                 proj.start_date.to_string().into(),
-                proj.end_date.to_string().into(),
+                proj.end_date.map(|date| date.to_string()).into(),
                 proj.status.clone().into(),
             ]),
             TableType::Payment(pay) => mysql_async::Params::Positional(vec![
@@ -53,12 +156,14 @@ impl GetParams for TableType {
                 emp.email.clone().into(),
                 emp.phone_number.clone().into(),
                 emp.position.clone().into(),
-                emp.contract_date.to_string().into(),
+                emp.contract_date.map(|date| date.to_string()).into(),
+                emp.department.clone().into(),
+                emp.team.clone().into(),
             ]),
             TableType::Contract(cont) => mysql_async::Params::Positional(vec![
                 cont.type_of_contract.clone().into(),
                 cont.start_date.to_string().into(),
-                cont.end_date.to_string().into(),
+                cont.end_date.map(|date| date.to_string()).into(),
                 cont.salary.to_string().into(),
             ]),
             TableType::Client(cli) => mysql_async::Params::Positional(vec![
@@ -72,6 +177,10 @@ impl GetParams for TableType {
                 addr.street.clone().into(),
                 addr.street_number.clone().into(),
                 addr.postal_code.clone().into(),
+                addr.voivodeship.clone().into(),
+                addr.country.clone().into(),
+                addr.latitude.into(),
+                addr.longitude.into(),
             ]),
         }
     }
@@ -0,0 +1,171 @@
+use std::collections::BTreeSet;
+
+use chrono::{Duration, NaiveDate};
+use rand::rngs::SmallRng;
+use rand::Rng;
+use serde_json::Value;
+
+use crate::db::table_type_mysql::{Row, TableType};
+use crate::models::contract::{salary_range, Contract, CONTRACT_TYPES};
+use crate::models::employee::{salary_band, years_of_service, Employee};
+
+fn date(row: &Row, column: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(row.get(column)?.as_str()?, "%Y-%m-%d").ok()
+}
+
+/// Makes each generated contract the contract of the employee at the same position among the
+/// generated employees: it starts on their contract date, keeps its length, and pays a salary
+/// from the band of their position and years of service, switching to a contract type that
+/// allows that salary when needed. Contract columns in `kept`, e.g. ones set by overrides, keep
+/// their values. Only pairs with a row in `only` change when it is given.
+/// Returns the positions of the contracts it changed.
+pub fn apply(
+    rows: &mut [(&'static str, Row)],
+    only: Option<&BTreeSet<usize>>,
+    kept: &[&str],
+    rng: &mut SmallRng,
+) -> BTreeSet<usize> {
+    let mut changed = BTreeSet::new();
+    for (employee, contract) in pairs(rows) {
+        if only.is_some_and(|only| !only.contains(&employee) && !only.contains(&contract)) {
+            continue;
+        }
+        let employee = &rows[employee].1;
+        let (Some(position), Some(hired)) = (
+            employee.get("position").and_then(Value::as_str),
+            date(employee, "contract_date"),
+        ) else {
+            continue;
+        };
+        let band = salary_band(position, years_of_service(hired));
+
+        let row = &mut rows[contract].1;
+        let salary = match row.get("salary").and_then(Value::as_i64) {
+            Some(salary) if kept.contains(&"salary") => salary as i32,
+            _ => rng.gen_range(band),
+        };
+        let length = match (date(row, "start_date"), date(row, "end_date")) {
+            (Some(start), Some(end)) => end - start,
+            _ => Duration::days(365),
+        };
+        let start = match date(row, "start_date") {
+            Some(start) if kept.contains(&"start_date") => start,
+            _ => hired,
+        };
+        let current = row
+            .get("type_of_contract")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let type_of_contract = if salary_range(current).contains(&salary) {
+            current.to_string()
+        } else {
+            CONTRACT_TYPES
+                .iter()
+                .find(|kind| salary_range(kind).contains(&salary))
+                .unwrap_or(&CONTRACT_TYPES[1])
+                .to_string()
+        };
+
+        let values = [
+            ("type_of_contract", Value::from(type_of_contract)),
+            (
+                "start_date",
+                Value::from(start.format("%Y-%m-%d").to_string()),
+            ),
+            (
+                "end_date",
+                Value::from((start + length).format("%Y-%m-%d").to_string()),
+            ),
+            ("salary", Value::from(salary)),
+        ];
+        for (column, value) in values {
+            if !kept.contains(&column) {
+                row.insert(column.to_string(), value);
+            }
+        }
+        changed.insert(contract);
+    }
+    changed
+}
+
+/// Positions of each generated employee and of the contract at the same position among the
+/// generated contracts.
+pub fn pairs(rows: &[(&'static str, Row)]) -> Vec<(usize, usize)> {
+    let positions = |table: &str| -> Vec<usize> {
+        rows.iter()
+            .enumerate()
+            .filter(|(_, (table_name, _))| *table_name == table)
+            .map(|(index, _)| index)
+            .collect()
+    };
+    positions("employee")
+        .into_iter()
+        .zip(positions("contract"))
+        .collect()
+}
+
+/// Takes the employees and contracts at `pairs` out of the generated rows, so they can be
+/// stored with a reference from each contract to its employee.
+pub fn take_pairs(
+    list: Vec<TableType>,
+    pairs: &[(usize, usize)],
+) -> (Vec<(Employee, Contract)>, Vec<TableType>) {
+    let mut slots: Vec<Option<TableType>> = list.into_iter().map(Some).collect();
+    let mut taken = Vec::with_capacity(pairs.len());
+    for &(employee, contract) in pairs {
+        match (slots[employee].take(), slots[contract].take()) {
+            (
+                Some(TableType::Employee(taken_employee)),
+                Some(TableType::Contract(taken_contract)),
+            ) => {
+                taken.push((taken_employee, taken_contract));
+            }
+            (other_employee, other_contract) => {
+                slots[employee] = other_employee;
+                slots[contract] = other_contract;
+            }
+        }
+    }
+    (taken, slots.into_iter().flatten().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::overrides::{self, to_rows};
+    use rand::SeedableRng;
+
+    #[test]
+    fn contracts_follow_their_employee() {
+        let mut rng = SmallRng::seed_from_u64(3);
+        let mut list = TableType::generate_batch_with_rng("employee", 50, &mut rng).unwrap();
+        list.extend(TableType::generate_batch_with_rng("contract", 50, &mut rng).unwrap());
+        let mut rows = to_rows(list);
+        assert_eq!(apply(&mut rows, None, &[], &mut rng).len(), 50);
+
+        for nth in 0..50 {
+            let (employee, contract) = (&rows[nth].1, &rows[50 + nth].1);
+            assert_eq!(employee["contract_date"], contract["start_date"]);
+            let band = salary_band(
+                employee["position"].as_str().unwrap(),
+                years_of_service(date(employee, "contract_date").unwrap()),
+            );
+            let salary = contract["salary"].as_i64().unwrap() as i32;
+            assert!(band.contains(&salary));
+            assert!(salary_range(contract["type_of_contract"].as_str().unwrap()).contains(&salary));
+        }
+
+        for (_, row) in rows.iter_mut().filter(|(table, _)| *table == "contract") {
+            row.insert("salary".to_string(), Value::from(12345));
+        }
+        apply(&mut rows, None, &["salary"], &mut rng);
+        assert!(rows[50..].iter().all(|(_, row)| row["salary"] == 12345));
+
+        let pairs = pairs(&rows);
+        let (taken, rest) = take_pairs(overrides::from_rows(rows).unwrap(), &pairs);
+        assert_eq!((taken.len(), rest.len()), (50, 0));
+        assert!(taken
+            .iter()
+            .all(|(employee, contract)| employee.contract_date == Some(contract.start_date)));
+    }
+}
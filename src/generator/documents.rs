@@ -0,0 +1,174 @@
+use std::collections::BTreeSet;
+
+use rand::rngs::SmallRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::db::schema::{table_schema, TableName};
+use crate::db::table_type_mysql::{Row, TableType};
+use crate::generator::text::Bounds;
+
+/// Longest array a shape may embed.
+const MAX_ARRAY_LENGTH: usize = 1000;
+
+/// Levels of embedding below the top-level document.
+const MAX_NESTING: usize = 5;
+
+/// A document made of a row of `table` with rows of other tables embedded in it, e.g. a client
+/// with their addresses and payments, or a project with its tasks.
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DocumentShape {
+    pub table: TableName,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schema(no_recursion)]
+    pub embed: Vec<Embedded>,
+}
+
+/// Rows of a table embedded in the document above, themselves shaped like a document.
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Embedded {
+    pub table: TableName,
+    /// Field holding the embedded rows; the table name when absent.
+    pub field: Option<String>,
+    /// Length of the array of embedded rows; a single sub-document when absent.
+    pub length: Option<Bounds>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schema(no_recursion)]
+    pub embed: Vec<Embedded>,
+}
+
+impl Embedded {
+    fn field(&self) -> &str {
+        self.field.as_deref().unwrap_or_else(|| self.table.as_str())
+    }
+}
+
+impl DocumentShape {
+    /// Every problem with the shape, prefixed with the path of the embedding it concerns.
+    pub fn validate(&self, path: &str) -> Vec<String> {
+        let mut errors = Vec::new();
+        check(self.table, &self.embed, path, 0, &mut errors);
+        errors
+    }
+
+    /// Largest number of rows one document holds, itself included.
+    pub fn max_rows(&self) -> usize {
+        max_rows(&self.embed)
+    }
+
+    /// A freshly generated document of this shape.
+    pub fn generate(&self, rng: &mut SmallRng) -> Result<Row, String> {
+        document(self.table, &self.embed, rng)
+    }
+}
+
+fn check(table: TableName, embed: &[Embedded], path: &str, depth: usize, errors: &mut Vec<String>) {
+    if depth > MAX_NESTING {
+        errors.push(format!(
+            "{}: documents nest at most {} levels deep",
+            path, MAX_NESTING
+        ));
+        return;
+    }
+    let columns: BTreeSet<&str> = table_schema(table.as_str())
+        .map(|schema| schema.columns.iter().map(|column| column.name).collect())
+        .unwrap_or_default();
+    let mut fields = BTreeSet::new();
+    for (index, embedded) in embed.iter().enumerate() {
+        let path = format!("{}.embed[{}]", path, index);
+        let field = embedded.field();
+        if field.is_empty() || field.starts_with('$') || field.contains('.') {
+            errors.push(format!(
+                "{}: field \"{}\" is not a valid field name",
+                path, field
+            ));
+        } else if columns.contains(field) || field == "_id" {
+            errors.push(format!(
+                "{}: field \"{}\" is already a column of {}",
+                path,
+                field,
+                table.as_str()
+            ));
+        } else if !fields.insert(field) {
+            errors.push(format!("{}: field \"{}\" is used twice", path, field));
+        }
+        if let Some(length) = &embedded.length {
+            if length.min > length.max || length.max > MAX_ARRAY_LENGTH {
+                errors.push(format!(
+                    "{}: length needs min <= max <= {}",
+                    path, MAX_ARRAY_LENGTH
+                ));
+            }
+        }
+        check(embedded.table, &embedded.embed, &path, depth + 1, errors);
+    }
+}
+
+fn max_rows(embed: &[Embedded]) -> usize {
+    embed
+        .iter()
+        .map(|embedded| {
+            let length = embedded.length.map_or(1, |length| length.max);
+            length.saturating_mul(max_rows(&embedded.embed))
+        })
+        .fold(1, usize::saturating_add)
+}
+
+/// A generated row of `table` with the embedded rows added as sub-documents or arrays.
+fn document(table: TableName, embed: &[Embedded], rng: &mut SmallRng) -> Result<Row, String> {
+    let mut row = TableType::generate_batch_with_rng(table.as_str(), 1, rng)
+        .and_then(|mut rows| rows.pop())
+        .ok_or_else(|| format!("no rows generated for {}", table.as_str()))?
+        .to_row();
+    for embedded in embed {
+        let value = match &embedded.length {
+            None => Value::Object(document(embedded.table, &embedded.embed, rng)?),
+            Some(length) => Value::Array(
+                (0..rng.gen_range(length.min..=length.max))
+                    .map(|_| document(embedded.table, &embedded.embed, rng).map(Value::Object))
+                    .collect::<Result<_, _>>()?,
+            ),
+        };
+        row.insert(embedded.field().to_string(), value);
+    }
+    Ok(row)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn documents_embed_rows_of_other_tables() {
+        let shape: DocumentShape = serde_json::from_value(serde_json::json!({
+            "table": "client",
+            "embed": [
+                {"table": "address", "field": "home"},
+                {"table": "payment", "length": {"min": 1, "max": 3}},
+            ]
+        }))
+        .unwrap();
+        assert!(shape.validate("shape").is_empty());
+        assert_eq!(shape.max_rows(), 5);
+
+        let mut rng = SmallRng::seed_from_u64(9);
+        let document = shape.generate(&mut rng).unwrap();
+        assert!(document["email"].is_string());
+        assert!(document["home"]["city"].is_string());
+        let payments = document["payment"].as_array().unwrap();
+        assert!((1..=3).contains(&payments.len()));
+        assert!(payments.iter().all(|payment| payment["amount"].is_number()));
+
+        let clash: DocumentShape = serde_json::from_value(serde_json::json!({
+            "table": "client",
+            "embed": [{"table": "address", "field": "email"}]
+        }))
+        .unwrap();
+        assert_eq!(clash.validate("shape").len(), 1);
+    }
+}
@@ -0,0 +1,889 @@
+use chrono::{Datelike, Months, NaiveDate, TimeDelta, Utc};
+use serde_json::Value;
+
+use crate::db::schema::{ColumnKind, TableName};
+
+/// A value during evaluation of a template expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Val {
+    Null,
+    Bool(bool),
+    Number(f64),
+    Text(String),
+    Date(NaiveDate),
+    /// A calendar span such as `30d` or `1y`; months are added separately from days.
+    Span {
+        months: i64,
+        days: i64,
+    },
+}
+
+impl Val {
+    /// Reads a row value of a column with the given kind.
+    pub fn from_json(value: &Value, kind: ColumnKind) -> Val {
+        match (value, kind) {
+            (Value::Null, _) => Val::Null,
+            (Value::String(text), ColumnKind::Date) => NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .map(Val::Date)
+                .unwrap_or_else(|_| Val::Text(text.clone())),
+            (Value::String(text), _) => Val::Text(text.clone()),
+            (Value::Number(number), _) => Val::Number(number.as_f64().unwrap_or_default()),
+            (Value::Bool(value), _) => Val::Bool(*value),
+            (value, _) => Val::Text(value.to_string()),
+        }
+    }
+
+    /// Converts the result of a template into a value for a column of the given kind.
+    pub fn into_json(self, kind: ColumnKind) -> Result<Value, String> {
+        let converted = match (&self, kind) {
+            (Val::Null, _) => Some(Value::Null),
+            (Val::Number(n), ColumnKind::Integer) => Some(Value::from(n.round() as i64)),
+            (Val::Text(text), ColumnKind::Integer) => {
+                text.trim().parse::<i64>().ok().map(Value::from)
+            }
+            (Val::Number(n), ColumnKind::Decimal) => Some(Value::from(*n)),
+            (Val::Text(text), ColumnKind::Decimal) => {
+                text.trim().parse::<f64>().ok().map(Value::from)
+            }
+            (Val::Date(date), ColumnKind::Date) => Some(Value::from(format_date(*date))),
+            (Val::Text(text), ColumnKind::Date) => {
+                NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d")
+                    .ok()
+                    .map(|date| Value::from(format_date(date)))
+            }
+            (_, ColumnKind::Text) => Some(Value::from(self.render())),
+            _ => None,
+        };
+        converted.ok_or_else(|| {
+            format!(
+                "template produced {} for a {} column",
+                self.render(),
+                kind.as_str()
+            )
+        })
+    }
+
+    /// The value as it appears inside a text template.
+    pub fn render(&self) -> String {
+        match self {
+            Val::Null => String::new(),
+            Val::Bool(value) => value.to_string(),
+            Val::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => format!("{}", *n as i64),
+            Val::Number(n) => n.to_string(),
+            Val::Text(text) => text.clone(),
+            Val::Date(date) => format_date(*date),
+            Val::Span { months, days } => match (months, days) {
+                (0, days) => format!("{}d", days),
+                (months, 0) => format!("{}m", months),
+                (months, days) => format!("{}m{}d", months, days),
+            },
+        }
+    }
+
+    fn truthy(&self) -> bool {
+        match self {
+            Val::Null => false,
+            Val::Bool(value) => *value,
+            Val::Number(n) => *n != 0.0,
+            Val::Text(text) => !text.is_empty(),
+            Val::Date(_) | Val::Span { .. } => true,
+        }
+    }
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+/// Replaces Polish diacritics with their ASCII base letters.
+pub fn transliterate(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            'ą' => 'a',
+            'ć' => 'c',
+            'ę' => 'e',
+            'ł' => 'l',
+            'ń' => 'n',
+            'ó' => 'o',
+            'ś' => 's',
+            'ź' | 'ż' => 'z',
+            'Ą' => 'A',
+            'Ć' => 'C',
+            'Ę' => 'E',
+            'Ł' => 'L',
+            'Ń' => 'N',
+            'Ó' => 'O',
+            'Ś' => 'S',
+            'Ź' | 'Ż' => 'Z',
+            c => c,
+        })
+        .collect()
+}
+
+/// Lowercase ASCII words joined by dashes, e.g. `"Łódź Fabryczna"` -> `"lodz-fabryczna"`.
+pub fn slugify(text: &str) -> String {
+    transliterate(text)
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+    Implies,
+}
+
+/// Functions callable as `name(x, ...)` or piped as `x | name(...)`, with their arity.
+const FUNCTIONS: &[(&str, usize, usize)] = &[
+    ("lower", 1, 1),
+    ("upper", 1, 1),
+    ("trim", 1, 1),
+    ("ascii", 1, 1),
+    ("slug", 1, 1),
+    ("substr", 2, 3),
+    ("round", 1, 2),
+    ("year", 1, 1),
+    ("month", 1, 1),
+    ("date", 1, 1),
+    ("today", 0, 0),
+    ("not", 1, 1),
+    ("if", 3, 3),
+    ("concat", 1, usize::MAX),
+    ("coalesce", 1, usize::MAX),
+];
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Literal(Val),
+    /// A column of the same row, or of the related row in `table`.
+    Column {
+        table: Option<String>,
+        column: String,
+    },
+    Binary(Box<Expr>, BinOp, Box<Expr>),
+    Call(&'static str, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Span { months: i64, days: i64 },
+    Text(String),
+    Ident(String),
+    Symbol(&'static str),
+}
+
+/// Levels an expression may nest, counting parentheses, unary operators and chained operators.
+const MAX_DEPTH: usize = 64;
+
+const SYMBOLS: [&str; 16] = [
+    "=>", "==", "!=", "<=", ">=", "<", ">", "+", "-", "*", "/", "(", ")", ",", ".", "|",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let unit = chars.get(i).copied().filter(|c| "dwmy".contains(*c));
+            let next_is_ident = chars
+                .get(i + 1)
+                .is_some_and(|c| c.is_alphanumeric() || *c == '_');
+            match unit {
+                Some(unit) if !next_is_ident => {
+                    let invalid = || format!("invalid span {}{}", text, unit);
+                    let n: i64 = text.parse().map_err(|_| invalid())?;
+                    tokens.push(match unit {
+                        'd' => Token::Span { months: 0, days: n },
+                        'w' => Token::Span {
+                            months: 0,
+                            days: n.checked_mul(7).ok_or_else(invalid)?,
+                        },
+                        'm' => Token::Span { months: n, days: 0 },
+                        _ => Token::Span {
+                            months: n.checked_mul(12).ok_or_else(invalid)?,
+                            days: 0,
+                        },
+                    });
+                    i += 1;
+                }
+                _ => tokens.push(Token::Number(
+                    text.parse()
+                        .map_err(|_| format!("invalid number {}", text))?,
+                )),
+            }
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if c == '"' || c == '\'' {
+            let end = chars[i + 1..]
+                .iter()
+                .position(|d| *d == c)
+                .ok_or("unterminated string")?;
+            tokens.push(Token::Text(chars[i + 1..i + 1 + end].iter().collect()));
+            i += end + 2;
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let symbol = SYMBOLS
+                .iter()
+                .find(|symbol| rest.starts_with(**symbol))
+                .ok_or_else(|| format!("unexpected character {:?}", c))?;
+            tokens.push(Token::Symbol(symbol));
+            i += symbol.len();
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Enters one more level of nesting.
+    fn descend(&mut self) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(format!(
+                "expression nests more than {} levels deep",
+                MAX_DEPTH
+            ));
+        }
+        Ok(())
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), String> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(format!("expected {}", symbol))
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expr, String> {
+        self.descend()?;
+        let mut expr = self.disjunction()?;
+        if self.eat("=>") {
+            let right = self.disjunction()?;
+            expr = Expr::Binary(Box::new(expr), BinOp::Implies, Box::new(right));
+        }
+        self.depth -= 1;
+        Ok(expr)
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Ident(name)) if name == keyword) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn disjunction(&mut self) -> Result<Expr, String> {
+        let depth = self.depth;
+        let mut left = self.conjunction()?;
+        while self.keyword("or") {
+            self.descend()?;
+            left = Expr::Binary(Box::new(left), BinOp::Or, Box::new(self.conjunction()?));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn conjunction(&mut self) -> Result<Expr, String> {
+        let depth = self.depth;
+        let mut left = self.negation()?;
+        while self.keyword("and") {
+            self.descend()?;
+            left = Expr::Binary(Box::new(left), BinOp::And, Box::new(self.negation()?));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn negation(&mut self) -> Result<Expr, String> {
+        if self.keyword("not") {
+            self.descend()?;
+            let negated = self.negation()?;
+            self.depth -= 1;
+            return Ok(Expr::Call("not", vec![negated]));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.additive()?;
+        let op = match self.peek() {
+            Some(Token::Symbol("==")) => BinOp::Eq,
+            Some(Token::Symbol("!=")) => BinOp::Ne,
+            Some(Token::Symbol("<")) => BinOp::Lt,
+            Some(Token::Symbol("<=")) => BinOp::Le,
+            Some(Token::Symbol(">")) => BinOp::Gt,
+            Some(Token::Symbol(">=")) => BinOp::Ge,
+            _ => return Ok(left),
+        };
+        self.position += 1;
+        Ok(Expr::Binary(Box::new(left), op, Box::new(self.additive()?)))
+    }
+
+    fn additive(&mut self) -> Result<Expr, String> {
+        let depth = self.depth;
+        let mut left = self.multiplicative()?;
+        loop {
+            let op = if self.eat("+") {
+                BinOp::Add
+            } else if self.eat("-") {
+                BinOp::Sub
+            } else {
+                self.depth = depth;
+                return Ok(left);
+            };
+            self.descend()?;
+            left = Expr::Binary(Box::new(left), op, Box::new(self.multiplicative()?));
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, String> {
+        let depth = self.depth;
+        let mut left = self.piped()?;
+        loop {
+            let op = if self.eat("*") {
+                BinOp::Mul
+            } else if self.eat("/") {
+                BinOp::Div
+            } else {
+                self.depth = depth;
+                return Ok(left);
+            };
+            self.descend()?;
+            left = Expr::Binary(Box::new(left), op, Box::new(self.piped()?));
+        }
+    }
+
+    fn piped(&mut self) -> Result<Expr, String> {
+        let depth = self.depth;
+        let mut value = self.primary()?;
+        while self.eat("|") {
+            self.descend()?;
+            let Some(Token::Ident(name)) = self.next() else {
+                return Err("expected a function name after |".to_string());
+            };
+            let mut args = vec![value];
+            if self.eat("(") {
+                args.extend(self.arguments()?);
+            }
+            value = call(&name, args)?;
+        }
+        self.depth = depth;
+        Ok(value)
+    }
+
+    fn arguments(&mut self) -> Result<Vec<Expr>, String> {
+        let mut args = Vec::new();
+        if self.eat(")") {
+            return Ok(args);
+        }
+        loop {
+            args.push(self.expression()?);
+            if self.eat(")") {
+                return Ok(args);
+            }
+            self.expect(",")?;
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Expr::Literal(Val::Number(n))),
+            Some(Token::Span { months, days }) => Ok(Expr::Literal(Val::Span { months, days })),
+            Some(Token::Text(text)) => Ok(Expr::Literal(Val::Text(text))),
+            Some(Token::Symbol("-")) => {
+                self.descend()?;
+                let operand = self.primary()?;
+                self.depth -= 1;
+                Ok(Expr::Binary(
+                    Box::new(Expr::Literal(Val::Number(0.0))),
+                    BinOp::Sub,
+                    Box::new(operand),
+                ))
+            }
+            Some(Token::Symbol("(")) => {
+                let inner = self.expression()?;
+                self.expect(")")?;
+                Ok(inner)
+            }
+            Some(Token::Ident(name)) => {
+                if self.eat("(") {
+                    let args = self.arguments()?;
+                    return call(&name, args);
+                }
+                match name.as_str() {
+                    "true" => return Ok(Expr::Literal(Val::Bool(true))),
+                    "false" => return Ok(Expr::Literal(Val::Bool(false))),
+                    "null" => return Ok(Expr::Literal(Val::Null)),
+                    _ => {}
+                }
+                if self.eat(".") {
+                    let Some(Token::Ident(column)) = self.next() else {
+                        return Err(format!("expected a column name after {}.", name));
+                    };
+                    return Ok(Expr::Column {
+                        table: Some(name),
+                        column,
+                    });
+                }
+                Ok(Expr::Column {
+                    table: None,
+                    column: name,
+                })
+            }
+            Some(token) => Err(format!("unexpected {:?}", token)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+fn call(name: &str, args: Vec<Expr>) -> Result<Expr, String> {
+    let (name, min, max) = FUNCTIONS
+        .iter()
+        .find(|(function, ..)| *function == name)
+        .ok_or_else(|| format!("unknown function {}", name))?;
+    if args.len() < *min || args.len() > *max {
+        return Err(format!("wrong number of arguments for {}", name));
+    }
+    Ok(Expr::Call(name, args))
+}
+
+/// `None` when the span or the resulting date is out of range.
+fn add_span(date: NaiveDate, months: i64, days: i64) -> Option<NaiveDate> {
+    let shift = Months::new(u32::try_from(months.unsigned_abs()).ok()?);
+    let date = if months >= 0 {
+        date.checked_add_months(shift)?
+    } else {
+        date.checked_sub_months(shift)?
+    };
+    date.checked_add_signed(TimeDelta::try_days(days)?)
+}
+
+fn binary(left: Val, op: BinOp, right: Val) -> Result<Val, String> {
+    use Val::*;
+    let result = match (op, &left, &right) {
+        (BinOp::Eq, ..) => Some(Bool(left == right)),
+        (BinOp::Ne, ..) => Some(Bool(left != right)),
+        (BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge, ..) => {
+            let ordering = match (&left, &right) {
+                (Number(a), Number(b)) => a.partial_cmp(b),
+                (Date(a), Date(b)) => Some(a.cmp(b)),
+                (Text(a), Text(b)) => Some(a.cmp(b)),
+                _ => None,
+            };
+            ordering.map(|ordering| {
+                Bool(match op {
+                    BinOp::Lt => ordering.is_lt(),
+                    BinOp::Le => ordering.is_le(),
+                    BinOp::Gt => ordering.is_gt(),
+                    _ => ordering.is_ge(),
+                })
+            })
+        }
+        (_, Null, _) | (_, _, Null) => Some(Null),
+        (BinOp::Add, Number(a), Number(b)) => Some(Number(a + b)),
+        (BinOp::Sub, Number(a), Number(b)) => Some(Number(a - b)),
+        (BinOp::Mul, Number(a), Number(b)) => Some(Number(a * b)),
+        (BinOp::Div, Number(_), Number(b)) if *b == 0.0 => return Err("division by zero".into()),
+        (BinOp::Div, Number(a), Number(b)) => Some(Number(a / b)),
+        (BinOp::Add, Date(date), Span { months, days })
+        | (BinOp::Add, Span { months, days }, Date(date)) => {
+            add_span(*date, *months, *days).map(Date)
+        }
+        (BinOp::Sub, Date(date), Span { months, days }) => months
+            .checked_neg()
+            .zip(days.checked_neg())
+            .and_then(|(months, days)| add_span(*date, months, days))
+            .map(Date),
+        (BinOp::Sub, Date(a), Date(b)) => Some(Number((*a - *b).num_days() as f64)),
+        (BinOp::Add, Span { months, days }, Span { months: m, days: d }) => months
+            .checked_add(*m)
+            .zip(days.checked_add(*d))
+            .map(|(months, days)| Span { months, days }),
+        (BinOp::Add, Text(_), _) | (BinOp::Add, _, Text(_)) => {
+            Some(Text(left.render() + &right.render()))
+        }
+        _ => None,
+    };
+    result.ok_or_else(|| {
+        format!(
+            "cannot apply {:?} to {} and {}",
+            op,
+            left.render(),
+            right.render()
+        )
+    })
+}
+
+impl Expr {
+    fn parse(source: &str) -> Result<Expr, String> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            position: 0,
+            depth: 0,
+        };
+        let expr = parser.expression()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(format!("unexpected {:?}", token)),
+        }
+    }
+
+    /// Every column the expression reads, as `(table, column)`.
+    fn references(&self) -> Vec<(Option<&str>, &str)> {
+        match self {
+            Expr::Literal(_) => Vec::new(),
+            Expr::Column { table, column } => vec![(table.as_deref(), column.as_str())],
+            Expr::Binary(left, _, right) => {
+                let mut references = left.references();
+                references.extend(right.references());
+                references
+            }
+            Expr::Call(_, args) => args.iter().flat_map(Expr::references).collect(),
+        }
+    }
+
+    /// Evaluates the expression, reading columns through `lookup`.
+    fn eval(&self, lookup: &dyn Fn(Option<&str>, &str) -> Val) -> Result<Val, String> {
+        match self {
+            Expr::Literal(value) => Ok(value.clone()),
+            Expr::Column { table, column } => Ok(lookup(table.as_deref(), column)),
+            Expr::Binary(left, op @ (BinOp::And | BinOp::Or | BinOp::Implies), right) => {
+                let left = left.eval(lookup)?.truthy();
+                Ok(Val::Bool(match op {
+                    BinOp::And => left && right.eval(lookup)?.truthy(),
+                    BinOp::Or => left || right.eval(lookup)?.truthy(),
+                    _ => !left || right.eval(lookup)?.truthy(),
+                }))
+            }
+            Expr::Binary(left, op, right) => binary(left.eval(lookup)?, *op, right.eval(lookup)?),
+            Expr::Call("if", args) => {
+                if args[0].eval(lookup)?.truthy() {
+                    args[1].eval(lookup)
+                } else {
+                    args[2].eval(lookup)
+                }
+            }
+            Expr::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.eval(lookup))
+                    .collect::<Result<Vec<_>, _>>()?;
+                apply_function(name, args)
+            }
+        }
+    }
+}
+
+fn apply_function(name: &str, args: Vec<Val>) -> Result<Val, String> {
+    let number = |index: usize| match args.get(index) {
+        Some(Val::Number(n)) => Ok(*n),
+        Some(other) => Err(format!("{} expects a number, got {}", name, other.render())),
+        None => Ok(0.0),
+    };
+    let text = || args[0].render();
+    Ok(match name {
+        "lower" => Val::Text(text().to_lowercase()),
+        "upper" => Val::Text(text().to_uppercase()),
+        "trim" => Val::Text(text().trim().to_string()),
+        "ascii" => Val::Text(transliterate(&text())),
+        "slug" => Val::Text(slugify(&text())),
+        "substr" => {
+            let start = number(1)?.max(0.0) as usize;
+            let chars = text().chars().skip(start).collect::<Vec<_>>();
+            let len = match args.get(2) {
+                Some(_) => number(2)?.max(0.0) as usize,
+                None => chars.len(),
+            };
+            Val::Text(chars.into_iter().take(len).collect())
+        }
+        "round" => {
+            if args[0] == Val::Null {
+                return Ok(Val::Null);
+            }
+            let factor = 10f64.powi(number(1)? as i32);
+            Val::Number((number(0)? * factor).round() / factor)
+        }
+        "year" | "month" => match &args[0] {
+            Val::Date(date) if name == "year" => Val::Number(date.year() as f64),
+            Val::Date(date) => Val::Number(date.month() as f64),
+            Val::Null => Val::Null,
+            other => return Err(format!("{} expects a date, got {}", name, other.render())),
+        },
+        "date" => match &args[0] {
+            Val::Text(text) => NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d")
+                .map(Val::Date)
+                .map_err(|_| format!("{} is not a YYYY-MM-DD date", text))?,
+            other => other.clone(),
+        },
+        "today" => Val::Date(Utc::now().date_naive()),
+        "not" => Val::Bool(!args[0].truthy()),
+        "concat" => Val::Text(args.iter().map(Val::render).collect()),
+        "coalesce" => args
+            .into_iter()
+            .find(|value| *value != Val::Null)
+            .unwrap_or(Val::Null),
+        _ => return Err(format!("unknown function {}", name)),
+    })
+}
+
+/// A boolean expression over the columns of a row, e.g.
+/// `status == 'Completed' => end_date <= today()`.
+#[derive(Debug, Clone)]
+pub struct Condition(Expr);
+
+impl Condition {
+    pub fn parse(source: &str) -> Result<Condition, String> {
+        Expr::parse(source).map(Condition)
+    }
+
+    pub fn references(&self) -> Vec<(Option<&str>, &str)> {
+        self.0.references()
+    }
+
+    pub fn holds(&self, lookup: &dyn Fn(Option<&str>, &str) -> Val) -> Result<bool, String> {
+        self.0.eval(lookup).map(|value| value.truthy())
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Part {
+    Text(String),
+    Expr(Expr),
+}
+
+/// Text with `{expression}` parts, e.g. `"{first_name | ascii | lower}.{last_name | slug}@acme.test"`.
+/// `{{` and `}}` stand for literal braces.
+#[derive(Debug, Clone)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Template, String> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut rest = source;
+        while let Some(index) = rest.find(['{', '}']) {
+            text.push_str(&rest[..index]);
+            let brace = &rest[index..index + 1];
+            rest = &rest[index + 1..];
+            if rest.starts_with(brace) {
+                text.push_str(brace);
+                rest = &rest[1..];
+                continue;
+            }
+            if brace == "}" {
+                return Err("unmatched } (write }} for a literal brace)".to_string());
+            }
+            let end = rest.find('}').ok_or("unclosed {")?;
+            if !text.is_empty() {
+                parts.push(Part::Text(std::mem::take(&mut text)));
+            }
+            parts.push(Part::Expr(Expr::parse(&rest[..end])?));
+            rest = &rest[end + 1..];
+        }
+        text.push_str(rest);
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(Template { parts })
+    }
+
+    pub fn references(&self) -> Vec<(Option<&str>, &str)> {
+        self.parts
+            .iter()
+            .flat_map(|part| match part {
+                Part::Text(_) => Vec::new(),
+                Part::Expr(expr) => expr.references(),
+            })
+            .collect()
+    }
+
+    /// Renders the template; a template that is a single expression keeps its type,
+    /// so `"{start_date + 30d}"` is a date and `"{amount * 0.23}"` a number.
+    pub fn render(&self, lookup: &dyn Fn(Option<&str>, &str) -> Val) -> Result<Val, String> {
+        if let [Part::Expr(expr)] = self.parts.as_slice() {
+            return expr.eval(lookup);
+        }
+        let mut text = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(part) => text.push_str(part),
+                Part::Expr(expr) => text.push_str(&expr.eval(lookup)?.render()),
+            }
+        }
+        Ok(Val::Text(text))
+    }
+}
+
+/// Resolves the table of a reference, which must be one of the generated ones.
+pub fn referenced_table(table: &str, tables: &[TableName]) -> Option<TableName> {
+    tables.iter().copied().find(|name| name.as_str() == table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(source: &str) -> Result<Val, String> {
+        let lookup = |table: Option<&str>, column: &str| match (table, column) {
+            (None, "first_name") => Val::Text("Łukasz".to_string()),
+            (None, "last_name") => Val::Text("Żółć-Nowak".to_string()),
+            (None, "start_date") => Val::Date(NaiveDate::from_ymd_opt(2024, 1, 31).unwrap()),
+            (None, "amount") => Val::Number(120.0),
+            (Some("employee"), "position") => Val::Text("IT".to_string()),
+            _ => Val::Null,
+        };
+        Template::parse(source)?.render(&lookup)
+    }
+
+    #[test]
+    fn text_functions_and_pipes() {
+        assert_eq!(
+            render("{first_name | ascii | lower}.{last_name | slug}@acme.test"),
+            Ok(Val::Text("lukasz.zolc-nowak@acme.test".to_string()))
+        );
+        assert_eq!(
+            render("{upper(substr(first_name, 0, 1))}. {last_name}"),
+            Ok(Val::Text("Ł. Żółć-Nowak".to_string()))
+        );
+        assert_eq!(render("{{{amount}}}"), Ok(Val::Text("{120}".to_string())));
+    }
+
+    #[test]
+    fn single_expressions_keep_their_type() {
+        let date = |y, m, d| Ok(Val::Date(NaiveDate::from_ymd_opt(y, m, d).unwrap()));
+        assert_eq!(render("{start_date + 30d}"), date(2024, 3, 1));
+        assert_eq!(render("{start_date + 1m}"), date(2024, 2, 29));
+        assert_eq!(render("{start_date - 1y + 2w}"), date(2023, 2, 14));
+        assert_eq!(render("{round(amount * 0.23, 2)}"), Ok(Val::Number(27.6)));
+        assert_eq!(
+            render("{start_date + 30d}")
+                .unwrap()
+                .into_json(ColumnKind::Date),
+            Ok(Value::from("2024-03-01"))
+        );
+    }
+
+    #[test]
+    fn conditions_and_related_rows() {
+        assert_eq!(
+            render("{if(employee.position == 'IT', 'tech', 'staff')}"),
+            Ok(Val::Text("tech".to_string()))
+        );
+        assert_eq!(
+            render("{if(amount > 500, amount, 0)}"),
+            Ok(Val::Number(0.0))
+        );
+        assert_eq!(
+            render("{coalesce(project.name, 'none')}"),
+            Ok(Val::Text("none".to_string()))
+        );
+    }
+
+    #[test]
+    fn conditions_combine_with_logic() {
+        let lookup = |_: Option<&str>, column: &str| match column {
+            "status" => Val::Text("Completed".to_string()),
+            "end_date" => Val::Date(NaiveDate::from_ymd_opt(2999, 1, 1).unwrap()),
+            _ => Val::Null,
+        };
+        let holds = |source: &str| Condition::parse(source).unwrap().holds(&lookup);
+        assert_eq!(
+            holds("status == 'Completed' => end_date <= today()"),
+            Ok(false)
+        );
+        assert_eq!(
+            holds("status != 'Completed' => end_date <= today()"),
+            Ok(true)
+        );
+        assert_eq!(
+            holds("not (status == 'Open' or end_date < today())"),
+            Ok(true)
+        );
+        assert_eq!(
+            holds("status == 'Completed' and end_date > date('2024-01-01')"),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn invalid_templates_are_rejected() {
+        assert!(Template::parse("{first_name").is_err());
+        assert!(Template::parse("first_name}").is_err());
+        assert!(Template::parse("{first_name | shout}").is_err());
+        assert!(Template::parse("{substr(first_name)}").is_err());
+        assert!(render("{amount / 0}").is_err());
+        assert!(render("{start_date * 2}").is_err());
+        assert!(render("{start_date + 9000000000000000000d}").is_err());
+        assert!(render("{start_date - 5000000000m}").is_err());
+        assert!(Template::parse("{start_date + 2000000000000000000w}").is_err());
+    }
+
+    #[test]
+    fn deep_nesting_is_rejected() {
+        let nested = |open: &str, close: &str, levels| {
+            format!("{{{}1{}}}", open.repeat(levels), close.repeat(levels))
+        };
+        assert!(Template::parse(&nested("(", ")", 40)).is_ok());
+        assert!(Template::parse(&nested("(", ")", 100_000)).is_err());
+        assert!(Template::parse(&nested("-", "", 100_000)).is_err());
+        assert!(Template::parse(&nested("not ", "", 100_000)).is_err());
+        assert!(Template::parse(&format!("{{1{}}}", " + 1".repeat(100_000))).is_err());
+    }
+}
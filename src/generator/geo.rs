@@ -0,0 +1,322 @@
+use std::collections::BTreeSet;
+use std::f64::consts::PI;
+use std::sync::OnceLock;
+
+use rand::rngs::SmallRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use utoipa::ToSchema;
+
+use crate::db::table_type_mysql::Row;
+use crate::utils::utils::word_list;
+
+pub const COUNTRY: &str = "Poland";
+
+/// Kilometres per degree of latitude.
+const KM_PER_DEGREE: f64 = 111.32;
+
+/// Largest radius a cluster may have.
+const MAX_CLUSTER_RADIUS_KM: f64 = 500.0;
+
+/// Points drawn around a cluster's centre before an address settles for a point of the city
+/// nearest to it.
+const MAX_DRAWS: usize = 100;
+
+/// A city of the gazetteer word list with the area and postal codes of its addresses.
+#[derive(Debug, Clone)]
+pub struct City {
+    pub name: String,
+    pub voivodeship: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub radius_km: f64,
+    /// First two digits of the city's postal codes, inclusive.
+    pub postal_prefixes: (u8, u8),
+}
+
+impl City {
+    fn parse(line: &str) -> Option<City> {
+        let fields: Vec<&str> = line.split(';').map(str::trim).collect();
+        let [name, voivodeship, latitude, longitude, radius_km, postal] = fields[..] else {
+            return None;
+        };
+        let (from, to) = postal.split_once('-')?;
+        Some(City {
+            name: name.to_string(),
+            voivodeship: voivodeship.to_string(),
+            latitude: latitude.parse().ok()?,
+            longitude: longitude.parse().ok()?,
+            radius_km: radius_km.parse().ok()?,
+            postal_prefixes: (from.parse().ok()?, to.parse().ok()?),
+        })
+    }
+
+    /// A random point within the city's radius.
+    pub fn point(&self, rng: &mut SmallRng) -> (f64, f64) {
+        // The square root spreads points evenly over the disc instead of bunching them
+        // at the centre.
+        let distance = self.radius_km * rng.gen::<f64>().sqrt();
+        offset(self.latitude, self.longitude, distance, rng)
+    }
+
+    pub fn postal_code(&self, rng: &mut SmallRng) -> String {
+        let (from, to) = self.postal_prefixes;
+        format!(
+            "{:02}-{:03}",
+            rng.gen_range(from..=to.max(from)),
+            rng.gen_range(0..1000)
+        )
+    }
+}
+
+/// Cities of the gazetteer, parsed on first use; malformed lines are skipped.
+pub fn cities() -> &'static [City] {
+    static CITIES: OnceLock<Vec<City>> = OnceLock::new();
+    CITIES.get_or_init(|| {
+        word_list("gazetteer.txt")
+            .iter()
+            .filter_map(|line| City::parse(line))
+            .collect()
+    })
+}
+
+/// The point `distance_km` from the given one in a random direction, with the longitude
+/// wrapped to ±180.
+fn offset(latitude: f64, longitude: f64, distance_km: f64, rng: &mut SmallRng) -> (f64, f64) {
+    let bearing = rng.gen_range(0.0..2.0 * PI);
+    let latitude = (latitude + distance_km * bearing.cos() / KM_PER_DEGREE).clamp(-90.0, 90.0);
+    // Degrees of longitude shrink to nothing at the poles.
+    let parallel = latitude.to_radians().cos().max(1e-6);
+    let longitude = longitude + distance_km * bearing.sin() / (KM_PER_DEGREE * parallel);
+    let longitude = (longitude + 180.0).rem_euclid(360.0) - 180.0;
+    (round(latitude), round(longitude))
+}
+
+/// Six decimal places, about 10 cm.
+fn round(degrees: f64) -> f64 {
+    (degrees * 1e6).round() / 1e6
+}
+
+fn distance_km(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (lat_a, lat_b) = (a.0.to_radians(), b.0.to_radians());
+    let d_lat = lat_b - lat_a;
+    let d_lon = (b.1 - a.1).to_radians();
+    let h = (d_lat / 2.0).sin().powi(2) + lat_a.cos() * lat_b.cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * 6371.0 * h.sqrt().asin()
+}
+
+/// The nearest city whose radius covers the point.
+fn city_at(latitude: f64, longitude: f64) -> Option<&'static City> {
+    nearest_city(latitude, longitude).filter(|city| {
+        distance_km((latitude, longitude), (city.latitude, city.longitude)) <= city.radius_km
+    })
+}
+
+pub fn nearest_city(latitude: f64, longitude: f64) -> Option<&'static City> {
+    cities().iter().min_by(|a, b| {
+        let to = |city: &City| distance_km((latitude, longitude), (city.latitude, city.longitude));
+        to(a).total_cmp(&to(b))
+    })
+}
+
+/// A centre generated addresses gather around.
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Cluster {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub radius_km: f64,
+    /// Share of the addresses relative to the other clusters.
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+/// Every problem with the clusters of a request.
+pub fn validate(clusters: &[Cluster]) -> Vec<String> {
+    let mut errors = Vec::new();
+    for (index, cluster) in clusters.iter().enumerate() {
+        if !(-90.0..=90.0).contains(&cluster.latitude) {
+            errors.push(format!("clusters[{}]: latitude must be within ±90", index));
+        }
+        if !(-180.0..=180.0).contains(&cluster.longitude) {
+            errors.push(format!(
+                "clusters[{}]: longitude must be within ±180",
+                index
+            ));
+        }
+        if !(cluster.radius_km > 0.0 && cluster.radius_km <= MAX_CLUSTER_RADIUS_KM) {
+            errors.push(format!(
+                "clusters[{}]: radius_km must be positive and at most {}",
+                index, MAX_CLUSTER_RADIUS_KM
+            ));
+        }
+        let covers_a_city = cities().iter().any(|city| {
+            distance_km(
+                (cluster.latitude, cluster.longitude),
+                (city.latitude, city.longitude),
+            ) < cluster.radius_km + city.radius_km
+        });
+        if !covers_a_city {
+            errors.push(format!(
+                "clusters[{}]: the cluster overlaps no city of the gazetteer",
+                index
+            ));
+        }
+        if !(cluster.weight.is_finite() && cluster.weight >= 0.0) {
+            errors.push(format!("clusters[{}]: weight must not be negative", index));
+        }
+    }
+    if !clusters.is_empty() && clusters.iter().all(|cluster| cluster.weight == 0.0) {
+        errors.push("clusters: at least one weight must be positive".to_string());
+    }
+    errors
+}
+
+/// Moves generated addresses, or those at `only` when given, around the clusters: points are
+/// densest at a cluster's centre and stay within its radius and the area of a gazetteer city,
+/// whose name, voivodeship and postal codes they take. An address whose cluster barely
+/// overlaps any city falls back to a point of the city nearest to the centre.
+/// Returns the positions of the addresses it moved.
+pub fn cluster(
+    rows: &mut [(&'static str, Row)],
+    clusters: &[Cluster],
+    only: Option<&BTreeSet<usize>>,
+    rng: &mut SmallRng,
+) -> BTreeSet<usize> {
+    let mut moved = BTreeSet::new();
+    let total: f64 = clusters.iter().map(|cluster| cluster.weight).sum();
+    if clusters.is_empty() || total <= 0.0 {
+        return moved;
+    }
+    for (index, (table_name, row)) in rows.iter_mut().enumerate() {
+        if *table_name != "address" || only.is_some_and(|only| !only.contains(&index)) {
+            continue;
+        }
+        let mut target = rng.gen::<f64>() * total;
+        let centre = clusters
+            .iter()
+            .find(|cluster| {
+                target -= cluster.weight;
+                target < 0.0
+            })
+            .unwrap_or(&clusters[clusters.len() - 1]);
+        let drawn = (0..MAX_DRAWS).find_map(|_| {
+            let distance = centre.radius_km * rng.gen::<f64>();
+            let (latitude, longitude) = offset(centre.latitude, centre.longitude, distance, rng);
+            city_at(latitude, longitude).map(|city| ((latitude, longitude), city))
+        });
+        let Some(((latitude, longitude), city)) = drawn.or_else(|| {
+            let city = nearest_city(centre.latitude, centre.longitude)?;
+            Some((city.point(rng), city))
+        }) else {
+            continue;
+        };
+        row.insert("latitude".to_string(), Value::from(latitude));
+        row.insert("longitude".to_string(), Value::from(longitude));
+        row.insert("city".to_string(), Value::from(city.name.clone()));
+        row.insert(
+            "voivodeship".to_string(),
+            Value::from(city.voivodeship.clone()),
+        );
+        row.insert(
+            "postal_code".to_string(),
+            Value::from(city.postal_code(rng)),
+        );
+        moved.insert(index);
+    }
+    moved
+}
+
+/// A GeoJSON FeatureCollection of address rows, with their other columns as properties.
+pub fn feature_collection(rows: Vec<Row>) -> Value {
+    let features: Vec<Value> = rows
+        .into_iter()
+        .filter_map(|mut row| {
+            let latitude = row.remove("latitude")?.as_f64()?;
+            let longitude = row.remove("longitude")?.as_f64()?;
+            Some(json!({
+                "type": "Feature",
+                "geometry": {"type": "Point", "coordinates": [longitude, latitude]},
+                "properties": row,
+            }))
+        })
+        .collect();
+    json!({"type": "FeatureCollection", "features": features})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::table_type_mysql::TableType;
+    use crate::generator::overrides::to_rows;
+    use rand::SeedableRng;
+
+    #[test]
+    fn points_stay_in_their_city() {
+        let mut rng = SmallRng::seed_from_u64(4);
+        assert!(cities().len() > 30);
+        for city in cities() {
+            for _ in 0..20 {
+                let point = city.point(&mut rng);
+                let distance = distance_km(point, (city.latitude, city.longitude));
+                assert!(
+                    distance <= city.radius_km + 0.01,
+                    "{} is {} km out",
+                    city.name,
+                    distance
+                );
+                let code = city.postal_code(&mut rng);
+                let prefix: u8 = code[..2].parse().unwrap();
+                assert!((city.postal_prefixes.0..=city.postal_prefixes.1).contains(&prefix));
+            }
+        }
+        let krakow = nearest_city(50.06, 19.94).unwrap();
+        assert_eq!(
+            (krakow.name.as_str(), krakow.voivodeship.as_str()),
+            ("Kraków", "małopolskie")
+        );
+    }
+
+    #[test]
+    fn clustered_points_stay_in_gazetteer_cities() {
+        let mut rng = SmallRng::seed_from_u64(6);
+        for (latitude, longitude) in [(90.0, 0.0), (-89.9999, 10.0), (0.0, 179.99)] {
+            for _ in 0..50 {
+                let (lat, lon) = offset(latitude, longitude, 100.0, &mut rng);
+                assert!((-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon));
+            }
+        }
+
+        let far_away = Cluster {
+            latitude: 0.0,
+            longitude: 0.0,
+            radius_km: 100.0,
+            weight: 1.0,
+        };
+        assert_eq!(validate(&[far_away]).len(), 1);
+
+        let warsaw = [Cluster {
+            latitude: 52.23,
+            longitude: 21.01,
+            radius_km: 150.0,
+            weight: 1.0,
+        }];
+        assert!(validate(&warsaw).is_empty());
+        let addresses = TableType::generate_batch_with_rng("address", 100, &mut rng).unwrap();
+        let mut rows = to_rows(addresses);
+        assert_eq!(cluster(&mut rows, &warsaw, None, &mut rng).len(), 100);
+        for (_, row) in &rows {
+            let point = (
+                row["latitude"].as_f64().unwrap(),
+                row["longitude"].as_f64().unwrap(),
+            );
+            let city = city_at(point.0, point.1).unwrap();
+            assert_eq!(row["city"], city.name.as_str());
+        }
+    }
+}
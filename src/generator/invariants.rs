@@ -0,0 +1,213 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use rand::rngs::SmallRng;
+
+use crate::db::schema::{table_schema, ColumnKind, TableName, RUN_ID_COLUMN};
+use crate::db::table_type_mysql::Row;
+use crate::generator::expression::{Condition, Val};
+
+/// Rules the generators keep for every row of a table. Each reads the columns of one row.
+const BUILTIN: &[(TableName, &str)] = &[
+    (TableName::Contract, "coalesce(end_date, start_date) >= start_date"),
+    (TableName::Contract, "type_of_contract == 'B2B' => salary >= 8000"),
+    (TableName::Contract, "type_of_contract == 'Mandate Contract' => salary <= 9000"),
+    (
+        TableName::Contract,
+        "type_of_contract == 'UoP' or type_of_contract == 'Contract of Employment' => salary >= 4666",
+    ),
+    (TableName::Employee, "contract_date <= today()"),
+    (TableName::Payment, "amount > 0"),
+    (TableName::Project, "coalesce(end_date, start_date) >= start_date"),
+    (
+        TableName::Project,
+        "status == 'Completed' => coalesce(end_date, today()) <= today()",
+    ),
+    (TableName::Task, "coalesce(end_date, start_date) >= start_date"),
+    (
+        TableName::Task,
+        "status == 'Completed' => coalesce(end_date, today()) <= today()",
+    ),
+];
+
+/// How many times violating rows are regenerated before a request is rejected.
+const MAX_ROUNDS: usize = 20;
+
+/// A condition every row of a table must satisfy, e.g. `end_date >= start_date`.
+#[derive(Debug, Clone)]
+pub struct Invariant {
+    pub table: TableName,
+    pub rule: String,
+    condition: Condition,
+}
+
+impl Invariant {
+    pub fn parse(table: TableName, rule: &str) -> Result<Invariant, String> {
+        let condition = Condition::parse(rule)?;
+        let schema = table_schema(table.as_str()).ok_or("unknown table")?;
+        for (related, column) in condition.references() {
+            if let Some(related) = related {
+                return Err(format!(
+                    "invariants read the row's own columns, not {}.{}",
+                    related, column
+                ));
+            }
+            if column == schema.primary_key || column == "_id" || column == RUN_ID_COLUMN {
+                // Keys and run ids are assigned on insert, after the invariants are checked.
+                return Err(format!(
+                    "invariants cannot read {}, which is only set on insert",
+                    column
+                ));
+            }
+            if schema.column(column).is_none() {
+                return Err(format!("table {} has no column {}", table.as_str(), column));
+            }
+        }
+        Ok(Invariant {
+            table,
+            rule: rule.to_string(),
+            condition,
+        })
+    }
+
+    pub fn holds(&self, row: &Row) -> Result<bool, String> {
+        let schema = table_schema(self.table.as_str());
+        let lookup = |_: Option<&str>, name: &str| {
+            let kind = schema
+                .and_then(|schema| schema.column(name))
+                .map_or(ColumnKind::Text, |column| column.kind);
+            row.get(name)
+                .map_or(Val::Null, |value| Val::from_json(value, kind))
+        };
+        self.condition
+            .holds(&lookup)
+            .map_err(|e| format!("{}: {}", self.rule, e))
+    }
+}
+
+/// The invariants of `tables`: the built-in ones when `builtin` is set, then the custom ones.
+pub fn for_tables(
+    tables: &[TableName],
+    custom: &BTreeMap<TableName, Vec<String>>,
+    builtin: bool,
+) -> Result<Vec<Invariant>, String> {
+    let mut invariants = Vec::new();
+    for table in tables {
+        if builtin {
+            for (_, rule) in BUILTIN.iter().filter(|(owner, _)| owner == table) {
+                invariants.push(Invariant::parse(*table, rule)?);
+            }
+        }
+        for rule in custom.get(table).into_iter().flatten() {
+            invariants.push(Invariant::parse(*table, rule)?);
+        }
+    }
+    Ok(invariants)
+}
+
+/// Every problem with the custom invariants of a request generating `tables`.
+pub fn validate(custom: &BTreeMap<TableName, Vec<String>>, tables: &[TableName]) -> Vec<String> {
+    let mut errors = Vec::new();
+    for (table, rules) in custom {
+        if !tables.contains(table) {
+            errors.push(format!(
+                "invariants.{}: table is not generated by this request",
+                table.as_str()
+            ));
+            continue;
+        }
+        for (index, rule) in rules.iter().enumerate() {
+            if let Err(e) = Invariant::parse(*table, rule) {
+                errors.push(format!("invariants.{}[{}]: {}", table.as_str(), index, e));
+            }
+        }
+    }
+    errors
+}
+
+/// The first invariant of its table a row breaks.
+pub fn violation<'a>(
+    invariants: &'a [Invariant],
+    table_name: &str,
+    row: &Row,
+) -> Result<Option<&'a Invariant>, String> {
+    for invariant in invariants
+        .iter()
+        .filter(|invariant| invariant.table.as_str() == table_name)
+    {
+        if !invariant.holds(row)? {
+            return Ok(Some(invariant));
+        }
+    }
+    Ok(None)
+}
+
+/// Regenerates rows breaking an invariant with `resample` until none do, giving up after
+/// `MAX_ROUNDS` rounds.
+pub fn enforce(
+    invariants: &[Invariant],
+    rows: &mut [(&'static str, Row)],
+    rng: &mut SmallRng,
+    mut resample: impl FnMut(
+        &mut [(&'static str, Row)],
+        &BTreeSet<usize>,
+        &mut SmallRng,
+    ) -> Result<(), String>,
+) -> Result<(), String> {
+    if invariants.is_empty() {
+        return Ok(());
+    }
+    for round in 0..=MAX_ROUNDS {
+        let mut violating = BTreeSet::new();
+        let mut first = None;
+        for (index, (table_name, row)) in rows.iter().enumerate() {
+            if let Some(invariant) = violation(invariants, table_name, row)? {
+                violating.insert(index);
+                first.get_or_insert(invariant);
+            }
+        }
+        let Some(invariant) = first else {
+            return Ok(());
+        };
+        if round == MAX_ROUNDS {
+            return Err(format!(
+                "{} rows still break invariants after {} resamples, e.g. {}: {}",
+                violating.len(),
+                MAX_ROUNDS,
+                invariant.table.as_str(),
+                invariant.rule
+            ));
+        }
+        resample(rows, &violating, rng)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::table_type_mysql::TableType;
+    use crate::generator::overrides::to_rows;
+    use rand::SeedableRng;
+
+    #[test]
+    fn generated_rows_satisfy_builtin_invariants() {
+        let invariants = for_tables(&TableName::ALL, &BTreeMap::new(), true).unwrap();
+        let mut rng = SmallRng::seed_from_u64(7);
+        for table in TableName::ALL {
+            let batch = TableType::generate_batch_with_rng(table.as_str(), 200, &mut rng).unwrap();
+            for (table_name, row) in to_rows(batch) {
+                let broken = violation(&invariants, table_name, &row).unwrap();
+                assert!(broken.is_none(), "{:?} breaks {:?}", row, broken);
+            }
+        }
+    }
+
+    #[test]
+    fn invariants_read_only_their_own_row() {
+        assert!(Invariant::parse(TableName::Contract, "salary > 0").is_ok());
+        assert!(Invariant::parse(TableName::Contract, "contract_id > 0").is_err());
+        assert!(Invariant::parse(TableName::Contract, "run_id != null").is_err());
+        assert!(Invariant::parse(TableName::Contract, "wage > 0").is_err());
+        assert!(Invariant::parse(TableName::Task, "project.end_date >= end_date").is_err());
+    }
+}
@@ -0,0 +1,14 @@
+pub mod coherence;
+pub mod documents;
+pub mod expression;
+pub mod geo;
+pub mod invariants;
+pub mod mutation;
+pub mod org;
+pub mod overrides;
+pub mod pattern;
+pub mod profile;
+pub mod relations;
+pub mod stream;
+pub mod text;
+pub mod timeseries;
@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+
+use chrono::{Duration, Utc};
+use rand::rngs::SmallRng;
+use rand::{seq::SliceRandom, Rng};
+
+use crate::db::schema::TableName;
+use crate::db::table_type_mysql::TableType;
+use crate::generator::invariants;
+use crate::models::client::EMAIL_DOMAINS;
+use crate::models::contract::salary_range;
+use crate::models::employee::DEPARTMENTS;
+use crate::models::person::email_address;
+use crate::models::{address::Address, technology::Technology};
+
+fn next_status(status: &str) -> Option<&'static str> {
+    match status {
+        "Not Started" => Some("In Progress"),
+        "In Progress" => Some("Completed"),
+        _ => None,
+    }
+}
+
+/// Rows of one table with their primary keys or `_id`s.
+pub type KeyedRows<K> = Vec<(K, TableType)>;
+
+/// Splits shuffled rows into the ones to update, already mutated, and the ones to delete.
+/// Updates only go to rows that can change and still keep the built-in invariants; the last value is how many updates fell short
+/// of `update_percent` for lack of such rows.
+pub fn pick_mutations<K>(
+    mut list: KeyedRows<K>,
+    update_percent: f64,
+    delete_percent: f64,
+    rng: &mut SmallRng,
+) -> (KeyedRows<K>, KeyedRows<K>, usize) {
+    list.shuffle(rng);
+    let total = list.len() as f64;
+    let updates = ((total * update_percent / 100.0).round() as usize).min(list.len());
+    let deletes = ((total * delete_percent / 100.0).round() as usize).min(list.len() - updates);
+
+    let invariants = invariants::for_tables(&TableName::ALL, &BTreeMap::new(), true)
+        .expect("built-in invariants parse");
+    let mut to_update = Vec::with_capacity(updates);
+    let mut rest = Vec::new();
+    for (key, item) in list {
+        if to_update.len() < updates {
+            let mutated = mutate(&item, rng).filter(|mutated| {
+                matches!(
+                    invariants::violation(&invariants, mutated.table_name(), &mutated.to_row()),
+                    Ok(None)
+                )
+            });
+            if let Some(mutated) = mutated {
+                to_update.push((key, mutated));
+                continue;
+            }
+        }
+        rest.push((key, item));
+    }
+    let skipped = updates - to_update.len();
+    rest.truncate(deletes);
+    (to_update, rest, skipped)
+}
+
+/// Applies a plausible real-world change to a row: status progression for tasks and projects,
+/// a salary raise for contracts, a new email for clients and so on.
+/// Returns `None` when the row has nowhere to go, e.g. an already completed task.
+pub fn mutate(item: &TableType, rng: &mut SmallRng) -> Option<TableType> {
+    let today = Utc::now().date_naive();
+
+    let mutated = match item {
+        TableType::Task(task) => {
+            let mut task = task.clone();
+            task.status = next_status(&task.status)?.to_string();
+            if task.start_date > today {
+                task.start_date = today;
+            }
+            if task.status == "Completed" {
+                task.end_date = Some(today);
+            }
+            task.end_date = task.end_date.map(|end_date| end_date.max(task.start_date));
+            TableType::Task(task)
+        }
+        TableType::Project(project) => {
+            let mut project = project.clone();
+            project.status = next_status(&project.status)?.to_string();
+            if project.start_date > today {
+                project.start_date = today;
+            }
+            if project.status == "Completed" {
+                project.end_date = Some(today);
+            }
+            project.end_date = project
+                .end_date
+                .map(|end_date| end_date.max(project.start_date));
+            TableType::Project(project)
+        }
+        TableType::Contract(contract) => {
+            let mut contract = contract.clone();
+            let cap = *salary_range(&contract.type_of_contract).end();
+            if contract.salary >= cap {
+                return None;
+            }
+            let raise = rng.gen_range(0.03..=0.15);
+            contract.salary =
+                (((contract.salary as f64 * (1.0 + raise) / 10.0).round() * 10.0) as i32).min(cap);
+            TableType::Contract(contract)
+        }
+        TableType::Client(client) => {
+            let mut client = client.clone();
+            client.email = email_address(
+                &client.first_name,
+                &format!("{}{}", client.last_name, rng.gen_range(1..100)),
+                EMAIL_DOMAINS.choose(rng).unwrap(),
+            );
+            TableType::Client(client)
+        }
+        TableType::Employee(employee) => {
+            let mut employee = employee.clone();
+            let other_positions: Vec<&&str> = DEPARTMENTS
+                .iter()
+                .filter(|position| **position != employee.position)
+                .collect();
+            employee.position = other_positions.choose(rng)?.to_string();
+            TableType::Employee(employee)
+        }
+        TableType::Payment(payment) => {
+            let mut payment = payment.clone();
+            payment.payment_due_date += Duration::days(rng.gen_range(7..=30));
+            TableType::Payment(payment)
+        }
+        TableType::Address(_) => TableType::Address(Address::generate_batch(1, rng).pop()?),
+        TableType::Technology(technology) => {
+            let mut technology = technology.clone();
+            technology.description = Technology::generate_batch(1, rng).pop()?.description;
+            TableType::Technology(technology)
+        }
+    };
+
+    Some(mutated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::contract::Contract;
+    use crate::models::task::Task;
+    use rand::SeedableRng;
+
+    fn task(status: &str, rng: &mut SmallRng) -> TableType {
+        let mut task = Task::generate_batch(1, rng).remove(0);
+        task.status = status.to_string();
+        TableType::Task(task)
+    }
+
+    #[test]
+    fn updates_go_to_rows_that_can_change() {
+        let mut rng = SmallRng::seed_from_u64(6);
+        let rows: KeyedRows<u64> = (0..10)
+            .map(|key| {
+                let status = if key < 3 { "Not Started" } else { "Completed" };
+                (key, task(status, &mut rng))
+            })
+            .collect();
+
+        let (updated, deleted, skipped) = pick_mutations(rows, 50.0, 20.0, &mut rng);
+        assert_eq!((updated.len(), deleted.len(), skipped), (3, 2, 2));
+        for (key, item) in &updated {
+            assert!(*key < 3);
+            let TableType::Task(task) = item else {
+                panic!("not a task");
+            };
+            assert_eq!(task.status, "In Progress");
+            assert!(task.start_date <= Utc::now().date_naive());
+        }
+        assert!(deleted
+            .iter()
+            .all(|(key, _)| updated.iter().all(|(other, _)| other != key)));
+
+        let completed = task("In Progress", &mut rng);
+        let Some(TableType::Task(completed)) = mutate(&completed, &mut rng) else {
+            panic!("task did not progress");
+        };
+        assert_eq!(completed.status, "Completed");
+        assert!(completed.end_date <= Some(Utc::now().date_naive()));
+        assert!(mutate(&TableType::Task(completed), &mut rng).is_none());
+    }
+
+    #[test]
+    fn raises_stay_within_the_salary_range() {
+        let mut rng = SmallRng::seed_from_u64(9);
+        let mut contract = Contract::generate_batch(1, &mut rng).remove(0);
+        contract.type_of_contract = "Mandate Contract".to_string();
+        contract.salary = 8900;
+        for _ in 0..20 {
+            let Some(TableType::Contract(raised)) =
+                mutate(&TableType::Contract(contract.clone()), &mut rng)
+            else {
+                panic!("contract was not raised");
+            };
+            assert!(raised.salary > 8900 && raised.salary <= 9000);
+        }
+
+        contract.salary = 9000;
+        assert!(mutate(&TableType::Contract(contract.clone()), &mut rng).is_none());
+        let rows: KeyedRows<u64> = vec![(1, TableType::Contract(contract))];
+        let (updated, _, skipped) = pick_mutations(rows, 100.0, 0.0, &mut rng);
+        assert_eq!((updated.len(), skipped), (0, 1));
+    }
+}
@@ -0,0 +1,262 @@
+use chrono::{Duration, Utc};
+use rand::rngs::SmallRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::generator::text::{Bounds, Grammar, TextGenerator};
+use crate::models::contract::{salary_range, Contract, CONTRACT_TYPES};
+use crate::models::employee::{salary_band, years_of_service, Employee, DEPARTMENTS};
+
+/// Department of the chief executive, above every other department.
+pub const MANAGEMENT: &str = "Management";
+
+/// Levels an org chart may have, from the chief executive down.
+const MAX_DEPTH: usize = 8;
+
+/// Largest number of direct reports a manager may have.
+const MAX_SPAN: usize = 50;
+
+/// Salary increase of every level above individual contributors, in percent.
+const LEVEL_PREMIUM: i32 = 25;
+
+/// Highest salary any contract type pays.
+const MAX_SALARY: i32 = 25000;
+
+/// How an org chart is stored in MongoDB.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OrgLayout {
+    /// Employee documents referencing their manager's `_id`.
+    #[default]
+    Referenced,
+    /// One document with every employee nested under their manager.
+    Nested,
+}
+
+/// Shape of a generated organisation.
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct OrgShape {
+    /// Levels from the chief executive down to individual contributors, at least 3.
+    #[serde(default = "default_depth")]
+    pub depth: usize,
+    /// Direct reports of every manager below the department heads.
+    #[serde(default = "default_span")]
+    pub span_of_control: Bounds,
+    /// Departments reporting to the chief executive; all of them when empty.
+    #[serde(default)]
+    pub departments: Vec<String>,
+}
+
+fn default_depth() -> usize {
+    4
+}
+
+fn default_span() -> Bounds {
+    Bounds { min: 3, max: 6 }
+}
+
+impl Default for OrgShape {
+    fn default() -> Self {
+        OrgShape {
+            depth: default_depth(),
+            span_of_control: default_span(),
+            departments: Vec::new(),
+        }
+    }
+}
+
+impl OrgShape {
+    /// Every problem with the shape.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if !(3..=MAX_DEPTH).contains(&self.depth) {
+            errors.push(format!("depth must be between 3 and {}", MAX_DEPTH));
+        }
+        let span = &self.span_of_control;
+        if span.min == 0 || span.min > span.max || span.max > MAX_SPAN {
+            errors.push(format!(
+                "span_of_control needs 1 <= min <= max <= {}",
+                MAX_SPAN
+            ));
+        }
+        for department in &self.departments {
+            if !DEPARTMENTS.contains(&department.as_str()) {
+                errors.push(format!(
+                    "departments: unknown department {}, expected one of {}",
+                    department,
+                    DEPARTMENTS.join(", ")
+                ));
+            }
+        }
+        errors
+    }
+
+    fn departments(&self) -> Vec<&str> {
+        if self.departments.is_empty() {
+            DEPARTMENTS.to_vec()
+        } else {
+            self.departments.iter().map(String::as_str).collect()
+        }
+    }
+}
+
+/// An employee of an org chart with their contract and their manager's position in the chart.
+#[derive(Debug, Clone)]
+pub struct OrgMember {
+    pub employee: Employee,
+    pub contract: Contract,
+    /// 0 for the chief executive, 1 for department heads and so on.
+    pub level: usize,
+    pub manager: Option<usize>,
+}
+
+/// Job title of a level: department heads report to the chief executive, team leads lead the
+/// individual contributors of the last level and managers fill the levels between.
+fn title(level: usize, depth: usize, department: &str) -> String {
+    match level {
+        0 => "Chief Executive Officer".to_string(),
+        1 => format!("Head of {}", department),
+        level if level == depth - 1 => format!("{} Specialist", department),
+        level if level == depth - 2 => "Team Lead".to_string(),
+        _ => format!("{} Manager", department),
+    }
+}
+
+/// Department salary band raised by `LEVEL_PREMIUM` for every level above individual
+/// contributors.
+pub fn level_salary_band(
+    department: &str,
+    level: usize,
+    depth: usize,
+    years: i32,
+) -> std::ops::RangeInclusive<i32> {
+    let band = salary_band(department, years);
+    let percent = 100 + LEVEL_PREMIUM * (depth - 1 - level) as i32;
+    let scale = |salary: i32| (salary * percent / 100).min(MAX_SALARY);
+    scale(*band.start())..=scale(*band.end())
+}
+
+/// Builds an organisation of the given shape, managers before their reports, with at most
+/// `limit` employees. The chief executive heads one department head per department; every
+/// other manager gets a span of control's worth of reports, and each manager of the last
+/// level before individual contributors leads a named team.
+pub fn generate(
+    shape: &OrgShape,
+    limit: usize,
+    rng: &mut SmallRng,
+) -> Result<Vec<OrgMember>, String> {
+    let depth = shape.depth;
+    let mut nodes: Vec<(usize, Option<usize>, &str)> = vec![(0, None, MANAGEMENT)];
+    let mut next = 0;
+    while next < nodes.len() {
+        let (level, _, department) = nodes[next];
+        if level + 1 < depth {
+            let reports = if level == 0 {
+                shape.departments()
+            } else {
+                vec![
+                    department;
+                    rng.gen_range(shape.span_of_control.min..=shape.span_of_control.max)
+                ]
+            };
+            for department in reports {
+                if nodes.len() == limit {
+                    return Err(format!("the org chart would exceed {} employees", limit));
+                }
+                nodes.push((level + 1, Some(next), department));
+            }
+        }
+        next += 1;
+    }
+
+    let team_names = TextGenerator::Grammar(Grammar::Codename);
+    let mut teams: Vec<Option<String>> = vec![None; nodes.len()];
+    for (index, (level, manager, _)) in nodes.iter().enumerate() {
+        if *level == depth - 2 {
+            teams[index] = Some(team_names.generate(rng, Some(60)));
+        } else if *level == depth - 1 {
+            teams[index] = manager.and_then(|manager| teams[manager].clone());
+        }
+    }
+
+    let employees = Employee::generate_batch(nodes.len(), rng);
+    Ok(nodes
+        .into_iter()
+        .zip(employees)
+        .zip(teams)
+        .map(|(((level, manager, department), mut employee), team)| {
+            employee.position = title(level, depth, department);
+            employee.department = Some(department.to_string());
+            employee.team = team;
+
+            let start_date = employee
+                .contract_date
+                .unwrap_or_else(|| Utc::now().date_naive());
+            let years = years_of_service(start_date);
+            let salary = rng.gen_range(level_salary_band(department, level, depth, years));
+            let type_of_contract = CONTRACT_TYPES
+                .iter()
+                .find(|kind| salary_range(kind).contains(&salary))
+                .unwrap_or(&CONTRACT_TYPES[1])
+                .to_string();
+            let contract = Contract {
+                type_of_contract,
+                start_date,
+                end_date: Some(start_date + Duration::days(rng.gen_range(365..=1825))),
+                salary,
+            };
+            OrgMember {
+                employee,
+                contract,
+                level,
+                manager,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn every_manager_has_a_span_of_reports() {
+        let mut rng = SmallRng::seed_from_u64(8);
+        let shape = OrgShape {
+            depth: 4,
+            span_of_control: Bounds { min: 2, max: 4 },
+            departments: vec!["IT".to_string(), "Sales".to_string()],
+        };
+        let members = generate(&shape, 1000, &mut rng).unwrap();
+        assert_eq!(members[0].employee.position, "Chief Executive Officer");
+
+        for (index, member) in members.iter().enumerate() {
+            let reports = members
+                .iter()
+                .filter(|report| report.manager == Some(index))
+                .count();
+            match member.level {
+                0 => assert_eq!(reports, 2),
+                3 => assert_eq!(reports, 0),
+                _ => assert!((2..=4).contains(&reports)),
+            }
+            if let Some(manager) = member.manager {
+                assert!(manager < index);
+                assert_eq!(members[manager].level + 1, member.level);
+            }
+            if member.level == 3 {
+                let lead = &members[member.manager.unwrap()];
+                assert_eq!(lead.employee.position, "Team Lead");
+                assert_eq!(member.employee.team, lead.employee.team);
+                assert_eq!(member.employee.department, lead.employee.department);
+            }
+            assert!(
+                salary_range(&member.contract.type_of_contract).contains(&member.contract.salary)
+            );
+        }
+        assert!(generate(&shape, 10, &mut rng).is_err());
+    }
+}
@@ -0,0 +1,416 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{Duration, NaiveDate};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::SmallRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::db::schema::{table_schema, ColumnDef, ColumnKind, TableName};
+use crate::db::table_type_mysql::{Row, TableType};
+use crate::generator::expression::{referenced_table, Template, Val};
+use crate::generator::pattern::{check_column, Pattern};
+use crate::generator::text::TextGenerator;
+
+/// Replaces the generated values of a column.
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum ColumnOverride {
+    /// The same value in every row.
+    Fixed(Value),
+    /// One of the listed values, drawn with the given weights or uniformly without them.
+    OneOf {
+        values: Vec<Value>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        weights: Option<Vec<f64>>,
+    },
+    /// A number or `YYYY-MM-DD` date between `min` and `max`, both inclusive.
+    Range { min: Value, max: Value },
+    /// Text with `{expression}` parts computed from the same row after the other overrides,
+    /// e.g. `"{first_name | ascii | lower}.{last_name | slug}@acme.test"` or `"{start_date + 30d}"`.
+    /// See `generator::expression` for the operators and functions.
+    Template(String),
+    /// Generated text, cut to the column's length; see `generator::text`.
+    Text(TextGenerator),
+    /// Strings matching a regular expression, e.g. `"[A-Z]{3}-\\d{6}"`; see
+    /// `generator::pattern`.
+    Pattern(String),
+}
+
+/// Overrides keyed by `column`, or by `table.column` when several tables are generated.
+pub type Overrides = BTreeMap<String, ColumnOverride>;
+
+/// Finds the column an override key refers to among the generated tables.
+fn resolve(key: &str, tables: &[TableName]) -> Result<(TableName, &'static ColumnDef), String> {
+    let (table, column) = match key.split_once('.') {
+        Some((table, column)) => {
+            let table = tables
+                .iter()
+                .find(|name| name.as_str() == table)
+                .ok_or_else(|| format!("table {} is not generated by this request", table))?;
+            (*table, column)
+        }
+        None => match tables {
+            [table] => (*table, key),
+            _ => return Err("use table.column keys when generating several tables".to_string()),
+        },
+    };
+    let schema = table_schema(table.as_str()).ok_or("unknown table")?;
+    let column = schema
+        .column(column)
+        .ok_or_else(|| format!("table {} has no column {}", table.as_str(), column))?;
+    Ok((table, column))
+}
+
+fn check_value(column: &ColumnDef, value: &Value) -> Result<(), String> {
+    let valid = match column.kind {
+        ColumnKind::Text => match value.as_str() {
+            Some(text) => {
+                if let Some(max_len) = column.max_len {
+                    if text.chars().count() > max_len {
+                        return Err(format!("{:?} is longer than {} characters", text, max_len));
+                    }
+                }
+                true
+            }
+            None => false,
+        },
+        ColumnKind::Integer => value.as_i64().is_some_and(|n| i32::try_from(n).is_ok()),
+        ColumnKind::Decimal => value.is_number(),
+        ColumnKind::Date => parse_date(value).is_some(),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} is not a valid {} value",
+            value,
+            column.kind.as_str()
+        ))
+    }
+}
+
+fn parse_date(value: &Value) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.as_str()?, "%Y-%m-%d").ok()
+}
+
+/// Checks that every column a template reads exists in a generated table.
+fn check_template(template: &str, table: TableName, tables: &[TableName]) -> Result<(), String> {
+    let template = Template::parse(template)?;
+    for (related, column) in template.references() {
+        let source = match related {
+            Some(related) => referenced_table(related, tables)
+                .ok_or_else(|| format!("table {} is not generated by this request", related))?,
+            None => table,
+        };
+        let schema = table_schema(source.as_str()).ok_or("unknown table")?;
+        if schema.column(column).is_none() {
+            return Err(format!(
+                "template refers to unknown column {} of table {}",
+                column,
+                source.as_str()
+            ));
+        }
+    }
+    Ok(())
+}
+
+impl ColumnOverride {
+    fn check(
+        &self,
+        table: TableName,
+        column: &ColumnDef,
+        tables: &[TableName],
+    ) -> Result<(), String> {
+        match self {
+            ColumnOverride::Fixed(value) => check_value(column, value),
+            ColumnOverride::OneOf { values, weights } => {
+                if values.is_empty() {
+                    return Err("values must not be empty".to_string());
+                }
+                values
+                    .iter()
+                    .try_for_each(|value| check_value(column, value))?;
+                if let Some(weights) = weights {
+                    if weights.len() != values.len() {
+                        return Err("weights must have one entry per value".to_string());
+                    }
+                    if weights.iter().any(|w| !w.is_finite() || *w < 0.0)
+                        || weights.iter().sum::<f64>() <= 0.0
+                    {
+                        return Err("weights must be non-negative with a positive sum".to_string());
+                    }
+                }
+                Ok(())
+            }
+            ColumnOverride::Range { min, max } => {
+                check_value(column, min)?;
+                check_value(column, max)?;
+                let ordered = match column.kind {
+                    ColumnKind::Integer | ColumnKind::Decimal => min.as_f64() <= max.as_f64(),
+                    ColumnKind::Date => parse_date(min) <= parse_date(max),
+                    ColumnKind::Text => {
+                        return Err("ranges apply to number and date columns only".to_string())
+                    }
+                };
+                if ordered {
+                    Ok(())
+                } else {
+                    Err("min must not be greater than max".to_string())
+                }
+            }
+            ColumnOverride::Template(template) => check_template(template, table, tables),
+            ColumnOverride::Pattern(source) => check_column(source, column).map(|_| ()),
+            ColumnOverride::Text(generator) => match column.kind {
+                ColumnKind::Text => generator.check(),
+                _ => Err("text generators apply to text columns only".to_string()),
+            },
+        }
+    }
+
+    /// Draws a value; templates are rendered separately by `apply`, and `apply` parses
+    /// patterns once instead of calling this.
+    fn sample(&self, column: &ColumnDef, rng: &mut SmallRng) -> Value {
+        match self {
+            ColumnOverride::Fixed(value) => value.clone(),
+            ColumnOverride::OneOf { values, weights } => {
+                let index = match weights.as_ref().and_then(|w| WeightedIndex::new(w).ok()) {
+                    Some(weights) => weights.sample(rng),
+                    None => rng.gen_range(0..values.len()),
+                };
+                values[index].clone()
+            }
+            ColumnOverride::Range { min, max } => match column.kind {
+                ColumnKind::Integer => {
+                    let (min, max) = (min.as_i64().unwrap_or(0), max.as_i64().unwrap_or(0));
+                    Value::from(rng.gen_range(min..=max))
+                }
+                ColumnKind::Decimal => {
+                    let (min, max) = (min.as_f64().unwrap_or(0.0), max.as_f64().unwrap_or(0.0));
+                    Value::from((rng.gen_range(min..=max) * 100.0).round() / 100.0)
+                }
+                _ => {
+                    let (Some(min), Some(max)) = (parse_date(min), parse_date(max)) else {
+                        return Value::Null;
+                    };
+                    let offset = rng.gen_range(0..=(max - min).num_days());
+                    Value::from(
+                        (min + Duration::days(offset))
+                            .format("%Y-%m-%d")
+                            .to_string(),
+                    )
+                }
+            },
+            ColumnOverride::Text(generator) => Value::from(generator.generate(rng, column.max_len)),
+            ColumnOverride::Pattern(source) => Pattern::parse(source)
+                .map_or(Value::Null, |pattern| Value::from(pattern.generate(rng))),
+            ColumnOverride::Template(_) => Value::Null,
+        }
+    }
+}
+
+/// Templates of one table with the columns they fill.
+type TableTemplates = Vec<(&'static ColumnDef, Template)>;
+
+/// Template overrides of one table, ordered so templates reading another template's
+/// column come after it.
+fn template_order(templates: Vec<(&'static ColumnDef, &str)>) -> Result<TableTemplates, String> {
+    let mut pending = templates
+        .into_iter()
+        .map(|(column, source)| Ok((column, Template::parse(source)?)))
+        .collect::<Result<Vec<_>, String>>()?;
+    let mut ordered = Vec::new();
+    while !pending.is_empty() {
+        let ready = pending.iter().position(|(_, template)| {
+            !template.references().iter().any(|(related, name)| {
+                related.is_none() && pending.iter().any(|(column, _)| column.name == *name)
+            })
+        });
+        match ready {
+            Some(index) => ordered.push(pending.remove(index)),
+            None => {
+                let names: Vec<&str> = pending.iter().map(|(column, _)| column.name).collect();
+                return Err(format!(
+                    "templates refer to each other: {}",
+                    names.join(", ")
+                ));
+            }
+        }
+    }
+    Ok(ordered)
+}
+
+/// Adds the configured patterns of generated tables for columns the request does not
+/// override itself.
+pub fn add_patterns(
+    overrides: &mut Overrides,
+    patterns: &BTreeMap<String, String>,
+    tables: &[TableName],
+) {
+    let overridden: Vec<(TableName, &str)> = overrides
+        .keys()
+        .filter_map(|key| resolve(key, tables).ok())
+        .map(|(table, column)| (table, column.name))
+        .collect();
+    for (key, pattern) in patterns {
+        if let Ok((table, column)) = resolve(key, tables) {
+            if !overridden.contains(&(table, column.name)) {
+                overrides.insert(key.clone(), ColumnOverride::Pattern(pattern.clone()));
+            }
+        }
+    }
+}
+
+/// Columns of `table` the overrides set.
+pub fn columns(overrides: &Overrides, tables: &[TableName], table: TableName) -> Vec<&'static str> {
+    overrides
+        .keys()
+        .filter_map(|key| resolve(key, tables).ok())
+        .filter(|(owner, _)| *owner == table)
+        .map(|(_, column)| column.name)
+        .collect()
+}
+
+/// Every problem with the overrides of a request generating `tables`.
+pub fn validate(overrides: &Overrides, tables: &[TableName]) -> Vec<String> {
+    let mut errors: Vec<String> = overrides
+        .iter()
+        .filter_map(|(key, column_override)| {
+            resolve(key, tables)
+                .and_then(|(table, column)| column_override.check(table, column, tables))
+                .err()
+                .map(|e| format!("overrides.{}: {}", key, e))
+        })
+        .collect();
+    if errors.is_empty() {
+        if let Err(e) = templates(overrides, tables) {
+            errors.push(format!("overrides: {}", e));
+        }
+    }
+    errors
+}
+
+/// Parsed templates per table, in evaluation order.
+fn templates(
+    overrides: &Overrides,
+    tables: &[TableName],
+) -> Result<Vec<(TableName, TableTemplates)>, String> {
+    tables
+        .iter()
+        .map(|table| {
+            let mut columns = Vec::new();
+            for (key, column_override) in overrides {
+                if let ColumnOverride::Template(source) = column_override {
+                    let (owner, column) = resolve(key, tables)?;
+                    if owner == *table {
+                        columns.push((column, source.as_str()));
+                    }
+                }
+            }
+            Ok((*table, template_order(columns)?))
+        })
+        .collect()
+}
+
+/// Rows of generated data, tagged with their table, in the form overrides and invariants
+/// work on.
+pub fn to_rows(list: Vec<TableType>) -> Vec<(&'static str, Row)> {
+    list.into_iter()
+        .map(|item| (item.table_name(), item.to_row()))
+        .collect()
+}
+
+pub fn from_rows(rows: Vec<(&'static str, Row)>) -> Result<Vec<TableType>, String> {
+    rows.into_iter()
+        .map(|(table_name, row)| TableType::from_row(table_name, row).map_err(|e| e.to_string()))
+        .collect()
+}
+
+/// Applies validated overrides to generated rows, or only to the rows at `only` when given.
+/// Fixed values, lists and ranges come first; templates are rendered afterwards, table by
+/// table in `tables` order, so they see the final values of the columns they read.
+/// `table.column` in a template reads the row at the same position among the generated rows
+/// of that table.
+pub fn apply(
+    overrides: &Overrides,
+    tables: &[TableName],
+    rows: &mut [(&'static str, Row)],
+    only: Option<&BTreeSet<usize>>,
+    rng: &mut SmallRng,
+) -> Result<(), String> {
+    let selected = |index: &usize| only.is_none_or(|only| only.contains(index));
+    let mut sampled: Vec<(TableName, &ColumnDef, &ColumnOverride, Option<Pattern>)> = Vec::new();
+    for (key, column_override) in overrides {
+        let (table, column) = resolve(key, tables)?;
+        match column_override {
+            ColumnOverride::Template(_) => {}
+            ColumnOverride::Pattern(source) => {
+                let pattern = Pattern::parse(source).map_err(|e| format!("{}: {}", key, e))?;
+                sampled.push((table, column, column_override, Some(pattern)));
+            }
+            _ => sampled.push((table, column, column_override, None)),
+        }
+    }
+    let templates = templates(overrides, tables)?;
+
+    for (index, (table_name, row)) in rows.iter_mut().enumerate() {
+        if !selected(&index) {
+            continue;
+        }
+        for (_, column, column_override, pattern) in sampled
+            .iter()
+            .filter(|(table, ..)| table.as_str() == *table_name)
+        {
+            let value = match pattern {
+                Some(pattern) => Value::from(pattern.generate(rng)),
+                None => column_override.sample(column, rng),
+            };
+            row.insert(column.name.to_string(), value);
+        }
+    }
+
+    // Positions of each table's rows in `rows`, to find related rows.
+    let mut positions: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (index, (table_name, _)) in rows.iter().enumerate() {
+        positions.entry(table_name).or_default().push(index);
+    }
+
+    for (table, columns) in &templates {
+        let Some(own) = positions.get(table.as_str()) else {
+            continue;
+        };
+        for (nth, index) in own.iter().enumerate() {
+            if !selected(index) {
+                continue;
+            }
+            for (column, template) in columns {
+                let lookup = |related: Option<&str>, name: &str| {
+                    let (source, row) = match related {
+                        Some(related) => match positions.get(related).and_then(|p| p.get(nth)) {
+                            Some(position) => (related, &rows[*position].1),
+                            None => return Val::Null,
+                        },
+                        None => (table.as_str(), &rows[*index].1),
+                    };
+                    match (
+                        table_schema(source).and_then(|s| s.column(name)),
+                        row.get(name),
+                    ) {
+                        (Some(column), Some(value)) => Val::from_json(value, column.kind),
+                        _ => Val::Null,
+                    }
+                };
+                let value = template
+                    .render(&lookup)
+                    .and_then(|value| value.into_json(column.kind))
+                    .map_err(|e| format!("{}.{}: {}", table.as_str(), column.name, e))?;
+                check_value(column, &value)
+                    .map_err(|e| format!("{}.{}: {}", table.as_str(), column.name, e))?;
+                rows[*index].1.insert(column.name.to_string(), value);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,385 @@
+use std::collections::BTreeSet;
+
+use rand::rngs::SmallRng;
+use rand::Rng;
+
+use crate::db::schema::{ColumnDef, ColumnKind};
+
+/// Repetitions `*`, `+` and `{n,}` stop at this many more than their minimum.
+const UNBOUNDED_REPEAT: usize = 8;
+
+/// Largest count allowed in `{n}` and `{n,m}`.
+const MAX_REPEAT: usize = 1000;
+
+/// Groups a pattern may nest inside each other.
+const MAX_NESTING: usize = 32;
+
+/// Characters `.` and negated classes draw from: printable ASCII.
+const PRINTABLE: std::ops::RangeInclusive<char> = ' '..='~';
+
+#[derive(Debug, Clone)]
+enum Node {
+    Literal(char),
+    Class(Vec<char>),
+    Concat(Vec<Node>),
+    Alternation(Vec<Node>),
+    Repeat(Box<Node>, usize, usize),
+}
+
+impl Node {
+    fn max_len(&self) -> usize {
+        match self {
+            Node::Literal(_) | Node::Class(_) => 1,
+            Node::Concat(nodes) => nodes.iter().map(Node::max_len).sum(),
+            Node::Alternation(nodes) => nodes.iter().map(Node::max_len).max().unwrap_or(0),
+            Node::Repeat(node, _, max) => node.max_len() * max,
+        }
+    }
+
+    /// Natural logarithm of the number of strings the node matches.
+    fn log_count(&self) -> f64 {
+        match self {
+            Node::Literal(_) => 0.0,
+            Node::Class(chars) => (chars.len() as f64).ln(),
+            Node::Concat(nodes) => nodes.iter().map(Node::log_count).sum(),
+            Node::Alternation(nodes) => log_sum_exp(nodes.iter().map(Node::log_count)),
+            Node::Repeat(node, min, max) => {
+                let each = node.log_count();
+                log_sum_exp((*min..=*max).map(|times| times as f64 * each))
+            }
+        }
+    }
+
+    fn generate(&self, rng: &mut SmallRng, out: &mut String) {
+        match self {
+            Node::Literal(c) => out.push(*c),
+            Node::Class(chars) => out.push(chars[rng.gen_range(0..chars.len())]),
+            Node::Concat(nodes) => nodes.iter().for_each(|node| node.generate(rng, out)),
+            Node::Alternation(nodes) => {
+                let index = pick_weighted(nodes.iter().map(Node::log_count), rng);
+                nodes[index].generate(rng, out);
+            }
+            Node::Repeat(node, min, max) => {
+                let each = node.log_count();
+                let times =
+                    min + pick_weighted((*min..=*max).map(|times| times as f64 * each), rng);
+                for _ in 0..times {
+                    node.generate(rng, out);
+                }
+            }
+        }
+    }
+}
+
+fn log_sum_exp(logs: impl Iterator<Item = f64>) -> f64 {
+    let logs: Vec<f64> = logs.collect();
+    let max = logs.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if max == f64::NEG_INFINITY {
+        return max;
+    }
+    max + logs.iter().map(|log| (log - max).exp()).sum::<f64>().ln()
+}
+
+/// Index of an option drawn with probability proportional to `exp(log weight)`.
+fn pick_weighted(logs: impl Iterator<Item = f64>, rng: &mut SmallRng) -> usize {
+    let logs: Vec<f64> = logs.collect();
+    let max = logs.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let weights: Vec<f64> = logs.iter().map(|log| (log - max).exp()).collect();
+    let mut target = rng.gen::<f64>() * weights.iter().sum::<f64>();
+    for (index, weight) in weights.iter().enumerate() {
+        if target < *weight {
+            return index;
+        }
+        target -= weight;
+    }
+    weights.len() - 1
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    /// Groups open at the current position.
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn alternation(&mut self) -> Result<Node, String> {
+        let mut branches = vec![self.concat()?];
+        while self.chars.next_if_eq(&'|').is_some() {
+            branches.push(self.concat()?);
+        }
+        Ok(if branches.len() == 1 {
+            branches.remove(0)
+        } else {
+            Node::Alternation(branches)
+        })
+    }
+
+    fn concat(&mut self) -> Result<Node, String> {
+        let mut nodes = Vec::new();
+        while let Some(&c) = self.chars.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            nodes.push(self.quantified(atom)?);
+        }
+        Ok(Node::Concat(nodes))
+    }
+
+    fn atom(&mut self) -> Result<Node, String> {
+        match self.chars.next().ok_or("unexpected end of pattern")? {
+            '(' => {
+                if self.chars.next_if_eq(&'?').is_some() && self.chars.next() != Some(':') {
+                    return Err("only (?:...) groups are supported".to_string());
+                }
+                if self.depth == MAX_NESTING {
+                    return Err(format!("groups nest at most {} levels deep", MAX_NESTING));
+                }
+                self.depth += 1;
+                let node = self.alternation()?;
+                if self.chars.next() != Some(')') {
+                    return Err("unclosed (".to_string());
+                }
+                self.depth -= 1;
+                Ok(node)
+            }
+            '[' => self.class(),
+            '.' => Ok(Node::Class(PRINTABLE.collect())),
+            '\\' => self.escape(),
+            c @ ('*' | '+' | '?' | '{') => Err(format!("{} has nothing to repeat", c)),
+            c @ (']' | '}' | '^' | '$') => Err(format!("unexpected {} (escape it as \\{})", c, c)),
+            c => Ok(Node::Literal(c)),
+        }
+    }
+
+    fn escape(&mut self) -> Result<Node, String> {
+        let c = self.chars.next().ok_or("pattern ends with \\")?;
+        Ok(match class_escape(c) {
+            Some(chars) => Node::Class(chars),
+            None if c.is_ascii_alphanumeric() => return Err(format!("unknown escape \\{}", c)),
+            None => Node::Literal(c),
+        })
+    }
+
+    fn class(&mut self) -> Result<Node, String> {
+        let negated = self.chars.next_if_eq(&'^').is_some();
+        let mut set = BTreeSet::new();
+        loop {
+            let c = self.chars.next().ok_or("unclosed [")?;
+            let start = match c {
+                ']' => break,
+                '\\' => {
+                    let escaped = self.chars.next().ok_or("unclosed [")?;
+                    if let Some(chars) = class_escape(escaped) {
+                        set.extend(chars);
+                        continue;
+                    }
+                    escaped
+                }
+                c => c,
+            };
+            let is_range = self.chars.next_if_eq(&'-').is_some();
+            if !is_range {
+                set.insert(start);
+                continue;
+            }
+            match self.chars.peek() {
+                Some(']') | None => {
+                    set.insert(start);
+                    set.insert('-');
+                }
+                Some(_) => {
+                    let mut end = self.chars.next().ok_or("unclosed [")?;
+                    if end == '\\' {
+                        end = self.chars.next().ok_or("unclosed [")?;
+                    }
+                    if end < start {
+                        return Err(format!("range {}-{} is out of order", start, end));
+                    }
+                    set.extend(start..=end);
+                }
+            }
+        }
+        let chars: Vec<char> = if negated {
+            PRINTABLE.filter(|c| !set.contains(c)).collect()
+        } else {
+            set.into_iter().collect()
+        };
+        if chars.is_empty() {
+            return Err("character class matches nothing".to_string());
+        }
+        Ok(Node::Class(chars))
+    }
+
+    fn quantified(&mut self, atom: Node) -> Result<Node, String> {
+        let (min, max) = match self.chars.peek() {
+            Some('?') => (0, 1),
+            Some('*') => (0, UNBOUNDED_REPEAT),
+            Some('+') => (1, 1 + UNBOUNDED_REPEAT),
+            Some('{') => {
+                self.chars.next();
+                let mut body = String::new();
+                loop {
+                    match self.chars.next().ok_or("unclosed {")? {
+                        '}' => break,
+                        c => body.push(c),
+                    }
+                }
+                let number = |text: &str| {
+                    text.trim()
+                        .parse::<usize>()
+                        .map_err(|_| format!("invalid repetition {{{}}}", body))
+                };
+                let (min, max) = match body.split_once(',') {
+                    None => (number(&body)?, number(&body)?),
+                    Some((min, "")) => (number(min)?, number(min)? + UNBOUNDED_REPEAT),
+                    Some((min, max)) => (number(min)?, number(max)?),
+                };
+                if min > max || max > MAX_REPEAT {
+                    return Err(format!(
+                        "repetition {{{}}} needs min <= max <= {}",
+                        body, MAX_REPEAT
+                    ));
+                }
+                return Ok(Node::Repeat(Box::new(atom), min, max));
+            }
+            _ => return Ok(atom),
+        };
+        self.chars.next();
+        Ok(Node::Repeat(Box::new(atom), min, max))
+    }
+}
+
+/// Characters of `\d`, `\w` and `\s`.
+fn class_escape(c: char) -> Option<Vec<char>> {
+    match c {
+        'd' => Some(('0'..='9').collect()),
+        'w' => Some(
+            ('a'..='z')
+                .chain('A'..='Z')
+                .chain('0'..='9')
+                .chain(['_'])
+                .collect(),
+        ),
+        's' => Some(vec![' ']),
+        _ => None,
+    }
+}
+
+/// Strings matching a regular expression, e.g. `[0-9]{2}-[0-9]{3}` or `[A-Z]{3}-\d{6}`.
+/// Supports literals, `.`, classes with ranges and negation, `\d`, `\w`, `\s`, groups,
+/// alternation and the quantifiers `?`, `*`, `+`, `{n}`, `{n,}` and `{n,m}`; a leading `^`
+/// and trailing `$` are ignored. Every matching string is about equally likely; unbounded
+/// repetitions stop after a few occurrences.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    root: Node,
+}
+
+impl Pattern {
+    pub fn parse(source: &str) -> Result<Pattern, String> {
+        let source = source.strip_prefix('^').unwrap_or(source);
+        let source = match source.strip_suffix('$') {
+            Some(rest) if !rest.ends_with('\\') => rest,
+            _ => source,
+        };
+        let mut parser = Parser {
+            chars: source.chars().peekable(),
+            depth: 0,
+        };
+        let root = parser.alternation()?;
+        if let Some(c) = parser.chars.next() {
+            return Err(format!("unexpected {}", c));
+        }
+        Ok(Pattern { root })
+    }
+
+    /// Length in characters of the longest string the pattern produces.
+    pub fn max_len(&self) -> usize {
+        self.root.max_len()
+    }
+
+    pub fn generate(&self, rng: &mut SmallRng) -> String {
+        let mut out = String::new();
+        self.root.generate(rng, &mut out);
+        out
+    }
+}
+
+/// Checks that a pattern parses and its strings fit a text column.
+pub fn check_column(source: &str, column: &ColumnDef) -> Result<Pattern, String> {
+    if column.kind != ColumnKind::Text {
+        return Err("patterns apply to text columns only".to_string());
+    }
+    let pattern = Pattern::parse(source)?;
+    if let Some(max_len) = column.max_len {
+        if pattern.max_len() > max_len {
+            return Err(format!(
+                "pattern produces up to {} characters, the column holds {}",
+                pattern.max_len(),
+                max_len
+            ));
+        }
+    }
+    Ok(pattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn generated_strings_match_the_pattern() {
+        let mut rng = SmallRng::seed_from_u64(9);
+        let postal = Pattern::parse("[0-9]{2}-[0-9]{3}").unwrap();
+        let code = Pattern::parse(r"^[A-Z]{3}-\d{6}$").unwrap();
+        let phone = Pattern::parse(r"\+48 (?:5|6|7|8)\d{2}[ -]?\d{3}").unwrap();
+        assert_eq!(
+            (postal.max_len(), code.max_len(), phone.max_len()),
+            (6, 10, 11)
+        );
+        for _ in 0..100 {
+            let text = postal.generate(&mut rng);
+            assert!(text.len() == 6 && text.as_bytes()[2] == b'-', "{}", text);
+            let text = code.generate(&mut rng);
+            assert!(
+                text[..3].chars().all(|c| c.is_ascii_uppercase()),
+                "{}",
+                text
+            );
+            assert!(text[4..].chars().all(|c| c.is_ascii_digit()), "{}", text);
+            let text = phone.generate(&mut rng);
+            assert!(text.starts_with("+48 ") && text.len() >= 10, "{}", text);
+        }
+    }
+
+    #[test]
+    fn every_string_is_equally_likely() {
+        // "a" is one string, [0-9]{2} a hundred: the second branch should win ~99% of draws.
+        let pattern = Pattern::parse("a|[0-9]{2}").unwrap();
+        let mut rng = SmallRng::seed_from_u64(1);
+        let mut seen = BTreeMap::new();
+        for _ in 0..10_000 {
+            *seen.entry(pattern.generate(&mut rng)).or_insert(0) += 1;
+        }
+        assert!(seen.get("a").copied().unwrap_or(0) < 300);
+        assert!(seen.len() > 95);
+    }
+
+    #[test]
+    fn invalid_patterns_are_rejected() {
+        for source in ["[a-", "(ab", "a{3,1}", "*a", r"\q", "[^ -~]", "a{2000}"] {
+            assert!(Pattern::parse(source).is_err(), "{} was accepted", source);
+        }
+    }
+
+    #[test]
+    fn deeply_nested_groups_are_rejected() {
+        let nested = |levels| format!("{}a{}", "(".repeat(levels), ")".repeat(levels));
+        assert_eq!(Pattern::parse(&nested(MAX_NESTING)).unwrap().max_len(), 1);
+        assert!(Pattern::parse(&nested(MAX_NESTING + 1)).is_err());
+        assert!(Pattern::parse(&"(".repeat(100_000)).is_err());
+    }
+}
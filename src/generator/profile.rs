@@ -0,0 +1,478 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::{Duration, NaiveDate};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::SmallRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::db::schema::{table_schema, ColumnKind};
+use crate::db::table_type_mysql::{Row, TableType};
+
+const PROFILE_DIR: &str = "profiles";
+const TOP_VALUES: usize = 50;
+const HISTOGRAM_BINS: usize = 20;
+/// Generated values a free-text column chooses from to get close to a sampled length.
+const LENGTH_CANDIDATES: usize = 8;
+
+/// Date columns of the same row sampled together, the second never before the first.
+const DATE_PAIRS: [(&str, &str); 1] = [("start_date", "end_date")];
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct TableProfile {
+    pub table_name: String,
+    pub row_count: usize,
+    pub columns: Vec<ColumnProfile>,
+    /// Days between paired date columns of the same row.
+    #[serde(default)]
+    pub date_spans: Vec<DateSpan>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ColumnProfile {
+    pub name: String,
+    pub kind: ColumnKind,
+    pub null_rate: f64,
+    pub distinct: usize,
+    /// Most frequent values, at most `TOP_VALUES` of them.
+    pub frequencies: Vec<ValueFrequency>,
+    pub histogram: Option<Histogram>,
+    pub date_range: Option<DateRange>,
+    pub lengths: Option<LengthDistribution>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ValueFrequency {
+    pub value: Value,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Histogram {
+    pub min: f64,
+    pub max: f64,
+    pub bins: Vec<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct DateRange {
+    pub min: NaiveDate,
+    pub max: NaiveDate,
+    /// Row counts over equal-width day buckets between `min` and `max`.
+    pub bins: Vec<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct DateSpan {
+    pub start: String,
+    pub end: String,
+    pub days: Histogram,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct LengthDistribution {
+    pub min: usize,
+    pub max: usize,
+    pub mean: f64,
+    pub counts: BTreeMap<usize, usize>,
+}
+
+pub fn profile_rows(table_name: &str, list: &[TableType]) -> Result<TableProfile, String> {
+    let schema = table_schema(table_name).ok_or("Invalid table name")?;
+    let rows: Vec<Row> = list
+        .iter()
+        .filter(|item| item.table_name() == table_name)
+        .map(TableType::to_row)
+        .collect();
+
+    let columns = schema
+        .columns
+        .iter()
+        .map(|column| {
+            let values: Vec<&Value> = rows
+                .iter()
+                .filter_map(|row| row.get(column.name))
+                .filter(|value| !value.is_null())
+                .collect();
+            profile_column(column.name, column.kind, rows.len(), &values)
+        })
+        .collect();
+
+    let date_spans = DATE_PAIRS
+        .iter()
+        .filter(|(start, end)| {
+            let has = |name: &str| schema.columns.iter().any(|column| column.name == name);
+            has(start) && has(end)
+        })
+        .filter_map(|(start, end)| {
+            let days: Vec<f64> = rows
+                .iter()
+                .filter_map(|row| Some((date(row.get(*start)?)?, date(row.get(*end)?)?)))
+                .map(|(start, end)| (end - start).num_days() as f64)
+                .collect();
+            let (min, max, bins) = histogram(&days)?;
+            Some(DateSpan {
+                start: start.to_string(),
+                end: end.to_string(),
+                days: Histogram { min, max, bins },
+            })
+        })
+        .collect();
+
+    Ok(TableProfile {
+        table_name: table_name.to_string(),
+        row_count: rows.len(),
+        columns,
+        date_spans,
+    })
+}
+
+fn profile_column(
+    name: &str,
+    kind: ColumnKind,
+    row_count: usize,
+    values: &[&Value],
+) -> ColumnProfile {
+    let mut counts: HashMap<String, ValueFrequency> = HashMap::new();
+    for value in values {
+        counts
+            .entry(value.to_string())
+            .or_insert_with(|| ValueFrequency {
+                value: (*value).clone(),
+                count: 0,
+            })
+            .count += 1;
+    }
+    let distinct = counts.len();
+    let mut frequencies: Vec<ValueFrequency> = counts.into_values().collect();
+    frequencies.sort_by_key(|frequency| Reverse(frequency.count));
+    frequencies.truncate(TOP_VALUES);
+
+    let null_rate = if row_count == 0 {
+        0.0
+    } else {
+        (row_count - values.len()) as f64 / row_count as f64
+    };
+
+    let mut profile = ColumnProfile {
+        name: name.to_string(),
+        kind,
+        null_rate,
+        distinct,
+        frequencies,
+        histogram: None,
+        date_range: None,
+        lengths: None,
+    };
+
+    match kind {
+        ColumnKind::Integer | ColumnKind::Decimal => {
+            let numbers: Vec<f64> = values.iter().filter_map(|value| value.as_f64()).collect();
+            profile.histogram =
+                histogram(&numbers).map(|(min, max, bins)| Histogram { min, max, bins });
+        }
+        ColumnKind::Date => {
+            let dates: Vec<NaiveDate> = values.iter().filter_map(|value| date(value)).collect();
+            if let (Some(min), Some(max)) = (dates.iter().min(), dates.iter().max()) {
+                let offsets: Vec<f64> = dates
+                    .iter()
+                    .map(|date| (*date - *min).num_days() as f64)
+                    .collect();
+                let bins = bin_counts(&offsets, 0.0, (*max - *min).num_days() as f64);
+                profile.date_range = Some(DateRange {
+                    min: *min,
+                    max: *max,
+                    bins,
+                });
+            }
+        }
+        ColumnKind::Text => {
+            let lengths: Vec<usize> = values
+                .iter()
+                .filter_map(|value| value.as_str())
+                .map(|value| value.chars().count())
+                .collect();
+            if let (Some(min), Some(max)) = (lengths.iter().min(), lengths.iter().max()) {
+                let mut counts = BTreeMap::new();
+                for length in &lengths {
+                    *counts.entry(*length).or_insert(0) += 1;
+                }
+                profile.lengths = Some(LengthDistribution {
+                    min: *min,
+                    max: *max,
+                    mean: lengths.iter().sum::<usize>() as f64 / lengths.len() as f64,
+                    counts,
+                });
+            }
+        }
+    }
+
+    profile
+}
+
+fn date(value: &Value) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.as_str()?, "%Y-%m-%d").ok()
+}
+
+fn histogram(values: &[f64]) -> Option<(f64, f64, Vec<usize>)> {
+    let min = values.iter().cloned().reduce(f64::min)?;
+    let max = values.iter().cloned().reduce(f64::max)?;
+    Some((min, max, bin_counts(values, min, max)))
+}
+
+fn bin_counts(values: &[f64], min: f64, max: f64) -> Vec<usize> {
+    let mut bins = vec![0; HISTOGRAM_BINS];
+    let width = (max - min) / HISTOGRAM_BINS as f64;
+    for value in values {
+        let index = if width > 0.0 {
+            (((value - min) / width) as usize).min(HISTOGRAM_BINS - 1)
+        } else {
+            0
+        };
+        bins[index] += 1;
+    }
+    bins
+}
+
+fn sample_bins(bins: &[usize], min: f64, max: f64, rng: &mut SmallRng) -> f64 {
+    let Ok(index) = WeightedIndex::new(bins) else {
+        return min;
+    };
+    let width = (max - min) / bins.len() as f64;
+    let low = min + width * index.sample(rng) as f64;
+    if width > 0.0 {
+        rng.gen_range(low..low + width).min(max)
+    } else {
+        low
+    }
+}
+
+impl ColumnProfile {
+    /// Whether the next value is NULL, as often as in the profiled rows.
+    fn sample_null(&self, rng: &mut SmallRng) -> bool {
+        self.null_rate > 0.0 && rng.gen_bool(self.null_rate.min(1.0))
+    }
+
+    /// Draws a length following the profiled distribution of a text column.
+    fn sample_length(&self, rng: &mut SmallRng) -> Option<usize> {
+        let lengths = self.lengths.as_ref()?;
+        let (values, weights): (Vec<usize>, Vec<usize>) =
+            lengths.counts.clone().into_iter().unzip();
+        let index = WeightedIndex::new(&weights).ok()?.sample(rng);
+        Some(values[index])
+    }
+
+    /// Draws a value following the profiled distribution.
+    /// Returns `None` for free-text columns, which take the generated value closest to a
+    /// sampled length instead.
+    fn sample(&self, rng: &mut SmallRng) -> Option<Value> {
+        match self.kind {
+            ColumnKind::Text => {
+                if self.frequencies.is_empty() || self.distinct > TOP_VALUES {
+                    return None;
+                }
+                let weights: Vec<usize> = self.frequencies.iter().map(|f| f.count).collect();
+                let index = WeightedIndex::new(&weights).ok()?.sample(rng);
+                Some(self.frequencies[index].value.clone())
+            }
+            ColumnKind::Integer => {
+                let histogram = self.histogram.as_ref()?;
+                let value = sample_bins(&histogram.bins, histogram.min, histogram.max, rng);
+                Some(Value::from(value.round() as i64))
+            }
+            ColumnKind::Decimal => {
+                let histogram = self.histogram.as_ref()?;
+                let value = sample_bins(&histogram.bins, histogram.min, histogram.max, rng);
+                Some(Value::from((value * 100.0).round() / 100.0))
+            }
+            ColumnKind::Date => {
+                let range = self.date_range.as_ref()?;
+                let days = (range.max - range.min).num_days() as f64;
+                let offset = sample_bins(&range.bins, 0.0, days, rng).floor() as i64;
+                let date = range.min + Duration::days(offset);
+                Some(Value::from(date.format("%Y-%m-%d").to_string()))
+            }
+        }
+    }
+}
+
+impl TableProfile {
+    /// Reshapes generated rows of the profiled table so their columns follow the profile.
+    /// Rows of other tables are returned unchanged.
+    pub fn apply(
+        &self,
+        list: Vec<TableType>,
+        rng: &mut SmallRng,
+    ) -> Result<Vec<TableType>, String> {
+        let schema = table_schema(&self.table_name).ok_or("Invalid table name")?;
+        list.into_iter()
+            .map(|item| {
+                if item.table_name() != self.table_name {
+                    return Ok(item);
+                }
+                let mut row = item.to_row();
+                let mut lengths = Vec::new();
+                for column in &self.columns {
+                    let nullable = schema
+                        .columns
+                        .iter()
+                        .any(|def| def.name == column.name && def.nullable);
+                    if nullable && column.sample_null(rng) {
+                        row.insert(column.name.clone(), Value::Null);
+                    } else if let Some(value) = column.sample(rng) {
+                        row.insert(column.name.clone(), value);
+                    } else if let Some(length) = column.sample_length(rng) {
+                        lengths.push((&column.name, length));
+                    }
+                }
+                if !lengths.is_empty() {
+                    let candidates: Vec<Row> = TableType::generate_batch_with_rng(
+                        &self.table_name,
+                        LENGTH_CANDIDATES,
+                        rng,
+                    )
+                    .ok_or("Invalid table name")?
+                    .iter()
+                    .map(TableType::to_row)
+                    .collect();
+                    for (name, length) in lengths {
+                        let closest = candidates
+                            .iter()
+                            .filter_map(|candidate| candidate.get(name))
+                            .chain(row.get(name))
+                            .filter_map(Value::as_str)
+                            .min_by_key(|value| value.chars().count().abs_diff(length))
+                            .map(str::to_string);
+                        if let Some(value) = closest {
+                            row.insert(name.clone(), Value::from(value));
+                        }
+                    }
+                }
+                for span in &self.date_spans {
+                    let Some(start) = row.get(&span.start).and_then(date) else {
+                        continue;
+                    };
+                    if row.get(&span.end).is_some_and(|end| !end.is_null()) {
+                        let days = sample_bins(&span.days.bins, span.days.min, span.days.max, rng);
+                        let end = start + Duration::days(days.round().max(0.0) as i64);
+                        row.insert(
+                            span.end.clone(),
+                            Value::from(end.format("%Y-%m-%d").to_string()),
+                        );
+                    }
+                }
+                TableType::from_row(&self.table_name, row).map_err(|e| e.to_string())
+            })
+            .collect()
+    }
+
+    pub fn save(&self, name: &str) -> Result<PathBuf, String> {
+        let path = profile_path(name)?;
+        fs::create_dir_all(PROFILE_DIR).map_err(|e| e.to_string())?;
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(&path, json).map_err(|e| e.to_string())?;
+        Ok(path)
+    }
+
+    pub fn load(name: &str) -> Result<TableProfile, String> {
+        let path = profile_path(name)?;
+        let json = fs::read_to_string(&path)
+            .map_err(|_| format!("Profile not found: {}", path.display()))?;
+        serde_json::from_str(&json).map_err(|e| format!("Invalid profile {}: {}", name, e))
+    }
+}
+
+fn profile_path(name: &str) -> Result<PathBuf, String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return Err(format!("Invalid profile name: {}", name));
+    }
+    Ok(PathBuf::from(PROFILE_DIR).join(format!("{}.json", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::contract::Contract;
+    use crate::models::technology::Technology;
+    use rand::SeedableRng;
+
+    #[test]
+    fn sampled_rows_follow_the_profile() {
+        let mut rng = SmallRng::seed_from_u64(3);
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let rows: Vec<TableType> = (0..100)
+            .map(|index| {
+                TableType::Contract(Contract {
+                    type_of_contract: if index % 4 == 0 { "B2B" } else { "UoP" }.to_string(),
+                    start_date: start + Duration::days(index),
+                    end_date: (index % 10 >= 3).then(|| start + Duration::days(index + 30)),
+                    salary: 5000 + 10 * index as i32,
+                })
+            })
+            .collect();
+        let profile = profile_rows("contract", &rows).unwrap();
+        let end_date = &profile.columns[2];
+        assert_eq!(end_date.name, "end_date");
+        assert!((end_date.null_rate - 0.3).abs() < 1e-9);
+        assert_eq!(profile.date_spans[0].days.min, 30.0);
+
+        let generated = TableType::generate_batch_with_rng("contract", 1000, &mut rng).unwrap();
+        let sampled = profile.apply(generated, &mut rng).unwrap();
+        let mut nulls = 0;
+        for item in &sampled {
+            let TableType::Contract(contract) = item else {
+                panic!("not a contract");
+            };
+            assert!((5000..=5990).contains(&contract.salary));
+            assert!(["B2B", "UoP"].contains(&contract.type_of_contract.as_str()));
+            match contract.end_date {
+                Some(end_date) => assert_eq!((end_date - contract.start_date).num_days(), 30),
+                None => nulls += 1,
+            }
+        }
+        assert!((200..=400).contains(&nulls), "{} nulls", nulls);
+    }
+
+    #[test]
+    fn free_text_follows_the_length_distribution() {
+        let mut rng = SmallRng::seed_from_u64(4);
+        let rows: Vec<TableType> = (0..100)
+            .map(|index| {
+                TableType::Technology(Technology {
+                    name: format!("Tech {}", index),
+                    description: format!("{:<40}", index),
+                })
+            })
+            .collect();
+        let profile = profile_rows("technology", &rows).unwrap();
+
+        let deviation = |list: &[TableType]| -> usize {
+            list.iter()
+                .map(|item| {
+                    let TableType::Technology(technology) = item else {
+                        panic!("not a technology");
+                    };
+                    technology.description.chars().count().abs_diff(40)
+                })
+                .sum()
+        };
+        let generated = TableType::generate_batch_with_rng("technology", 200, &mut rng).unwrap();
+        let before = deviation(&generated);
+        let sampled = profile.apply(generated, &mut rng).unwrap();
+        assert!(
+            deviation(&sampled) * 2 < before,
+            "{} vs {}",
+            deviation(&sampled),
+            before
+        );
+    }
+}
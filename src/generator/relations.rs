@@ -0,0 +1,243 @@
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::db::schema::TableName;
+use crate::generator::text::Bounds;
+
+/// Most links a single row may get in one relation.
+const MAX_LINKS_PER_ROW: usize = 1000;
+
+/// Steepest popularity skew a Zipf distribution may have.
+const MAX_ZIPF_EXPONENT: f64 = 5.0;
+
+/// Draws per wanted link before a row settles for fewer, distinct links; only reached when a
+/// steep skew leaves few rows likely to be drawn.
+const DRAWS_PER_LINK: usize = 50;
+
+/// Relative frequency of skill levels 1 (beginner) to 5 (expert).
+const SKILL_LEVEL_WEIGHTS: [u32; 5] = [15, 30, 30, 18, 7];
+
+/// Many-to-many relationships between generated tables. Rows of the first table get links to
+/// rows of the second: employees to projects and technologies, projects to technologies.
+#[derive(
+    Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Relation {
+    EmployeeProject,
+    ProjectTechnology,
+    /// Links carry a skill level from 1 to 5.
+    EmployeeTechnology,
+}
+
+impl Relation {
+    pub const ALL: [Relation; 3] = [
+        Relation::EmployeeProject,
+        Relation::ProjectTechnology,
+        Relation::EmployeeTechnology,
+    ];
+
+    /// The relation stored in the MySQL join table with the given name.
+    pub fn from_table(name: &str) -> Option<Relation> {
+        Relation::ALL
+            .into_iter()
+            .find(|relation| relation.as_str() == name)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Relation::EmployeeProject => "employee_project",
+            Relation::ProjectTechnology => "project_technology",
+            Relation::EmployeeTechnology => "employee_technology",
+        }
+    }
+
+    /// The table whose rows own the links and the table they link to.
+    pub fn tables(&self) -> (TableName, TableName) {
+        match self {
+            Relation::EmployeeProject => (TableName::Employee, TableName::Project),
+            Relation::ProjectTechnology => (TableName::Project, TableName::Technology),
+            Relation::EmployeeTechnology => (TableName::Employee, TableName::Technology),
+        }
+    }
+
+    /// Array of references on the owning MongoDB documents.
+    pub fn field(&self) -> &'static str {
+        match self {
+            Relation::EmployeeProject => "project_ids",
+            Relation::ProjectTechnology => "technology_ids",
+            Relation::EmployeeTechnology => "skills",
+        }
+    }
+
+    pub fn has_skill_level(&self) -> bool {
+        matches!(self, Relation::EmployeeTechnology)
+    }
+}
+
+/// How likely each row of the linked table is to be picked.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Popularity {
+    #[default]
+    Uniform,
+    /// The k-th most popular row is picked in proportion to 1/k^exponent; which rows are
+    /// popular is random.
+    Zipf {
+        #[serde(default = "default_exponent")]
+        exponent: f64,
+    },
+}
+
+fn default_exponent() -> f64 {
+    1.0
+}
+
+/// Number of links of each owning row and how they spread over the linked rows.
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Cardinality {
+    /// Links of every row of the owning table, e.g. technologies per project; rows get as
+    /// many as the linked table has when it has fewer.
+    pub per_row: Bounds,
+    #[serde(default)]
+    pub popularity: Popularity,
+}
+
+impl Cardinality {
+    /// Every problem with the cardinality, prefixed with `path`.
+    pub fn validate(&self, path: &str) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.per_row.min > self.per_row.max || self.per_row.max > MAX_LINKS_PER_ROW {
+            errors.push(format!(
+                "{}.per_row needs min <= max <= {}",
+                path, MAX_LINKS_PER_ROW
+            ));
+        }
+        if let Popularity::Zipf { exponent } = self.popularity {
+            if !(exponent > 0.0 && exponent <= MAX_ZIPF_EXPONENT) {
+                errors.push(format!(
+                    "{}.popularity: zipf exponent must be positive and at most {}",
+                    path, MAX_ZIPF_EXPONENT
+                ));
+            }
+        }
+        errors
+    }
+}
+
+/// A link between a row of the owning table and a row of the linked table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link<K> {
+    pub owner: K,
+    pub linked: K,
+    pub skill_level: Option<u8>,
+}
+
+/// Links every owner to distinct linked rows, drawn with the cardinality's popularity.
+pub fn link<K: Clone>(
+    relation: Relation,
+    cardinality: &Cardinality,
+    owners: &[K],
+    linked: &[K],
+    rng: &mut SmallRng,
+) -> Vec<Link<K>> {
+    if linked.is_empty() {
+        return Vec::new();
+    }
+    let mut ranked: Vec<usize> = (0..linked.len()).collect();
+    ranked.shuffle(rng);
+    let weights: Vec<f64> = match cardinality.popularity {
+        Popularity::Uniform => vec![1.0; linked.len()],
+        Popularity::Zipf { exponent } => (1..=linked.len())
+            .map(|rank| 1.0 / (rank as f64).powf(exponent))
+            .collect(),
+    };
+    let popularity = WeightedIndex::new(&weights).expect("Invalid popularity weights");
+    let skill_levels = WeightedIndex::new(SKILL_LEVEL_WEIGHTS).expect("Invalid skill weights");
+
+    let mut links = Vec::new();
+    for owner in owners {
+        let wanted = rng
+            .gen_range(cardinality.per_row.min..=cardinality.per_row.max)
+            .min(linked.len());
+        let mut picked: Vec<usize> = Vec::with_capacity(wanted);
+        let mut draws = 0;
+        while picked.len() < wanted && draws < wanted * DRAWS_PER_LINK {
+            let index = ranked[popularity.sample(rng)];
+            if !picked.contains(&index) {
+                picked.push(index);
+            }
+            draws += 1;
+        }
+        for index in picked {
+            links.push(Link {
+                owner: owner.clone(),
+                linked: linked[index].clone(),
+                skill_level: relation
+                    .has_skill_level()
+                    .then(|| skill_levels.sample(rng) as u8 + 1),
+            });
+        }
+    }
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn relations_are_found_by_table() {
+        for relation in Relation::ALL {
+            assert_eq!(Relation::from_table(relation.as_str()), Some(relation));
+        }
+        assert_eq!(Relation::from_table("employee"), None);
+    }
+
+    #[test]
+    fn links_follow_cardinality_and_popularity() {
+        let mut rng = SmallRng::seed_from_u64(12);
+        let owners: Vec<u64> = (1..=500).collect();
+        let linked: Vec<u64> = (1..=40).collect();
+        let cardinality = Cardinality {
+            per_row: Bounds { min: 1, max: 5 },
+            popularity: Popularity::Zipf { exponent: 1.2 },
+        };
+        let links = link(
+            Relation::EmployeeTechnology,
+            &cardinality,
+            &owners,
+            &linked,
+            &mut rng,
+        );
+
+        let mut uses = vec![0; linked.len() + 1];
+        for owner in &owners {
+            let own: Vec<&Link<u64>> = links.iter().filter(|l| l.owner == *owner).collect();
+            assert!((1..=5).contains(&own.len()));
+            for (index, link) in own.iter().enumerate() {
+                assert!(own[..index].iter().all(|other| other.linked != link.linked));
+                assert!((1..=5).contains(&link.skill_level.unwrap()));
+                uses[link.linked as usize] += 1;
+            }
+        }
+        uses.sort_unstable();
+        // The most popular technology is used far more often than the median one.
+        assert!(uses[linked.len()] > 5 * uses[linked.len() / 2]);
+
+        let none = link(
+            Relation::ProjectTechnology,
+            &cardinality,
+            &owners,
+            &[],
+            &mut rng,
+        );
+        assert!(none.is_empty());
+    }
+}
@@ -0,0 +1,372 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use utoipa::ToSchema;
+
+use crate::db::backends::{Backends, DbType, Target};
+use crate::db::mongodb::{
+    delete_random_mongodb, insert_batch_mongodb, update_random_mongodb, update_run_mongodb,
+};
+use crate::db::mysql::{delete_random_mysql, insert_batch, update_random_mysql, update_run_mysql};
+use crate::db::schema::table_schema;
+use crate::db::table_type_mysql::TableType;
+use crate::models::generation_run::GenerationRun;
+
+const TICK: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct StreamConfig {
+    pub db_type: DbType,
+    #[serde(flatten)]
+    pub target: Target,
+    pub table_name: String,
+    /// Target number of operations per second once ramp-up is over, at most `max_count`.
+    pub rate: f64,
+    /// Seconds over which the rate grows linearly from zero to `rate`.
+    #[serde(default)]
+    pub ramp_up_secs: f64,
+    /// Random variation of the tick length, as a fraction (0.0 - 1.0).
+    #[serde(default)]
+    pub jitter: f64,
+    /// Stops the stream after this many seconds; runs until cancelled when absent.
+    pub duration_secs: Option<f64>,
+    /// Fraction of operations that update an existing row.
+    #[serde(default)]
+    pub update_ratio: f64,
+    /// Fraction of operations that delete an existing row.
+    #[serde(default)]
+    pub delete_ratio: f64,
+}
+
+impl StreamConfig {
+    /// Checks the settings; `max_rate` bounds the operations per second.
+    pub fn validate(&self, max_rate: f64) -> Result<(), String> {
+        if table_schema(&self.table_name).is_none() {
+            return Err("Invalid table name".to_string());
+        }
+        if !(self.rate > 0.0 && self.rate <= max_rate) {
+            return Err(format!(
+                "Rate must be greater than zero and at most {}",
+                max_rate
+            ));
+        }
+        if !(self.ramp_up_secs.is_finite() && self.ramp_up_secs >= 0.0) {
+            return Err("Ramp-up must be a non-negative number of seconds".to_string());
+        }
+        if self
+            .duration_secs
+            .is_some_and(|duration| !(duration.is_finite() && duration > 0.0))
+        {
+            return Err("Duration must be a positive number of seconds".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.jitter) {
+            return Err("Jitter must be between 0 and 1".to_string());
+        }
+        let ratios = [self.update_ratio, self.delete_ratio];
+        if ratios.iter().any(|ratio| !(0.0..=1.0).contains(ratio))
+            || self.update_ratio + self.delete_ratio > 1.0
+        {
+            return Err("Update and delete ratios must be between 0 and 1 in total".to_string());
+        }
+        Ok(())
+    }
+
+    fn current_rate(&self, elapsed_secs: f64) -> f64 {
+        if self.ramp_up_secs > 0.0 && elapsed_secs < self.ramp_up_secs {
+            self.rate * elapsed_secs / self.ramp_up_secs
+        } else {
+            self.rate
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct StreamStats {
+    inserted: AtomicU64,
+    updated: AtomicU64,
+    deleted: AtomicU64,
+    errors: AtomicU64,
+    last_error: Mutex<Option<String>>,
+}
+
+impl StreamStats {
+    fn fail(&self, e: String) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+        *self.last_error.lock().unwrap() = Some(e);
+    }
+
+    fn record(&self, counter: &AtomicU64, result: Result<usize, String>) {
+        match result {
+            Ok(count) => {
+                counter.fetch_add(count as u64, Ordering::Relaxed);
+            }
+            Err(e) => self.fail(e),
+        }
+    }
+}
+
+struct StreamJob {
+    config: StreamConfig,
+    run: GenerationRun,
+    started_at: Instant,
+    stats: Arc<StreamStats>,
+    handle: JoinHandle<()>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StreamStatus {
+    pub id: u64,
+    pub config: StreamConfig,
+    /// Run the stream's inserted rows are tagged with.
+    pub run_id: String,
+    pub running: bool,
+    pub elapsed_secs: f64,
+    pub inserted: u64,
+    pub updated: u64,
+    pub deleted: u64,
+    pub errors: u64,
+    pub last_error: Option<String>,
+}
+
+/// Registry of running and finished stream jobs, shared between handlers.
+#[derive(Clone, Default)]
+pub struct StreamJobs {
+    jobs: Arc<Mutex<HashMap<u64, StreamJob>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl StreamJobs {
+    /// Starts a job whose inserts are tagged with the already recorded `run`.
+    pub fn start(&self, config: StreamConfig, run: GenerationRun, backends: Backends) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let stats = Arc::new(StreamStats::default());
+        let handle = tokio::spawn(run_stream(
+            config.clone(),
+            run.clone(),
+            backends,
+            stats.clone(),
+        ));
+
+        self.jobs.lock().unwrap().insert(
+            id,
+            StreamJob {
+                config,
+                run,
+                started_at: Instant::now(),
+                stats,
+                handle,
+            },
+        );
+        id
+    }
+
+    pub fn status(&self, id: u64) -> Option<StreamStatus> {
+        self.jobs.lock().unwrap().get(&id).map(|job| job.status(id))
+    }
+
+    pub fn list(&self) -> Vec<StreamStatus> {
+        let jobs = self.jobs.lock().unwrap();
+        let mut statuses: Vec<StreamStatus> =
+            jobs.iter().map(|(id, job)| job.status(*id)).collect();
+        statuses.sort_by_key(|status| status.id);
+        statuses
+    }
+
+    /// Stops a job and records its run as finished; its final status stays available until
+    /// the server restarts.
+    pub async fn cancel(&self, id: u64, backends: &Backends) -> Option<StreamStatus> {
+        let (status, run, stats) = {
+            let jobs = self.jobs.lock().unwrap();
+            let job = jobs.get(&id)?;
+            let running = !job.handle.is_finished();
+            job.handle.abort();
+            (
+                job.status(id),
+                running.then(|| job.run.clone()),
+                job.stats.clone(),
+            )
+        };
+        if let Some(run) = run {
+            if let Err(e) = finish_run(&status.config, backends, run, status.inserted).await {
+                stats.fail(e);
+            }
+        }
+        Some(status)
+    }
+}
+
+impl StreamJob {
+    fn status(&self, id: u64) -> StreamStatus {
+        StreamStatus {
+            id,
+            config: self.config.clone(),
+            run_id: self.run.run_id.clone(),
+            running: !self.handle.is_finished(),
+            elapsed_secs: self.started_at.elapsed().as_secs_f64(),
+            inserted: self.stats.inserted.load(Ordering::Relaxed),
+            updated: self.stats.updated.load(Ordering::Relaxed),
+            deleted: self.stats.deleted.load(Ordering::Relaxed),
+            errors: self.stats.errors.load(Ordering::Relaxed),
+            last_error: self.stats.last_error.lock().unwrap().clone(),
+        }
+    }
+}
+
+async fn run_stream(
+    config: StreamConfig,
+    run: GenerationRun,
+    backends: Backends,
+    stats: Arc<StreamStats>,
+) {
+    let mut rng = SmallRng::seed_from_u64(run.seed as u64);
+    let started = Instant::now();
+    let mut last_tick = started;
+    let mut budget = 0.0;
+
+    loop {
+        let elapsed = started.elapsed().as_secs_f64();
+        if config
+            .duration_secs
+            .is_some_and(|duration| elapsed >= duration)
+        {
+            break;
+        }
+
+        let jitter = 1.0 + config.jitter * rng.gen_range(-1.0..=1.0);
+        tokio::time::sleep(TICK.mul_f64(jitter)).await;
+
+        let now = Instant::now();
+        budget += config.current_rate(elapsed) * (now - last_tick).as_secs_f64();
+        last_tick = now;
+        let operations = budget.floor() as usize;
+        budget -= operations as f64;
+
+        let (mut inserts, mut updates, mut deletes) = (0, 0, 0);
+        for _ in 0..operations {
+            let roll: f64 = rng.gen();
+            if roll < config.delete_ratio {
+                deletes += 1;
+            } else if roll < config.delete_ratio + config.update_ratio {
+                updates += 1;
+            } else {
+                inserts += 1;
+            }
+        }
+
+        if inserts > 0 {
+            let result = insert_rows(&config, &backends, inserts, &run.run_id).await;
+            stats.record(&stats.inserted, result);
+        }
+        if updates > 0 {
+            let result = update_rows(&config, &backends, updates).await;
+            stats.record(&stats.updated, result);
+        }
+        if deletes > 0 {
+            let result = delete_rows(&config, &backends, deletes).await;
+            stats.record(&stats.deleted, result);
+        }
+    }
+
+    let inserted = stats.inserted.load(Ordering::Relaxed);
+    if let Err(e) = finish_run(&config, &backends, run, inserted).await {
+        stats.fail(e);
+    }
+}
+
+/// Records the rows a stream inserted and when it stopped.
+async fn finish_run(
+    config: &StreamConfig,
+    backends: &Backends,
+    mut run: GenerationRun,
+    inserted: u64,
+) -> Result<(), String> {
+    run.counts
+        .insert(config.table_name.clone(), inserted as usize);
+    run.finished_at = Some(Utc::now().naive_utc());
+    match config.db_type {
+        DbType::Mysql => update_run_mysql(&mysql(backends, config).await?, &run)
+            .await
+            .map_err(|e| e.to_string()),
+        DbType::Mongodb => update_run_mongodb(&mongodb(backends, config).await?, &run)
+            .await
+            .map_err(|e| e.to_string()),
+    }
+}
+
+async fn insert_rows(
+    config: &StreamConfig,
+    backends: &Backends,
+    count: usize,
+    run_id: &str,
+) -> Result<usize, String> {
+    let rows = TableType::generate_batch(&config.table_name, count)
+        .ok_or_else(|| format!("Invalid table name: {}", config.table_name))?;
+    match config.db_type {
+        DbType::Mysql => insert_batch(&mysql(backends, config).await?, &rows, Some(run_id))
+            .await
+            .map_err(|e| e.to_string())?,
+        DbType::Mongodb => {
+            insert_batch_mongodb(&mongodb(backends, config).await?, &rows, Some(run_id))
+                .await
+                .map_err(|e| e.to_string())?
+        }
+    }
+    Ok(rows.len())
+}
+
+async fn update_rows(
+    config: &StreamConfig,
+    backends: &Backends,
+    count: usize,
+) -> Result<usize, String> {
+    match config.db_type {
+        DbType::Mysql => {
+            update_random_mysql(&mysql(backends, config).await?, &config.table_name, count)
+                .await
+                .map(|keys| keys.len())
+                .map_err(|e| e.to_string())
+        }
+        DbType::Mongodb => {
+            update_random_mongodb(&mongodb(backends, config).await?, &config.table_name, count)
+                .await
+                .map(|ids| ids.len())
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+async fn delete_rows(
+    config: &StreamConfig,
+    backends: &Backends,
+    count: usize,
+) -> Result<usize, String> {
+    match config.db_type {
+        DbType::Mysql => {
+            delete_random_mysql(&mysql(backends, config).await?, &config.table_name, count)
+                .await
+                .map(|keys| keys.len())
+                .map_err(|e| e.to_string())
+        }
+        DbType::Mongodb => {
+            delete_random_mongodb(&mongodb(backends, config).await?, &config.table_name, count)
+                .await
+                .map(|ids| ids.len())
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+async fn mysql(backends: &Backends, config: &StreamConfig) -> Result<mysql_async::Pool, String> {
+    backends.mysql(&config.target).await.map_err(|(_, e)| e)
+}
+
+async fn mongodb(backends: &Backends, config: &StreamConfig) -> Result<mongodb::Database, String> {
+    backends.mongodb(&config.target).await.map_err(|(_, e)| e)
+}
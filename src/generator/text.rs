@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::utils::utils::word_list;
+
+/// Upper bound on words or sentences a request may ask for.
+const MAX_BOUND: usize = 200;
+
+/// Words after which a Markov sentence is cut off if the chain has not ended it.
+const MAX_SENTENCE_WORDS: usize = 40;
+
+/// An inclusive range of words or sentences.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Bounds {
+    pub min: usize,
+    pub max: usize,
+}
+
+impl Bounds {
+    fn words() -> Bounds {
+        Bounds { min: 4, max: 12 }
+    }
+
+    fn sentences() -> Bounds {
+        Bounds { min: 1, max: 2 }
+    }
+
+    fn paragraph() -> Bounds {
+        Bounds { min: 3, max: 6 }
+    }
+
+    fn check(&self) -> Result<(), String> {
+        if self.min == 0 || self.min > self.max {
+            Err("bounds need 1 <= min <= max".to_string())
+        } else if self.max > MAX_BOUND {
+            Err(format!("bounds may not exceed {}", MAX_BOUND))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn sample(&self, rng: &mut SmallRng) -> usize {
+        rng.gen_range(self.min..=self.max)
+    }
+}
+
+/// Bundled texts Markov chains are trained on; a data pack can replace them.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Corpus {
+    Projects,
+    Tasks,
+    Technology,
+}
+
+impl Corpus {
+    const ALL: [Corpus; 3] = [Corpus::Projects, Corpus::Tasks, Corpus::Technology];
+
+    fn word_list(&self) -> &'static str {
+        match self {
+            Corpus::Projects => "project_descriptions.txt",
+            Corpus::Tasks => "task_descriptions.txt",
+            Corpus::Technology => "technology_descriptions.txt",
+        }
+    }
+}
+
+/// Word-combination grammars for short names.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Grammar {
+    /// Adjective and noun, e.g. "Silent Falcon".
+    Codename,
+    /// Verb and object, e.g. "Migrate billing service".
+    Task,
+    /// Prefix and field, e.g. "Distributed Stream Processing".
+    Technology,
+}
+
+/// Generates the text of a column; the result is cut to the column's length at a word
+/// boundary.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum TextGenerator {
+    /// One lorem ipsum sentence of `words` words.
+    Lorem {
+        #[serde(default = "Bounds::words")]
+        words: Bounds,
+    },
+    /// `sentences` lorem ipsum sentences.
+    Paragraph {
+        #[serde(default = "Bounds::paragraph")]
+        sentences: Bounds,
+    },
+    /// `sentences` sentences from a Markov chain trained on a bundled corpus.
+    Markov {
+        corpus: Corpus,
+        #[serde(default = "Bounds::sentences")]
+        sentences: Bounds,
+    },
+    Grammar(Grammar),
+}
+
+impl TextGenerator {
+    pub fn check(&self) -> Result<(), String> {
+        match self {
+            TextGenerator::Lorem { words: bounds }
+            | TextGenerator::Paragraph { sentences: bounds }
+            | TextGenerator::Markov {
+                sentences: bounds, ..
+            } => bounds.check(),
+            TextGenerator::Grammar(_) => Ok(()),
+        }
+    }
+
+    pub fn generate(&self, rng: &mut SmallRng, max_len: Option<usize>) -> String {
+        let text = match self {
+            TextGenerator::Lorem { words } => lorem_sentence(words.sample(rng), rng),
+            TextGenerator::Paragraph { sentences } => (0..sentences.sample(rng))
+                .map(|_| lorem_sentence(rng.gen_range(4..=12), rng))
+                .collect::<Vec<_>>()
+                .join(" "),
+            TextGenerator::Markov { corpus, sentences } => {
+                let chain = &chains()[corpus];
+                (0..sentences.sample(rng))
+                    .map(|_| chain.sentence(rng))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            }
+            TextGenerator::Grammar(grammar) => grammar.generate(rng),
+        };
+        match max_len {
+            Some(max_len) => truncate(&text, max_len),
+            None => text,
+        }
+    }
+}
+
+fn pick(list: &str, rng: &mut SmallRng) -> String {
+    word_list(list).choose(rng).cloned().unwrap_or_default()
+}
+
+impl Grammar {
+    fn generate(&self, rng: &mut SmallRng) -> String {
+        let (first, second) = match self {
+            Grammar::Codename => ("adjectives.txt", "nouns.txt"),
+            Grammar::Task => ("verbs.txt", "objects.txt"),
+            Grammar::Technology => ("technology_prefixes.txt", "technology_terms.txt"),
+        };
+        format!("{} {}", pick(first, rng), pick(second, rng))
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn lorem_sentence(words: usize, rng: &mut SmallRng) -> String {
+    let words: Vec<String> = (0..words).map(|_| pick("lorem.txt", rng)).collect();
+    format!("{}.", capitalize(&words.join(" ")))
+}
+
+/// Cuts text to at most `max_len` characters, ending at a whole word and keeping the final
+/// full stop when the text had one.
+pub fn truncate(text: &str, max_len: usize) -> String {
+    if text.chars().count() <= max_len {
+        return text.to_string();
+    }
+    let sentence = text.ends_with('.');
+    let budget = if sentence {
+        max_len.saturating_sub(1)
+    } else {
+        max_len
+    };
+    let mut cut = String::new();
+    for word in text.split(' ') {
+        let needed = cut.chars().count() + word.chars().count() + usize::from(!cut.is_empty());
+        if needed > budget {
+            break;
+        }
+        if !cut.is_empty() {
+            cut.push(' ');
+        }
+        cut.push_str(word);
+    }
+    if cut.is_empty() {
+        cut = text.chars().take(budget).collect();
+    }
+    let mut cut = cut
+        .trim_end_matches(|c: char| c.is_ascii_punctuation())
+        .to_string();
+    if sentence {
+        cut.push('.');
+    }
+    cut
+}
+
+/// A word-level Markov chain of order two; an empty word ends a sentence.
+#[derive(Debug, Default)]
+struct Chain {
+    starts: Vec<(String, String)>,
+    next: HashMap<(String, String), Vec<String>>,
+}
+
+impl Chain {
+    fn train(lines: &[String]) -> Chain {
+        let mut chain = Chain::default();
+        for line in lines {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if words.len() < 2 {
+                continue;
+            }
+            chain
+                .starts
+                .push((words[0].to_string(), words[1].to_string()));
+            for (index, pair) in words.windows(2).enumerate() {
+                let follower = words.get(index + 2).copied().unwrap_or_default();
+                chain
+                    .next
+                    .entry((pair[0].to_string(), pair[1].to_string()))
+                    .or_default()
+                    .push(follower.to_string());
+            }
+        }
+        chain
+    }
+
+    fn sentence(&self, rng: &mut SmallRng) -> String {
+        let Some((first, second)) = self.starts.choose(rng) else {
+            return String::new();
+        };
+        let mut words = vec![first.clone(), second.clone()];
+        while words.len() < MAX_SENTENCE_WORDS {
+            let key = (
+                words[words.len() - 2].clone(),
+                words[words.len() - 1].clone(),
+            );
+            match self
+                .next
+                .get(&key)
+                .and_then(|followers| followers.choose(rng))
+            {
+                Some(word) if !word.is_empty() => words.push(word.clone()),
+                _ => break,
+            }
+        }
+        let sentence = words.join(" ");
+        if sentence.ends_with(['.', '!', '?']) {
+            sentence
+        } else {
+            format!(
+                "{}.",
+                sentence.trim_end_matches(|c: char| c.is_ascii_punctuation())
+            )
+        }
+    }
+}
+
+/// Chains of every corpus, trained on first use from the loaded word lists.
+fn chains() -> &'static HashMap<Corpus, Chain> {
+    static CHAINS: OnceLock<HashMap<Corpus, Chain>> = OnceLock::new();
+    CHAINS.get_or_init(|| {
+        Corpus::ALL
+            .iter()
+            .map(|corpus| (*corpus, Chain::train(word_list(corpus.word_list()))))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn text_fits_the_column() {
+        let mut rng = SmallRng::seed_from_u64(11);
+        let generators = [
+            TextGenerator::Lorem {
+                words: Bounds { min: 50, max: 80 },
+            },
+            TextGenerator::Paragraph {
+                sentences: Bounds::paragraph(),
+            },
+            TextGenerator::Markov {
+                corpus: Corpus::Technology,
+                sentences: Bounds { min: 3, max: 5 },
+            },
+            TextGenerator::Grammar(Grammar::Codename),
+        ];
+        for generator in generators {
+            for _ in 0..50 {
+                let text = generator.generate(&mut rng, Some(100));
+                assert!(!text.is_empty());
+                assert!(text.chars().count() <= 100, "{:?} is too long", text);
+            }
+        }
+        assert_eq!(
+            truncate("Lorem ipsum dolor sit amet.", 18),
+            "Lorem ipsum dolor."
+        );
+    }
+
+    #[test]
+    fn markov_sentences_come_from_the_corpus() {
+        let mut rng = SmallRng::seed_from_u64(5);
+        let corpus = word_list("task_descriptions.txt");
+        let starts: Vec<&str> = corpus
+            .iter()
+            .filter_map(|line| line.split(' ').next())
+            .collect();
+        for _ in 0..20 {
+            let sentence = chains()[&Corpus::Tasks].sentence(&mut rng);
+            assert!(sentence.ends_with('.'));
+            assert!(starts.contains(&sentence.split(' ').next().unwrap()));
+        }
+    }
+}
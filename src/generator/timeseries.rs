@@ -0,0 +1,272 @@
+use std::f64::consts::TAU;
+
+use chrono::{Datelike, Duration, NaiveDateTime, Timelike, Weekday};
+use rand::rngs::SmallRng;
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::{payment_event::PaymentEvent, task::Task, task_status_event::TaskStatusEvent};
+
+const BURST_FACTOR: f64 = 4.0;
+const BURST_HALF_WIDTH_SECS: i64 = 3 * 60 * 60;
+const MAX_ATTEMPTS_PER_EVENT: usize = 1000;
+const MAX_BURSTS: usize = 1000;
+
+/// Shape of the event rate over the generated interval.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, ToSchema)]
+pub struct Seasonality {
+    /// Amplitude of the daily cycle (0.0 - 1.0), peaking in the afternoon.
+    #[serde(default)]
+    pub daily: f64,
+    /// How much quieter weekends are than weekdays (0.0 - 1.0).
+    #[serde(default)]
+    pub weekly: f64,
+    /// Relative change of the rate between start and end, e.g. `0.5` for +50%.
+    #[serde(default)]
+    pub trend: f64,
+    /// Number of short bursts of activity scattered over the interval.
+    #[serde(default)]
+    pub bursts: usize,
+}
+
+impl Seasonality {
+    /// Every problem with the shape.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        for (name, value) in [
+            ("daily", self.daily),
+            ("weekly", self.weekly),
+            ("trend", self.trend),
+        ] {
+            if !value.is_finite() {
+                errors.push(format!("shape.{} must be a finite number", name));
+            }
+        }
+        if self.bursts > MAX_BURSTS {
+            errors.push(format!("shape.bursts must be at most {}", MAX_BURSTS));
+        }
+        errors
+    }
+}
+
+struct Intensity {
+    start: NaiveDateTime,
+    span_secs: i64,
+    daily: f64,
+    weekly: f64,
+    trend: f64,
+    bursts: Vec<NaiveDateTime>,
+}
+
+impl Intensity {
+    fn new(
+        shape: &Seasonality,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        rng: &mut SmallRng,
+    ) -> Result<Self, String> {
+        let span_secs = (end - start).num_seconds();
+        if span_secs < 1 {
+            return Err("The interval must end at least a second after its start".to_string());
+        }
+        let bursts = (0..shape.bursts)
+            .map(|_| start + Duration::seconds(rng.gen_range(0..span_secs)))
+            .collect();
+
+        Ok(Intensity {
+            start,
+            span_secs,
+            daily: shape.daily.clamp(0.0, 1.0),
+            weekly: shape.weekly.clamp(0.0, 1.0),
+            trend: shape.trend.max(-0.95),
+            bursts,
+        })
+    }
+
+    fn rate(&self, at: NaiveDateTime) -> f64 {
+        let progress = (at - self.start).num_seconds() as f64 / self.span_secs as f64;
+        let mut rate = 1.0 + self.trend * progress;
+
+        let hour = at.hour() as f64 + at.minute() as f64 / 60.0;
+        rate *= 1.0 + self.daily * ((hour - 14.0) / 24.0 * TAU).cos();
+
+        if matches!(at.weekday(), Weekday::Sat | Weekday::Sun) {
+            rate *= 1.0 - self.weekly;
+        }
+
+        let in_burst = self
+            .bursts
+            .iter()
+            .any(|burst| (at - *burst).num_seconds().abs() <= BURST_HALF_WIDTH_SECS);
+        if in_burst {
+            rate *= BURST_FACTOR;
+        }
+        rate
+    }
+
+    fn max_rate(&self) -> f64 {
+        let burst = if self.bursts.is_empty() {
+            1.0
+        } else {
+            BURST_FACTOR
+        };
+        (1.0 + self.trend.max(0.0)) * (1.0 + self.daily) * burst
+    }
+
+    /// Draws `count` timestamps by thinning a uniform process with the rate function.
+    fn sample(&self, count: usize, rng: &mut SmallRng) -> Vec<NaiveDateTime> {
+        let max_rate = self.max_rate();
+        let mut timestamps = Vec::with_capacity(count);
+        let mut attempts = 0;
+
+        while timestamps.len() < count && attempts < count * MAX_ATTEMPTS_PER_EVENT {
+            attempts += 1;
+            let at = self.start + Duration::seconds(rng.gen_range(0..self.span_secs));
+            if rng.gen::<f64>() * max_rate <= self.rate(at) {
+                timestamps.push(at);
+            }
+        }
+
+        timestamps.sort();
+        timestamps
+    }
+}
+
+pub fn payment_events(
+    shape: &Seasonality,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    count: usize,
+) -> Result<Vec<PaymentEvent>, String> {
+    let mut rng = SmallRng::from_entropy();
+    let intensity = Intensity::new(shape, start, end, &mut rng)?;
+
+    let payment_methods = [
+        "Credit Card",
+        "Bank Transfer",
+        "PayPal",
+        "Cash",
+        "Cryptocurrency",
+    ];
+
+    let events = intensity
+        .sample(count, &mut rng)
+        .into_iter()
+        .map(|occurred_at| {
+            // Most payments are small, with a long tail of large ones.
+            let amount = 10.0 + 9_990.0 * rng.gen::<f32>().powi(3);
+            PaymentEvent {
+                amount: (amount * 100.0).round() / 100.0,
+                method: payment_methods.choose(&mut rng).unwrap().to_string(),
+                occurred_at,
+            }
+        })
+        .collect();
+
+    Ok(events)
+}
+
+/// Generates the status history of `count` tasks created within the interval.
+/// Transitions that would happen after `end` are left out, so recent tasks stay open.
+/// The tasks exist only in their events: refs look like `TASK-1f3a9c02-000001`, numbered
+/// within a random batch id so the refs of separate calls don't collide.
+pub fn task_status_events(
+    shape: &Seasonality,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    count: usize,
+) -> Result<Vec<TaskStatusEvent>, String> {
+    let mut rng = SmallRng::from_entropy();
+    let intensity = Intensity::new(shape, start, end, &mut rng)?;
+    let tasks = Task::generate_batch(count, &mut rng);
+    let batch = rng.gen::<u32>();
+
+    let mut events = Vec::new();
+    for (index, (created_at, task)) in intensity
+        .sample(count, &mut rng)
+        .into_iter()
+        .zip(tasks)
+        .enumerate()
+    {
+        let task_ref = format!("TASK-{:08x}-{:06}", batch, index + 1);
+        let mut occurred_at = created_at;
+
+        for (status, mean_hours) in [
+            ("Not Started", 0.0),
+            ("In Progress", 36.0),
+            ("Completed", 96.0),
+        ] {
+            occurred_at += Duration::minutes((exponential(&mut rng, mean_hours) * 60.0) as i64);
+            if occurred_at > end {
+                break;
+            }
+            events.push(TaskStatusEvent {
+                task_ref: task_ref.clone(),
+                task_name: task.name.clone(),
+                status: status.to_string(),
+                occurred_at,
+            });
+        }
+    }
+
+    events.sort_by_key(|event| event.occurred_at);
+    Ok(events)
+}
+
+fn exponential(rng: &mut SmallRng, mean: f64) -> f64 {
+    -mean * (1.0 - rng.gen::<f64>()).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 1, day)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn intensity_follows_the_shape() {
+        let mut rng = SmallRng::seed_from_u64(5);
+        let shape = Seasonality {
+            daily: 0.9,
+            weekly: 1.0,
+            trend: 2.0,
+            bursts: 0,
+        };
+        // 2024-01-01 is a Monday; the interval covers four full weeks.
+        let intensity = Intensity::new(&shape, at(1, 0), at(29, 0), &mut rng).unwrap();
+        assert!(intensity.rate(at(2, 14)) > 5.0 * intensity.rate(at(2, 2)));
+        assert!(intensity.rate(at(22, 14)) > intensity.rate(at(1, 14)));
+
+        let timestamps = intensity.sample(5000, &mut rng);
+        assert_eq!(timestamps.len(), 5000);
+        assert!(timestamps.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(timestamps
+            .iter()
+            .all(|timestamp| !matches!(timestamp.weekday(), Weekday::Sat | Weekday::Sun)));
+        let late = timestamps
+            .iter()
+            .filter(|timestamp| **timestamp >= at(15, 0));
+        assert!(late.count() > 2750);
+
+        let short = at(1, 0) + Duration::milliseconds(500);
+        assert!(Intensity::new(&shape, at(1, 0), short, &mut rng).is_err());
+    }
+
+    #[test]
+    fn task_refs_differ_between_calls() {
+        let shape = Seasonality::default();
+        let first = task_status_events(&shape, at(1, 0), at(8, 0), 20).unwrap();
+        let second = task_status_events(&shape, at(1, 0), at(8, 0), 20).unwrap();
+        assert!(first.iter().all(|event| event.task_ref.len() == 20));
+        assert!(first
+            .iter()
+            .all(|event| second.iter().all(|other| other.task_ref != event.task_ref)));
+    }
+}
@@ -1,58 +1,131 @@
+mod auth;
+mod config;
 mod db;
+mod generator;
 mod models;
+mod openapi;
 mod utils;
 
-use db::database_handler::{clear_staff, generate_data, get_data};
-use db::mongodb::connect_mongodb;
-use db::mysql::connect_mysql;
+use auth::{authorize, Auth};
+use config::Config;
+use db::backends::Backends;
+use db::database_handler::{
+    cancel_stream, clear_staff, export_geojson, generate_data, generate_documents,
+    generate_org_chart, generate_relations, generate_time_series, get_connection, get_data,
+    get_run, get_stream, health, list_audit, list_connections, list_runs, list_streams,
+    mutate_data, profile_table, register_connection, remove_connection, replay_run, rollback_run,
+    start_stream, test_connection, test_connection_settings, validate_data,
+};
+use generator::stream::StreamJobs;
+use openapi::{openapi_json, ApiDoc};
 
-use axum::{extract::Extension, routing::post, Router};
+use axum::{
+    extract::Extension,
+    middleware,
+    routing::{get, post},
+    Router,
+};
 use http::header::HeaderValue;
+use std::sync::Arc;
 use tower_http::cors::{Any, CorsLayer};
+use utoipa::OpenApi;
+use utoipa_scalar::{Scalar, Servable};
+
+/// Every route of the API, with access control but without shared state.
+fn router(auth: Auth) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/openapi.json", get(openapi_json))
+        .merge(Scalar::with_url("/docs", ApiDoc::openapi()))
+        .route("/connections", get(list_connections))
+        .route("/connections/test", post(test_connection_settings))
+        .route(
+            "/connections/{name}",
+            get(get_connection)
+                .put(register_connection)
+                .delete(remove_connection),
+        )
+        .route("/connections/{name}/test", post(test_connection))
+        .route("/generate", post(generate_data))
+        .route("/generate/org-chart", post(generate_org_chart))
+        .route("/generate/documents", post(generate_documents))
+        .route("/generate/relations", post(generate_relations))
+        .route("/generate/timeseries", post(generate_time_series))
+        .route("/clear", post(clear_staff))
+        .route("/data", post(get_data))
+        .route("/validate", post(validate_data))
+        .route("/data/geojson", post(export_geojson))
+        .route("/mutate", post(mutate_data))
+        .route("/profile", post(profile_table))
+        .route("/runs", get(list_runs))
+        .route("/runs/{run_id}", get(get_run))
+        .route("/runs/{run_id}/replay", post(replay_run))
+        .route("/runs/{run_id}/rollback", post(rollback_run))
+        .route("/stream", post(start_stream).get(list_streams))
+        .route("/stream/{id}", get(get_stream).delete(cancel_stream))
+        .route("/audit", get(list_audit))
+        .route_layer(middleware::from_fn_with_state(auth, authorize))
+}
 
 #[tokio::main]
 async fn main() {
-    if let Err(e) = dotenvy::dotenv() {
-        eprintln!("Failed to load .env file: {:?}", e);
-        return;
-    }
-    let mysql_url = dotenvy::var("MYSQL_URL").expect("MYSQL_URL must be set");
-    let mongodb_uri = dotenvy::var("MONGODB_URI").expect("MONGODB_URI must be set");
-
-    println!("🔍 Connecting to MySQL...");
-    let pool = match connect_mysql(&mysql_url).await {
-        Ok(pool) => pool,
-        Err(e) => {
-            eprintln!("❌ Failed to connect to MySQL: {:?}", e);
+    let config = match Config::load() {
+        Ok(config) => Arc::new(config),
+        Err(errors) => {
+            eprintln!("❌ Invalid configuration:");
+            for error in errors {
+                eprintln!("   - {}", error);
+            }
             return;
         }
     };
-
-    println!("🔍 Connecting to MongoDB...");
-    let mongodb_client = match connect_mongodb(&mongodb_uri).await {
-        Ok(client) => client,
-        Err(e) => {
-            eprintln!("❌ Failed to connect to MongoDB: {:?}", e);
+    if let Err(errors) = utils::utils::load_dictionary(config.data_pack_dir.as_deref()) {
+        eprintln!("❌ Invalid word lists:");
+        for error in errors {
+            eprintln!("   - {}", error);
+        }
+        return;
+    }
+    let backends = match Backends::new(&config) {
+        Ok(backends) => backends,
+        Err(errors) => {
+            eprintln!("❌ Invalid connections:");
+            for error in errors {
+                eprintln!("   - {}", error);
+            }
             return;
         }
     };
+    backends.spawn_health_checks();
+
+    let auth = Auth::new(&config);
+    if !auth.enabled() {
+        println!("⚠️ No API keys configured, every endpoint is open to anyone who can reach it");
+    }
 
     let cors = CorsLayer::new()
-        .allow_origin("http://localhost:5173".parse::<HeaderValue>().unwrap())
+        .allow_origin(
+            config
+                .cors_origins
+                .iter()
+                .map(|origin| origin.parse::<HeaderValue>().unwrap())
+                .collect::<Vec<_>>(),
+        )
         .allow_methods(Any)
         .allow_headers(Any);
 
-    let app = Router::new()
-        .route("/generate", post(generate_data))
-        .route("/clear", post(clear_staff))
-        .route("/data", post(get_data))
-        .layer(Extension(pool.clone()))
-        .layer(Extension(mongodb_client.clone()))
+    let app = router(auth.clone())
+        .layer(Extension(backends.clone()))
+        .layer(Extension(config.clone()))
+        .layer(Extension(auth))
+        .layer(Extension(StreamJobs::default()))
         .layer(cors);
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
+    let listener = tokio::net::TcpListener::bind(&config.bind_address)
+        .await
+        .unwrap();
+    println!("🚀 Listening on {}", config.bind_address);
     axum::serve(listener, app).await.unwrap();
 
-    pool.disconnect().await.unwrap();
-    mongodb_client.shutdown().await;
+    backends.shutdown().await;
 }
@@ -1,42 +1,51 @@
 use rand::rngs::SmallRng;
-use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand::seq::SliceRandom;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
+use crate::generator::geo::{cities, COUNTRY};
+use crate::generator::pattern::Pattern;
 use crate::utils;
-use utils::utils::load_from_file;
+use utils::utils::word_list;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Building number with an optional letter and flat, e.g. `12`, `7A` or `140/3`.
+pub const STREET_NUMBER: &str = r"[1-9][0-9]{0,2}(?:[A-C]|/[1-9][0-9]?)?";
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Address {
     pub city: String,
     pub street: String,
     pub street_number: String,
-    pub postal_code: String,
+    pub postal_code: Option<String>,
+    pub voivodeship: String,
+    pub country: String,
+    pub latitude: f64,
+    pub longitude: f64,
 }
 
 impl Address {
-    pub fn generate_batch(count: usize) -> Vec<Self> {
-        let mut rng = SmallRng::from_entropy();
-
-        let cities: Vec<String> = load_from_file("src/utils/cities.txt");
-        let streets: Vec<String> = load_from_file("src/utils/streets.txt");
+    /// Addresses in gazetteer cities, with a point inside the city and one of its postal codes.
+    pub fn generate_batch(count: usize, rng: &mut SmallRng) -> Vec<Self> {
+        let streets = word_list("streets.txt");
+        let street_numbers = Pattern::parse(STREET_NUMBER).expect("Invalid street number pattern");
 
         let mut addresses = Vec::new();
 
         for _ in 0..count {
-            if let (Some(city), Some(street)) = (cities.choose(&mut rng), streets.choose(&mut rng))
-            {
-                let street_number = rng.gen_range(1..=200).to_string();
-                let postal_code = format!(
-                    "{:02}-{:03}",
-                    rng.gen_range(10..=99),
-                    rng.gen_range(100..=999)
-                );
+            if let (Some(city), Some(street)) = (cities().choose(rng), streets.choose(rng)) {
+                let street_number = street_numbers.generate(rng);
+                let postal_code = city.postal_code(rng);
+                let (latitude, longitude) = city.point(rng);
 
                 addresses.push(Address {
-                    city: city.clone(),
+                    city: city.name.clone(),
                     street: street.clone(),
                     street_number,
-                    postal_code,
+                    postal_code: Some(postal_code),
+                    voivodeship: city.voivodeship.clone(),
+                    country: COUNTRY.to_string(),
+                    latitude,
+                    longitude,
                 });
             }
         }
@@ -1,12 +1,15 @@
 use rand::rngs::SmallRng;
-use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand::seq::SliceRandom;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
+use utoipa::ToSchema;
 
-use crate::utils;
-use utils::utils::load_from_file;
+use super::person::{email_address, full_name, phone_number_pattern};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Mail providers of client email addresses.
+pub const EMAIL_DOMAINS: [&str; 4] = ["gmail.com", "yahoo.com", "outlook.com", "example.com"];
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Client {
     pub first_name: String,
     pub last_name: String,
@@ -15,39 +18,17 @@ pub struct Client {
 }
 
 impl Client {
-    pub fn generate_batch(count: usize) -> Vec<Self> {
-        let mut rng = SmallRng::from_entropy();
-
-        let last_names: Vec<String> = load_from_file("src/utils/last_names.txt");
-        let first_names: Vec<String> = load_from_file("src/utils/names.txt");
-
-        let email_domains = vec!["gmail.com", "yahoo.com", "outlook.com", "example.com"];
+    pub fn generate_batch(count: usize, rng: &mut SmallRng) -> Vec<Self> {
+        let phone_numbers = phone_number_pattern();
         let mut used_phones = HashSet::new();
         let mut clients = Vec::new();
 
         for _ in 0..count {
-            let first_name = first_names
-                .choose(&mut rng)
-                .cloned()
-                .unwrap_or_else(|| "John".to_string());
-
-            let last_name = last_names
-                .choose(&mut rng)
-                .cloned()
-                .unwrap_or_else(|| "Doe".to_string());
-
-            let email = format!(
-                "{}.{}@{}",
-                first_name.to_lowercase(),
-                last_name.to_lowercase(),
-                email_domains.choose(&mut rng).unwrap()
-            );
+            let (first_name, last_name) = full_name(rng);
+            let email = email_address(&first_name, &last_name, EMAIL_DOMAINS.choose(rng).unwrap());
 
             let phone_number = loop {
-                let new_phone = format!(
-                    "+48 {:08}",
-                    rng.gen_range(6_000_000_000_i64..9_000_000_000_i64)
-                );
+                let new_phone = phone_numbers.generate(rng);
                 if used_phones.insert(new_phone.clone()) {
                     break new_phone;
                 }
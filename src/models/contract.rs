@@ -1,25 +1,25 @@
 use chrono::{Datelike, Duration, NaiveDate, Utc};
 use rand::rngs::SmallRng;
-use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand::{seq::SliceRandom, Rng};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Contract {
     pub type_of_contract: String,
     pub start_date: NaiveDate,
-    pub end_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
     pub salary: i32,
 }
 
-impl Contract {
-    pub fn generate_batch(count: usize) -> Vec<Self> {
-        let mut rng = SmallRng::from_entropy();
-        let type_of_contract_list = ["B2B", "UoP", "Mandate Contract", "Contract of Employment"];
+pub const CONTRACT_TYPES: [&str; 4] = ["B2B", "UoP", "Mandate Contract", "Contract of Employment"];
 
+impl Contract {
+    pub fn generate_batch(count: usize, rng: &mut SmallRng) -> Vec<Self> {
         let mut contracts = Vec::new();
 
         for _ in 0..count {
-            let type_of_contract = type_of_contract_list.choose(&mut rng).unwrap().to_string();
+            let type_of_contract = CONTRACT_TYPES.choose(rng).unwrap().to_string();
 
             let start_date = NaiveDate::from_ymd_opt(
                 rng.gen_range(Utc::now().year() - 5..=Utc::now().year()),
@@ -31,12 +31,12 @@ impl Contract {
             let contract_duration = Duration::days(rng.gen_range(180..=1825));
             let end_date = start_date + contract_duration;
 
-            let salary = rng.gen_range(3000..=25000);
+            let salary = rng.gen_range(salary_range(&type_of_contract));
 
             contracts.push(Contract {
                 type_of_contract,
                 start_date,
-                end_date,
+                end_date: Some(end_date),
                 salary,
             });
         }
@@ -44,3 +44,13 @@ impl Contract {
         contracts
     }
 }
+
+/// Monthly salaries plausible for a type of contract; employment contracts start at the
+/// minimum wage.
+pub fn salary_range(type_of_contract: &str) -> std::ops::RangeInclusive<i32> {
+    match type_of_contract {
+        "B2B" => 8000..=25000,
+        "Mandate Contract" => 3000..=9000,
+        _ => 4666..=18000,
+    }
+}
@@ -1,60 +1,55 @@
-use chrono::NaiveDate;
+use chrono::{NaiveDate, Utc};
 use rand::rngs::SmallRng;
-use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand::{seq::SliceRandom, Rng};
 use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+use utoipa::ToSchema;
 
-use crate::utils::utils::load_from_file;
+use super::person::{email_address, full_name, phone_number_pattern};
 use std::collections::HashSet;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Email domain of every employee.
+pub const COMPANY_DOMAIN: &str = "company.com";
+
+/// Positions of generated employees, which are also the departments of an org chart.
+pub const DEPARTMENTS: [&str; 6] = [
+    "HR",
+    "IT",
+    "Finance",
+    "Sales",
+    "Administration",
+    "Public Relations",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Employee {
     pub first_name: String,
     pub last_name: String,
     pub email: String,
     pub phone_number: String,
     pub position: String,
-    pub contract_date: NaiveDate,
+    pub contract_date: Option<NaiveDate>,
+    /// Set for employees of a generated org chart.
+    #[serde(default)]
+    pub department: Option<String>,
+    #[serde(default)]
+    pub team: Option<String>,
 }
 
 impl Employee {
-    pub fn generate_batch(count: usize) -> Vec<Self> {
-        let mut rng = SmallRng::from_entropy();
-
-        let first_names: Vec<String> = load_from_file("src/utils/names.txt");
-        let last_names: Vec<String> = load_from_file("src/utils/last_names.txt");
-        let positions = [
-            "HR",
-            "IT",
-            "Finance",
-            "Sales",
-            "Administration",
-            "Public Relations",
-        ];
-        let email_domains = ["company.com", "corporate.com", "business.com"];
-
+    pub fn generate_batch(count: usize, rng: &mut SmallRng) -> Vec<Self> {
+        let phone_numbers = phone_number_pattern();
         let mut used_phones = HashSet::new();
         let mut employees = Vec::new();
 
         for _ in 0..count {
-            let first_name = first_names
-                .choose(&mut rng)
-                .cloned()
-                .unwrap_or_else(|| "John".to_string());
-            let last_name = last_names
-                .choose(&mut rng)
-                .cloned()
-                .unwrap_or_else(|| "Doe".to_string());
+            let (first_name, last_name) = full_name(rng);
 
-            let position = positions.choose(&mut rng).unwrap().to_string();
-            let email = format!(
-                "{}.{}@{}",
-                first_name.to_lowercase(),
-                last_name.to_lowercase(),
-                email_domains.choose(&mut rng).unwrap()
-            );
+            let position = DEPARTMENTS.choose(rng).unwrap().to_string();
+            let email = email_address(&first_name, &last_name, COMPANY_DOMAIN);
 
             let phone_number = loop {
-                let new_phone = format!("+48 {}", rng.gen_range(600_000_000..999_999_999));
+                let new_phone = phone_numbers.generate(rng);
                 if used_phones.insert(new_phone.clone()) {
                     break new_phone;
                 }
@@ -73,10 +68,36 @@ impl Employee {
                 email,
                 phone_number,
                 position,
-                contract_date,
+                contract_date: Some(contract_date),
+                department: None,
+                team: None,
             });
         }
 
         employees
     }
 }
+
+/// Full years between the contract date and today.
+pub fn years_of_service(contract_date: NaiveDate) -> i32 {
+    Utc::now()
+        .date_naive()
+        .years_since(contract_date)
+        .map_or(0, |years| years as i32)
+}
+
+/// Monthly salaries plausible for a position after `years` of service; each year adds 4%
+/// to the starting band, up to 15 years.
+pub fn salary_band(position: &str, years: i32) -> RangeInclusive<i32> {
+    let (min, max) = match position {
+        "Management" => (9000, 13000),
+        "IT" => (7000, 11000),
+        "Finance" => (6000, 9500),
+        "Sales" => (5000, 8500),
+        "Public Relations" => (5500, 8500),
+        "HR" => (5000, 7500),
+        _ => (4666, 6500),
+    };
+    let percent = 100 + 4 * years.clamp(0, 15);
+    (min * percent / 100)..=(max * percent / 100)
+}
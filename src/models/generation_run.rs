@@ -0,0 +1,25 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+/// A single `/generate`, `/generate/org-chart` or `/generate/documents` call or `/stream` job,
+/// recorded in the `generation_runs` table or collection. Every row it inserted carries its
+/// `run_id`.
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct GenerationRun {
+    pub run_id: String,
+    pub backend: String,
+    /// The request that produced the run, with the seed filled in; org chart, document and
+    /// stream requests are wrapped in an `org_chart`, `documents` or `stream` object.
+    pub params: Value,
+    pub seed: i64,
+    /// Number of generated rows per table, or documents per collection.
+    pub counts: BTreeMap<String, usize>,
+    pub started_at: NaiveDateTime,
+    pub finished_at: Option<NaiveDateTime>,
+    pub rolled_back_at: Option<NaiveDateTime>,
+    pub replay_of: Option<String>,
+}
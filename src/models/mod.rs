@@ -2,7 +2,11 @@ pub mod address;
 pub mod client;
 pub mod contract;
 pub mod employee;
+pub mod generation_run;
 pub mod payment;
+pub mod payment_event;
+pub mod person;
 pub mod project;
 pub mod task;
+pub mod task_status_event;
 pub mod technology;
@@ -1,9 +1,10 @@
 use chrono::{Datelike, NaiveDate, Utc};
 use rand::rngs::SmallRng;
-use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand::{seq::SliceRandom, Rng};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Payment {
     pub amount: f32,
     pub payment_due_date: NaiveDate,
@@ -11,10 +12,8 @@ pub struct Payment {
 }
 
 impl Payment {
-    pub fn generate_batch(count: usize) -> Vec<Self> {
-        let mut rng = SmallRng::from_entropy();
-
-        let payment_methods = vec![
+    pub fn generate_batch(count: usize, rng: &mut SmallRng) -> Vec<Self> {
+        let payment_methods = [
             "Credit Card",
             "Bank Transfer",
             "PayPal",
@@ -35,7 +34,7 @@ impl Payment {
             )
             .unwrap_or_else(|| NaiveDate::from_ymd_opt(2022, 1, 1).unwrap());
 
-            let method = payment_methods.choose(&mut rng).unwrap().to_string();
+            let method = payment_methods.choose(rng).unwrap().to_string();
 
             payments.push(Payment {
                 amount,
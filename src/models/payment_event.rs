@@ -0,0 +1,9 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PaymentEvent {
+    pub amount: f32,
+    pub method: String,
+    pub occurred_at: NaiveDateTime,
+}
@@ -0,0 +1,86 @@
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+
+use crate::generator::expression::slugify;
+use crate::generator::pattern::Pattern;
+use crate::utils::utils::word_list;
+
+/// Polish mobile numbers.
+pub const PHONE_NUMBER: &str = r"\+48 [6-9][0-9]{8}";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gender {
+    Female,
+    Male,
+}
+
+/// Male first names ending in "a", which otherwise marks a female name in Polish.
+const MALE_NAMES_ENDING_IN_A: [&str; 4] = ["Kuba", "Barnaba", "Bonawentura", "Kosma"];
+
+/// Surname endings inflected by gender, as (masculine, feminine) pairs.
+const GENDERED_ENDINGS: [(&str, &str); 3] = [("ski", "ska"), ("cki", "cka"), ("dzki", "dzka")];
+
+pub fn gender_of(first_name: &str) -> Gender {
+    if first_name.ends_with('a') && !MALE_NAMES_ENDING_IN_A.contains(&first_name) {
+        Gender::Female
+    } else {
+        Gender::Male
+    }
+}
+
+/// Inflects adjectival surnames such as Kowalski/Kowalska to the given gender; others,
+/// such as Nowak, are the same for both.
+pub fn inflect_last_name(last_name: &str, gender: Gender) -> String {
+    for (masculine, feminine) in GENDERED_ENDINGS {
+        let (from, to) = match gender {
+            Gender::Female => (masculine, feminine),
+            Gender::Male => (feminine, masculine),
+        };
+        if let Some(stem) = last_name.strip_suffix(from) {
+            return format!("{}{}", stem, to);
+        }
+    }
+    last_name.to_string()
+}
+
+/// A first and last name that agree in gender, drawn from the name word lists.
+pub fn full_name(rng: &mut SmallRng) -> (String, String) {
+    let first_name = word_list("names.txt")
+        .choose(rng)
+        .cloned()
+        .unwrap_or_else(|| "John".to_string());
+    let last_name = word_list("last_names.txt")
+        .choose(rng)
+        .map(String::as_str)
+        .unwrap_or("Doe");
+    let last_name = inflect_last_name(last_name, gender_of(&first_name));
+    (first_name, last_name)
+}
+
+pub fn phone_number_pattern() -> Pattern {
+    Pattern::parse(PHONE_NUMBER).expect("Invalid phone number pattern")
+}
+
+/// `first.last@domain` without diacritics, e.g. `lukasz.wisniewski@company.com`.
+pub fn email_address(first_name: &str, last_name: &str, domain: &str) -> String {
+    format!("{}.{}@{}", slugify(first_name), slugify(last_name), domain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn surnames_follow_the_first_name() {
+        assert_eq!(gender_of("Anna"), Gender::Female);
+        assert_eq!(gender_of("Kuba"), Gender::Male);
+        assert_eq!(inflect_last_name("Kowalski", Gender::Female), "Kowalska");
+        assert_eq!(inflect_last_name("Zawadzki", Gender::Female), "Zawadzka");
+        assert_eq!(inflect_last_name("Kowalska", Gender::Male), "Kowalski");
+        assert_eq!(inflect_last_name("Nowak", Gender::Female), "Nowak");
+        assert_eq!(
+            email_address("Łukasz", "Wiśniewski", "company.com"),
+            "lukasz.wisniewski@company.com"
+        );
+    }
+}
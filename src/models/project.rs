@@ -1,62 +1,43 @@
-use chrono::NaiveDate;
+use chrono::{Duration, NaiveDate, Utc};
 use rand::rngs::SmallRng;
-use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand::{seq::SliceRandom, Rng};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+use crate::generator::text::{Bounds, Corpus, Grammar, TextGenerator};
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Project {
     pub name: String,
     pub description: String,
     pub start_date: NaiveDate,
-    pub end_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
     pub status: String,
 }
 
 impl Project {
-    pub fn generate_batch(count: usize) -> Vec<Self> {
-        let mut rng = SmallRng::from_entropy();
-
-        let names = vec!["Project A", "Project B", "Project C", "Project D"];
-        let descriptions = vec![
-            "A project focused on AI research.",
-            "A new web development initiative.",
-            "A marketing campaign for a new product.",
-            "A system upgrade for internal software.",
-        ];
-        let statuses = vec!["Not Started", "In Progress", "Completed"];
+    pub fn generate_batch(count: usize, rng: &mut SmallRng) -> Vec<Self> {
+        let names = TextGenerator::Grammar(Grammar::Codename);
+        let descriptions = TextGenerator::Markov {
+            corpus: Corpus::Projects,
+            sentences: Bounds { min: 1, max: 2 },
+        };
+        let statuses = ["Not Started", "In Progress", "Completed"];
 
         let mut projects = Vec::new();
 
         for _ in 0..count {
-            let name = names
-                .choose(&mut rng)
-                .unwrap_or(&"Default Project")
-                .to_string();
-            let description = descriptions
-                .choose(&mut rng)
-                .unwrap_or(&"Default description")
-                .to_string();
-
-            let start_year = rng.gen_range(2022..2025);
-            let end_year = rng.gen_range(start_year..2026);
-
-            let start_date =
-                NaiveDate::from_ymd_opt(start_year, rng.gen_range(1..=12), rng.gen_range(1..=28))
-                    .expect("Invalid start date");
-            let end_date =
-                NaiveDate::from_ymd_opt(end_year, rng.gen_range(1..=12), rng.gen_range(1..=28))
-                    .expect("Invalid end date");
+            let name = names.generate(rng, Some(100));
+            let description = descriptions.generate(rng, Some(250));
 
-            let status = statuses
-                .choose(&mut rng)
-                .unwrap_or(&"Not Started")
-                .to_string();
+            let status = statuses.choose(rng).unwrap_or(&"Not Started").to_string();
+            let (start_date, end_date) = schedule(&status, rng);
 
             projects.push(Project {
                 name,
                 description,
                 start_date,
-                end_date,
+                end_date: Some(end_date),
                 status,
             });
         }
@@ -64,3 +45,22 @@ impl Project {
         projects
     }
 }
+
+/// Start and end dates that agree with the status: work never ends before it starts,
+/// and completed work ended no later than today.
+pub fn schedule(status: &str, rng: &mut SmallRng) -> (NaiveDate, NaiveDate) {
+    let today = Utc::now().date_naive();
+    let start_date = NaiveDate::from_ymd_opt(
+        rng.gen_range(2022..2025),
+        rng.gen_range(1..=12),
+        rng.gen_range(1..=28),
+    )
+    .expect("Invalid start date")
+    .min(today);
+    let end_date = start_date + Duration::days(rng.gen_range(14..=720));
+    if status == "Completed" {
+        (start_date, end_date.min(today))
+    } else {
+        (start_date, end_date)
+    }
+}
@@ -0,0 +1,10 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TaskStatusEvent {
+    pub task_ref: String,
+    pub task_name: String,
+    pub status: String,
+    pub occurred_at: NaiveDateTime,
+}
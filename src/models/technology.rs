@@ -1,48 +1,28 @@
 use rand::rngs::SmallRng;
-use rand::{seq::SliceRandom, SeedableRng};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+use crate::generator::text::{Bounds, Corpus, Grammar, TextGenerator};
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Technology {
     pub name: String,
     pub description: String,
 }
 
 impl Technology {
-    pub fn generate_batch(count: usize) -> Vec<Self> {
-        let mut rng = SmallRng::from_entropy();
-
-        let names = vec![
-            "Rust Programming",
-            "Machine Learning",
-            "Blockchain",
-            "Quantum Computing",
-            "Artificial Intelligence",
-            "Cloud Computing",
-            "Internet of Things",
-        ];
-
-        let descriptions = vec![
-            "A systems programming language focused on performance and safety.",
-            "A subset of artificial intelligence that focuses on algorithms and models that allow machines to learn from data.",
-            "A decentralized technology for secure and transparent transactions.",
-            "A new field of computing that uses quantum mechanics to process information.",
-            "A field of study in computer science that involves creating intelligent machines capable of performing tasks that usually require human intelligence.",
-            "A model of computing where services and resources are provided over the internet.",
-            "A network of physical devices, vehicles, buildings, and other objects embedded with sensors and software for the purpose of connecting and exchanging data.",
-        ];
+    pub fn generate_batch(count: usize, rng: &mut SmallRng) -> Vec<Self> {
+        let names = TextGenerator::Grammar(Grammar::Technology);
+        let descriptions = TextGenerator::Markov {
+            corpus: Corpus::Technology,
+            sentences: Bounds { min: 1, max: 2 },
+        };
 
         let mut technologies = Vec::new();
 
         for _ in 0..count {
-            let name = names
-                .choose(&mut rng)
-                .unwrap_or(&"Default Technology")
-                .to_string();
-            let description = descriptions
-                .choose(&mut rng)
-                .unwrap_or(&"Default description")
-                .to_string();
+            let name = names.generate(rng, Some(100));
+            let description = descriptions.generate(rng, Some(250));
 
             technologies.push(Technology { name, description });
         }
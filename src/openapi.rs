@@ -0,0 +1,242 @@
+use axum::{http::Method, Json};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, Http, HttpAuthScheme, SecurityScheme};
+use utoipa::openapi::{Components, OpenApi as OpenApiDocument, Response, SecurityRequirement};
+use utoipa::{Modify, OpenApi};
+
+use crate::auth::required_role;
+use crate::db::database_handler::*;
+use crate::generator::documents::Embedded;
+
+/// The API description served at `/openapi.json` and rendered at `/docs`.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "rust-db-data-generator",
+        description = "Generates fake company data into MySQL and MongoDB."
+    ),
+    paths(
+        health,
+        list_connections,
+        test_connection_settings,
+        get_connection,
+        register_connection,
+        remove_connection,
+        test_connection,
+        generate_data,
+        generate_time_series,
+        generate_org_chart,
+        generate_documents,
+        generate_relations,
+        clear_staff,
+        get_data,
+        export_geojson,
+        validate_data,
+        mutate_data,
+        profile_table,
+        list_runs,
+        get_run,
+        replay_run,
+        rollback_run,
+        start_stream,
+        list_streams,
+        get_stream,
+        cancel_stream,
+        list_audit,
+    ),
+    // Recursive, so not collected from the request bodies that refer to it.
+    components(schemas(Embedded)),
+    modifiers(&AccessControl)
+)]
+pub struct ApiDoc;
+
+/// Documents the role each operation needs, taken from the same table `authorize` enforces.
+struct AccessControl;
+
+impl Modify for AccessControl {
+    fn modify(&self, openapi: &mut OpenApiDocument) {
+        openapi
+            .components
+            .get_or_insert_with(Components::new)
+            .add_security_schemes_from_iter([
+                (
+                    "bearer",
+                    SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+                ),
+                (
+                    "api_key",
+                    SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-API-Key"))),
+                ),
+            ]);
+
+        for (path, item) in openapi.paths.paths.iter_mut() {
+            let operations = [
+                (Method::GET, &mut item.get),
+                (Method::PUT, &mut item.put),
+                (Method::POST, &mut item.post),
+                (Method::DELETE, &mut item.delete),
+            ];
+            for (method, operation) in operations {
+                let Some(operation) = operation else {
+                    continue;
+                };
+                let Some(role) = required_role(&method, path) else {
+                    continue;
+                };
+                operation.security = Some(vec![
+                    SecurityRequirement::new("bearer", Vec::<String>::new()),
+                    SecurityRequirement::new("api_key", Vec::<String>::new()),
+                ]);
+                let requirement = format!("Requires the `{}` role.", role.as_str());
+                operation.description = Some(match operation.description.take() {
+                    Some(description) => format!("{}\n\n{}", description, requirement),
+                    None => requirement,
+                });
+                let responses = &mut operation.responses.responses;
+                responses.insert(
+                    "401".to_string(),
+                    Response::new("Missing or invalid API key").into(),
+                );
+                responses.insert(
+                    "403".to_string(),
+                    Response::new(format!("The API key lacks the {} role", role.as_str())).into(),
+                );
+            }
+        }
+    }
+}
+
+pub async fn openapi_json() -> Json<OpenApiDocument> {
+    Json(ApiDoc::openapi())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Auth;
+    use crate::config::Config;
+    use crate::db::schema::TableName;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    const METHODS: [Method; 4] = [Method::GET, Method::PUT, Method::POST, Method::DELETE];
+
+    fn document() -> Value {
+        serde_json::to_value(ApiDoc::openapi()).unwrap()
+    }
+
+    /// Replaces path parameters with a placeholder value.
+    fn concrete(path: &str) -> String {
+        path.split('/')
+            .map(|part| if part.starts_with('{') { "1" } else { part })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    async fn status(method: Method, path: &str) -> StatusCode {
+        let request = Request::builder()
+            .method(method)
+            .uri(concrete(path))
+            .header("content-type", "application/json")
+            .body(Body::from("{}"))
+            .unwrap();
+        let router = crate::router(Auth::new(&Config::default()));
+        router.oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn documented_operations_match_the_router() {
+        let document = document();
+        let paths = document["paths"].as_object().unwrap();
+        assert!(!paths.is_empty());
+        for (path, item) in paths {
+            for method in METHODS {
+                let documented = item.get(method.as_str().to_lowercase()).is_some();
+                let status = status(method.clone(), path).await;
+                if documented {
+                    assert!(
+                        status != StatusCode::NOT_FOUND && status != StatusCode::METHOD_NOT_ALLOWED,
+                        "{} {} is documented but not routed ({})",
+                        method,
+                        path,
+                        status
+                    );
+                } else {
+                    assert!(
+                        status == StatusCode::METHOD_NOT_ALLOWED,
+                        "{} {} is routed but not documented ({})",
+                        method,
+                        path,
+                        status
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn every_schema_reference_resolves() {
+        fn collect<'a>(value: &'a Value, refs: &mut Vec<&'a str>) {
+            match value {
+                Value::Object(map) => {
+                    if let Some(Value::String(reference)) = map.get("$ref") {
+                        refs.push(reference);
+                    }
+                    map.values().for_each(|value| collect(value, refs));
+                }
+                Value::Array(items) => items.iter().for_each(|value| collect(value, refs)),
+                _ => {}
+            }
+        }
+
+        let document = document();
+        let mut refs = Vec::new();
+        collect(&document, &mut refs);
+        assert!(!refs.is_empty());
+        for reference in refs {
+            let name = reference
+                .strip_prefix("#/components/schemas/")
+                .unwrap_or_else(|| panic!("unexpected reference {}", reference));
+            assert!(
+                document["components"]["schemas"].get(name).is_some(),
+                "{} is referenced but not documented",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn table_names_are_listed() {
+        let document = document();
+        let documented = &document["components"]["schemas"]["TableName"]["enum"];
+        let expected: Vec<&str> = TableName::ALL.iter().map(TableName::as_str).collect();
+        assert_eq!(documented, &serde_json::json!(expected));
+    }
+
+    #[test]
+    fn table_rows_are_wrapped_in_their_type() {
+        let document = document();
+        let variants = document["components"]["schemas"]["TableType"]["oneOf"]
+            .as_array()
+            .unwrap();
+        for variant in ["Employee", "Client", "Payment"] {
+            assert!(
+                variants
+                    .iter()
+                    .any(|schema| schema["properties"].get(variant).is_some()),
+                "TableType does not document the {} variant",
+                variant
+            );
+        }
+    }
+
+    #[test]
+    fn protected_operations_document_their_role() {
+        let document = document();
+        let generate = &document["paths"]["/generate"]["post"];
+        assert!(generate["security"].is_array());
+        assert!(generate["responses"]["401"].is_object());
+        assert!(document["paths"]["/health"]["get"]["security"].is_null());
+    }
+}
@@ -32,7 +32,13 @@ CREATE TABLE `address` (
   `city` varchar(40) NOT NULL,
   `street` varchar(40) NOT NULL,
   `street_number` varchar(15) NOT NULL,
-  `postal_code` varchar(15) DEFAULT NULL
+  `postal_code` varchar(15) DEFAULT NULL,
+  `voivodeship` varchar(40) NOT NULL,
+  `country` varchar(56) NOT NULL,
+  `latitude` decimal(9,6) NOT NULL,
+  `longitude` decimal(9,6) NOT NULL,
+  `location` point GENERATED ALWAYS AS (ST_PointFromText(CONCAT('POINT(', `longitude`, ' ', `latitude`, ')'), 4326, 'axis-order=long-lat')) STORED NOT NULL SRID 4326,
+  `run_id` varchar(36) DEFAULT NULL
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8 COLLATE=utf8_polish_ci;
 
 
@@ -41,7 +47,8 @@ CREATE TABLE `client` (
   `first_name` varchar(35) NOT NULL,
   `last_name` varchar(100) NOT NULL,
   `email` varchar(100) NOT NULL,
-  `phone_number` varchar(15) NOT NULL
+  `phone_number` varchar(15) NOT NULL,
+  `run_id` varchar(36) DEFAULT NULL
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8 COLLATE=utf8_polish_ci;
 
 
@@ -50,7 +57,9 @@ CREATE TABLE `contract` (
   `type_of_contract` varchar(100) NOT NULL,
   `start_date` date NOT NULL,
   `end_date` date DEFAULT NULL,
-  `salary` int(11) NOT NULL
+  `salary` int(11) NOT NULL,
+  `employee_id` int(11) DEFAULT NULL,
+  `run_id` varchar(36) DEFAULT NULL
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8 COLLATE=utf8_polish_ci;
 
 -- --------------------------------------------------------
@@ -66,7 +75,11 @@ CREATE TABLE `employee` (
   `email` varchar(100) NOT NULL,
   `phone_number` varchar(15) NOT NULL,
   `position` varchar(35) NOT NULL,
-  `contract_date` date DEFAULT NULL
+  `contract_date` date DEFAULT NULL,
+  `department` varchar(35) DEFAULT NULL,
+  `team` varchar(60) DEFAULT NULL,
+  `manager_id` int(11) DEFAULT NULL,
+  `run_id` varchar(36) DEFAULT NULL
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8 COLLATE=utf8_polish_ci;
 
 
@@ -78,7 +91,8 @@ CREATE TABLE `payment` (
   `payment_id` int(11) NOT NULL,
   `amount` decimal(9,4) NOT NULL,
   `payment_due_date` date NOT NULL,
-  `method` varchar(30) NOT NULL
+  `method` varchar(30) NOT NULL,
+  `run_id` varchar(36) DEFAULT NULL
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8 COLLATE=utf8_polish_ci;
 
 
@@ -92,7 +106,8 @@ CREATE TABLE `project` (
   `description` varchar(250) NOT NULL,
   `start_date` date NOT NULL,
   `end_date` date DEFAULT NULL,
-  `status` varchar(15) NOT NULL
+  `status` varchar(15) NOT NULL,
+  `run_id` varchar(36) DEFAULT NULL
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8 COLLATE=utf8_polish_ci;
 
 -- --------------------------------------------------------
@@ -107,7 +122,8 @@ CREATE TABLE `task` (
   `description` varchar(250) NOT NULL,
   `start_date` date NOT NULL,
   `end_date` date DEFAULT NULL,
-  `status` varchar(15) NOT NULL
+  `status` varchar(15) NOT NULL,
+  `run_id` varchar(36) DEFAULT NULL
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8 COLLATE=utf8_polish_ci;
 -- --------------------------------------------------------
 
@@ -118,7 +134,87 @@ CREATE TABLE `task` (
 CREATE TABLE `technology` (
   `technology_id` int(11) NOT NULL,
   `name` varchar(100) NOT NULL,
-  `description` varchar(250) NOT NULL
+  `description` varchar(250) NOT NULL,
+  `run_id` varchar(36) DEFAULT NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8 COLLATE=utf8_polish_ci;
+
+-- --------------------------------------------------------
+
+--
+-- Struktura tabeli dla tabeli `generation_runs`
+--
+
+CREATE TABLE `generation_runs` (
+  `run_id` varchar(36) NOT NULL,
+  `backend` varchar(10) NOT NULL,
+  `params` text NOT NULL,
+  `seed` bigint(20) NOT NULL,
+  `counts` text NOT NULL,
+  `started_at` datetime NOT NULL,
+  `finished_at` datetime DEFAULT NULL,
+  `rolled_back_at` datetime DEFAULT NULL,
+  `replay_of` varchar(36) DEFAULT NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8 COLLATE=utf8_polish_ci;
+
+-- --------------------------------------------------------
+
+--
+-- Struktura tabeli dla tabeli `payment_event`
+--
+
+CREATE TABLE `payment_event` (
+  `payment_event_id` int(11) NOT NULL,
+  `amount` decimal(9,4) NOT NULL,
+  `method` varchar(30) NOT NULL,
+  `occurred_at` datetime NOT NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8 COLLATE=utf8_polish_ci;
+
+-- --------------------------------------------------------
+
+--
+-- Struktura tabeli dla tabeli `task_status_event`
+--
+
+CREATE TABLE `task_status_event` (
+  `task_status_event_id` int(11) NOT NULL,
+  `task_ref` varchar(20) NOT NULL,
+  `task_name` varchar(100) NOT NULL,
+  `status` varchar(15) NOT NULL,
+  `occurred_at` datetime NOT NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8 COLLATE=utf8_polish_ci;
+
+-- --------------------------------------------------------
+
+--
+-- Struktura tabeli dla tabeli `employee_project`
+--
+
+CREATE TABLE `employee_project` (
+  `employee_id` int(11) NOT NULL,
+  `project_id` int(11) NOT NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8 COLLATE=utf8_polish_ci;
+
+-- --------------------------------------------------------
+
+--
+-- Struktura tabeli dla tabeli `project_technology`
+--
+
+CREATE TABLE `project_technology` (
+  `project_id` int(11) NOT NULL,
+  `technology_id` int(11) NOT NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8 COLLATE=utf8_polish_ci;
+
+-- --------------------------------------------------------
+
+--
+-- Struktura tabeli dla tabeli `employee_technology`
+--
+
+CREATE TABLE `employee_technology` (
+  `employee_id` int(11) NOT NULL,
+  `technology_id` int(11) NOT NULL,
+  `skill_level` tinyint(1) NOT NULL
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8 COLLATE=utf8_polish_ci;
 
 --
@@ -129,49 +225,103 @@ CREATE TABLE `technology` (
 -- Indeksy dla tabeli `address`
 --
 ALTER TABLE `address`
-  ADD PRIMARY KEY (`address_id`);
+  ADD PRIMARY KEY (`address_id`),
+  ADD KEY `run_id` (`run_id`),
+  ADD SPATIAL KEY `location` (`location`);
 
 --
 -- Indeksy dla tabeli `client`
 --
 ALTER TABLE `client`
-  ADD PRIMARY KEY (`client_id`);
+  ADD PRIMARY KEY (`client_id`),
+  ADD KEY `run_id` (`run_id`);
 
 --
 -- Indeksy dla tabeli `contract`
 --
 ALTER TABLE `contract`
-  ADD PRIMARY KEY (`contract_id`);
+  ADD PRIMARY KEY (`contract_id`),
+  ADD KEY `run_id` (`run_id`),
+  ADD KEY `employee_id` (`employee_id`);
 
 --
 -- Indeksy dla tabeli `employee`
 --
 ALTER TABLE `employee`
-  ADD PRIMARY KEY (`employee_id`);
+  ADD PRIMARY KEY (`employee_id`),
+  ADD KEY `run_id` (`run_id`),
+  ADD KEY `manager_id` (`manager_id`);
 
 --
 -- Indeksy dla tabeli `payment`
 --
 ALTER TABLE `payment`
-  ADD PRIMARY KEY (`payment_id`);
+  ADD PRIMARY KEY (`payment_id`),
+  ADD KEY `run_id` (`run_id`);
 
 --
 -- Indeksy dla tabeli `project`
 --
 ALTER TABLE `project`
-  ADD PRIMARY KEY (`project_id`);
+  ADD PRIMARY KEY (`project_id`),
+  ADD KEY `run_id` (`run_id`);
 
 --
 -- Indeksy dla tabeli `task`
 --
 ALTER TABLE `task`
-  ADD PRIMARY KEY (`task_id`);
+  ADD PRIMARY KEY (`task_id`),
+  ADD KEY `run_id` (`run_id`);
 
 --
 -- Indeksy dla tabeli `technology`
 --
 ALTER TABLE `technology`
-  ADD PRIMARY KEY (`technology_id`);
+  ADD PRIMARY KEY (`technology_id`),
+  ADD KEY `run_id` (`run_id`);
+
+--
+-- Indeksy dla tabeli `employee_project`
+--
+ALTER TABLE `employee_project`
+  ADD PRIMARY KEY (`employee_id`,`project_id`),
+  ADD KEY `project_id` (`project_id`);
+
+--
+-- Indeksy dla tabeli `project_technology`
+--
+ALTER TABLE `project_technology`
+  ADD PRIMARY KEY (`project_id`,`technology_id`),
+  ADD KEY `technology_id` (`technology_id`);
+
+--
+-- Indeksy dla tabeli `employee_technology`
+--
+ALTER TABLE `employee_technology`
+  ADD PRIMARY KEY (`employee_id`,`technology_id`),
+  ADD KEY `technology_id` (`technology_id`);
+
+--
+-- Indeksy dla tabeli `generation_runs`
+--
+ALTER TABLE `generation_runs`
+  ADD PRIMARY KEY (`run_id`),
+  ADD KEY `started_at` (`started_at`);
+
+--
+-- Indeksy dla tabeli `payment_event`
+--
+ALTER TABLE `payment_event`
+  ADD PRIMARY KEY (`payment_event_id`),
+  ADD KEY `occurred_at` (`occurred_at`);
+
+--
+-- Indeksy dla tabeli `task_status_event`
+--
+ALTER TABLE `task_status_event`
+  ADD PRIMARY KEY (`task_status_event_id`),
+  ADD KEY `task_ref` (`task_ref`),
+  ADD KEY `occurred_at` (`occurred_at`);
 
 --
 -- AUTO_INCREMENT for dumped tables
@@ -224,6 +374,24 @@ ALTER TABLE `task`
 --
 ALTER TABLE `technology`
   MODIFY `technology_id` int(11) NOT NULL AUTO_INCREMENT, AUTO_INCREMENT=1181;
+
+--
+-- AUTO_INCREMENT for table `payment_event`
+--
+ALTER TABLE `payment_event`
+  MODIFY `payment_event_id` int(11) NOT NULL AUTO_INCREMENT;
+
+--
+-- AUTO_INCREMENT for table `task_status_event`
+--
+ALTER TABLE `task_status_event`
+  MODIFY `task_status_event_id` int(11) NOT NULL AUTO_INCREMENT;
+
+--
+-- Ograniczenia dla tabeli `employee`
+--
+ALTER TABLE `employee`
+  ADD CONSTRAINT `employee_manager` FOREIGN KEY (`manager_id`) REFERENCES `employee` (`employee_id`) ON DELETE SET NULL;
 COMMIT;
 
 /*!40101 SET CHARACTER_SET_CLIENT=@OLD_CHARACTER_SET_CLIENT */;
@@ -1,10 +1,105 @@
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-};
-
-pub fn load_from_file(filename: &str) -> Vec<String> {
-    let file = File::open(filename).expect("Failed to open names file");
-    let reader = BufReader::new(file);
-    reader.lines().filter_map(Result::ok).collect()
+use std::{collections::HashMap, fs, path::Path, sync::OnceLock};
+
+/// Word lists compiled into the binary, used unless the data pack directory overrides them.
+const EMBEDDED_WORD_LISTS: [(&str, &str); 14] = [
+    ("names.txt", include_str!("names.txt")),
+    ("last_names.txt", include_str!("last_names.txt")),
+    // Cities as `name;voivodeship;latitude;longitude;radius_km;postal_prefixes`, e.g.
+    // `Kraków;małopolskie;50.0647;19.9450;9;30-31`.
+    ("gazetteer.txt", include_str!("gazetteer.txt")),
+    ("streets.txt", include_str!("streets.txt")),
+    ("lorem.txt", include_str!("lorem.txt")),
+    ("adjectives.txt", include_str!("adjectives.txt")),
+    ("nouns.txt", include_str!("nouns.txt")),
+    ("verbs.txt", include_str!("verbs.txt")),
+    ("objects.txt", include_str!("objects.txt")),
+    (
+        "technology_prefixes.txt",
+        include_str!("technology_prefixes.txt"),
+    ),
+    ("technology_terms.txt", include_str!("technology_terms.txt")),
+    // Corpora for Markov text, one sentence per line.
+    (
+        "project_descriptions.txt",
+        include_str!("project_descriptions.txt"),
+    ),
+    (
+        "task_descriptions.txt",
+        include_str!("task_descriptions.txt"),
+    ),
+    (
+        "technology_descriptions.txt",
+        include_str!("technology_descriptions.txt"),
+    ),
+];
+
+static DICTIONARY: OnceLock<HashMap<&'static str, Vec<String>>> = OnceLock::new();
+
+fn parse_word_list(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn read_override(dir: &Path, name: &str) -> Result<Option<Vec<String>>, String> {
+    let path = dir.join(name);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read word list {}: {}", path.display(), e))?;
+    let words = parse_word_list(&content);
+    if words.is_empty() {
+        return Err(format!("Word list {} is empty", path.display()));
+    }
+    Ok(Some(words))
+}
+
+/// Loads the word lists into the shared dictionary. Lists found in `override_dir`
+/// replace the embedded ones; lists it does not contain keep their embedded content.
+/// Only the first successful call has an effect.
+pub fn load_dictionary(override_dir: Option<&Path>) -> Result<(), Vec<String>> {
+    if let Some(dir) = override_dir {
+        if !dir.is_dir() {
+            return Err(vec![format!(
+                "Data pack directory {} does not exist",
+                dir.display()
+            )]);
+        }
+    }
+
+    let mut errors = Vec::new();
+    let mut dictionary = HashMap::new();
+    for (name, embedded) in EMBEDDED_WORD_LISTS {
+        let words = match override_dir.map(|dir| read_override(dir, name)) {
+            Some(Ok(Some(words))) => words,
+            Some(Err(e)) => {
+                errors.push(e);
+                continue;
+            }
+            Some(Ok(None)) | None => parse_word_list(embedded),
+        };
+        dictionary.insert(name, words);
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    let _ = DICTIONARY.set(dictionary);
+    Ok(())
+}
+
+/// Returns a word list from the dictionary, falling back to the embedded lists
+/// when `load_dictionary` was never called.
+pub fn word_list(name: &str) -> &'static [String] {
+    let dictionary = DICTIONARY.get_or_init(|| {
+        EMBEDDED_WORD_LISTS
+            .iter()
+            .map(|(name, embedded)| (*name, parse_word_list(embedded)))
+            .collect()
+    });
+    dictionary.get(name).map(Vec::as_slice).unwrap_or_default()
 }